@@ -0,0 +1,263 @@
+//! Reference implementation of the lighting in `pbr.wgsl`, the functions match the ones of
+//! the shader.
+
+use std::f32::consts::PI;
+
+use glam::Vec3;
+
+/// Reflectance of dielectrics at normal incidence.
+pub const DIELECTRIC_F0: f32 = 0.04;
+
+/// Lower bound of the roughness, smoother surfaces give specular highlights too small to
+/// be sampled.
+pub const MIN_ROUGHNESS: f32 = 0.045;
+
+/// Surface parameters of a fragment, like [`StandardMaterial`](crate::material::StandardMaterial)
+/// after sampling the textures.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Surface {
+    pub base_color: Vec3,
+    pub metallic: f32,
+    /// Perceptual roughness, squared before use.
+    pub roughness: f32,
+}
+
+impl Surface {
+    #[inline]
+    pub fn diffuse_color(&self) -> Vec3 {
+        self.base_color * (1.0 - self.metallic)
+    }
+
+    /// Reflectance at normal incidence, metals tint their reflections.
+    #[inline]
+    pub fn f0(&self) -> Vec3 {
+        Vec3::splat(DIELECTRIC_F0).lerp(self.base_color, self.metallic)
+    }
+
+    #[inline]
+    pub fn alpha(&self) -> f32 {
+        let roughness = self.roughness.clamp(MIN_ROUGHNESS, 1.0);
+        roughness * roughness
+    }
+}
+
+/// GGX normal distribution.
+#[inline]
+pub fn distribution_ggx(n_dot_h: f32, alpha: f32) -> f32 {
+    let alpha2 = alpha * alpha;
+    let d = n_dot_h * n_dot_h * (alpha2 - 1.0) + 1.0;
+
+    alpha2 / (PI * d * d)
+}
+
+/// Height correlated Smith visibility, the geometry term divided by `4 n.l n.v`.
+#[inline]
+pub fn visibility_smith_ggx(n_dot_l: f32, n_dot_v: f32, alpha: f32) -> f32 {
+    let alpha2 = alpha * alpha;
+    let ggx_v = n_dot_l * (n_dot_v * n_dot_v * (1.0 - alpha2) + alpha2).sqrt();
+    let ggx_l = n_dot_v * (n_dot_l * n_dot_l * (1.0 - alpha2) + alpha2).sqrt();
+
+    0.5 / (ggx_v + ggx_l)
+}
+
+/// Schlick's approximation of the fresnel reflectance.
+#[inline]
+pub fn fresnel_schlick(f0: Vec3, v_dot_h: f32) -> Vec3 {
+    f0 + (Vec3::ONE - f0) * (1.0 - v_dot_h).clamp(0.0, 1.0).powi(5)
+}
+
+/// Falloff of a point light at `distance`, inverse square windowed to reach zero at
+/// `range`.
+#[inline]
+pub fn attenuation(distance: f32, range: f32) -> f32 {
+    let window = (1.0 - (distance / range).powi(4)).clamp(0.0, 1.0);
+
+    window * window / (distance * distance).max(0.0001)
+}
+
+/// Falloff of a spot light at an angle with cosine `cos_angle` from its axis, one inside
+/// the inner cone and zero outside the outer one.
+#[inline]
+pub fn spot_cone(cos_angle: f32, inner_cos: f32, outer_cos: f32) -> f32 {
+    let t = ((cos_angle - outer_cos) / (inner_cos - outer_cos).max(0.0001)).clamp(0.0, 1.0);
+
+    t * t
+}
+
+/// Cook-Torrance BRDF with a lambertian diffuse term. `normal`, `view` and `light` are
+/// normalized and point away from the surface, the result is multiplied with the light
+/// radiance and `n.l`.
+#[inline]
+pub fn brdf(surface: &Surface, normal: Vec3, view: Vec3, light: Vec3) -> Vec3 {
+    let half = (view + light).normalize();
+
+    let n_dot_l = normal.dot(light).max(0.0);
+    let n_dot_v = normal.dot(view).max(0.0001);
+    let n_dot_h = normal.dot(half).max(0.0);
+    let v_dot_h = view.dot(half).max(0.0);
+
+    let alpha = surface.alpha();
+
+    let f = fresnel_schlick(surface.f0(), v_dot_h);
+    let d = distribution_ggx(n_dot_h, alpha);
+    let v = visibility_smith_ggx(n_dot_l, n_dot_v, alpha);
+
+    let specular = f * d * v;
+    let diffuse = (Vec3::ONE - f) * surface.diffuse_color() / PI;
+
+    diffuse + specular
+}
+
+/// Light reflected towards `view` from a point light at `light_position`.
+#[inline]
+pub fn point_light(
+    surface: &Surface,
+    position: Vec3,
+    normal: Vec3,
+    view: Vec3,
+    light_position: Vec3,
+    radiance: Vec3,
+    range: f32,
+) -> Vec3 {
+    let to_light = light_position - position;
+    let distance = to_light.length();
+    let light = to_light / distance;
+
+    let n_dot_l = normal.dot(light).max(0.0);
+
+    brdf(surface, normal, view, light) * radiance * attenuation(distance, range) * n_dot_l
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 0.001;
+
+    #[test]
+    fn fresnel_is_f0_at_normal_incidence() {
+        let f0 = Vec3::new(0.9, 0.6, 0.3);
+
+        assert!(fresnel_schlick(f0, 1.0).abs_diff_eq(f0, EPSILON));
+        assert!(fresnel_schlick(f0, 0.0).abs_diff_eq(Vec3::ONE, EPSILON));
+    }
+
+    #[test]
+    fn distribution_is_normalized() {
+        // integral of D(h) n.h over the hemisphere
+        for &alpha in &[0.1, 0.25, 0.5, 1.0] {
+            let steps = 20000;
+            let mut integral = 0.0;
+
+            for i in 0..steps {
+                let theta = (i as f32 + 0.5) / steps as f32 * PI / 2.0;
+                let d_theta = PI / 2.0 / steps as f32;
+
+                integral += distribution_ggx(theta.cos(), alpha)
+                    * theta.cos()
+                    * theta.sin()
+                    * d_theta
+                    * 2.0
+                    * PI;
+            }
+
+            assert!((integral - 1.0).abs() < 0.01, "{} for alpha {}", integral, alpha);
+        }
+    }
+
+    #[test]
+    fn rough_dielectric_at_normal_incidence() {
+        let surface = Surface {
+            base_color: Vec3::ONE,
+            metallic: 0.0,
+            roughness: 1.0,
+        };
+
+        // with alpha 1 the distribution is 1 / pi and the visibility 1 / 4
+        let expected = (1.0 - DIELECTRIC_F0) / PI + DIELECTRIC_F0 / PI * 0.25;
+        let value = brdf(&surface, Vec3::Z, Vec3::Z, Vec3::Z);
+
+        assert!(value.abs_diff_eq(Vec3::splat(expected), EPSILON), "{}", value);
+    }
+
+    #[test]
+    fn metals_have_no_diffuse() {
+        let surface = Surface {
+            base_color: Vec3::new(1.0, 0.8, 0.4),
+            metallic: 1.0,
+            roughness: 0.3,
+        };
+
+        let light = Vec3::new(1.0, 0.0, 1.0).normalize();
+        let view = Vec3::new(-1.0, 0.0, 1.0).normalize();
+        let half = (light + view).normalize();
+
+        let alpha = surface.alpha();
+        let specular = fresnel_schlick(surface.base_color, view.dot(half))
+            * distribution_ggx(Vec3::Z.dot(half), alpha)
+            * visibility_smith_ggx(Vec3::Z.dot(light), Vec3::Z.dot(view), alpha);
+
+        let value = brdf(&surface, Vec3::Z, view, light);
+
+        assert!(value.abs_diff_eq(specular, EPSILON), "{} != {}", value, specular);
+    }
+
+    #[test]
+    fn brdf_is_reciprocal() {
+        let surface = Surface {
+            base_color: Vec3::new(0.5, 0.2, 0.1),
+            metallic: 0.4,
+            roughness: 0.6,
+        };
+
+        let a = Vec3::new(0.3, 0.2, 1.0).normalize();
+        let b = Vec3::new(-0.7, 0.1, 0.4).normalize();
+
+        let ab = brdf(&surface, Vec3::Z, a, b);
+        let ba = brdf(&surface, Vec3::Z, b, a);
+
+        assert!(ab.abs_diff_eq(ba, EPSILON), "{} != {}", ab, ba);
+    }
+
+    #[test]
+    fn attenuation_fades_out_at_range() {
+        assert!((attenuation(2.0, f32::INFINITY) - 0.25).abs() < EPSILON);
+        assert!(attenuation(1.0, 10.0) > attenuation(2.0, 10.0));
+        assert_eq!(attenuation(10.0, 10.0), 0.0);
+        assert_eq!(attenuation(12.0, 10.0), 0.0);
+    }
+
+    #[test]
+    fn spot_cone_fades_between_the_angles() {
+        let inner = 0.2f32.cos();
+        let outer = 0.4f32.cos();
+
+        assert_eq!(spot_cone(1.0, inner, outer), 1.0);
+        assert_eq!(spot_cone(0.2f32.cos(), inner, outer), 1.0);
+        assert_eq!(spot_cone(0.5f32.cos(), inner, outer), 0.0);
+
+        let middle = spot_cone(0.3f32.cos(), inner, outer);
+        assert!(middle > 0.0 && middle < 1.0, "{}", middle);
+    }
+
+    #[test]
+    fn lights_behind_the_surface_add_nothing() {
+        let surface = Surface {
+            base_color: Vec3::ONE,
+            metallic: 0.0,
+            roughness: 0.5,
+        };
+
+        let light = point_light(
+            &surface,
+            Vec3::ZERO,
+            Vec3::Z,
+            Vec3::new(1.0, 0.0, 1.0).normalize(),
+            Vec3::new(0.0, 0.0, -1.0),
+            Vec3::ONE,
+            10.0,
+        );
+
+        assert_eq!(light, Vec3::ZERO);
+    }
+}
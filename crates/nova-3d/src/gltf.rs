@@ -0,0 +1,749 @@
+use std::collections::BTreeMap;
+
+use ::gltf::{
+    buffer::Source,
+    camera::Projection,
+    image::Source as ImageSource,
+    khr_lights_punctual::Kind,
+    material::AlphaMode as GltfAlphaMode,
+    mesh::Mode,
+    texture::{MagFilter, WrappingMode},
+    Document, Gltf, Node,
+};
+use glam::{Quat, Vec2, Vec3};
+use nova_assets::{
+    loader::{AssetError, AssetLoader, LoadContext},
+    Assets, Handle,
+};
+use nova_core::{systems::Runnable, Entity, IntoQuery, SystemBuilder, World};
+use nova_render::{
+    camera::Camera,
+    color::Color,
+    component::MeshInstance,
+    image::Image,
+    light::{DirectionalLight, PointLight, SpotLight},
+    mesh::Mesh,
+    mesh::MeshData,
+};
+use nova_transform::component::{GlobalTransform, Parent, Transform};
+use nova_wgpu::{AddressMode, FilterMode};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    material::{AlphaMode, StandardMaterial},
+    Vertex3d, PBR_PIPELINE_HANDLE,
+};
+
+/// A mesh of a node with its material.
+#[derive(Clone, Debug)]
+pub struct GltfPrimitive {
+    pub mesh: Handle<MeshData>,
+    pub material: Handle<StandardMaterial>,
+}
+
+#[derive(Clone, Debug)]
+pub enum GltfLight {
+    Point(PointLight),
+    Directional(DirectionalLight),
+    Spot(SpotLight),
+}
+
+#[derive(Clone, Debug)]
+pub struct GltfNode {
+    pub name: String,
+    pub transform: Transform,
+    /// Index of the parent node, parents come before their children.
+    pub parent: Option<usize>,
+    pub primitives: Vec<GltfPrimitive>,
+    pub camera: Option<Camera>,
+    pub light: Option<GltfLight>,
+}
+
+/// The node hierarchy of a glTF scene, meshes and materials are labeled assets of the file
+/// so they can be referred to from saved scenes.
+#[derive(Clone, Debug, Default)]
+pub struct GltfScene {
+    pub nodes: Vec<GltfNode>,
+}
+
+impl GltfScene {
+    /// Spawns an entity for every node, root nodes are parented to `parent`. Returns the
+    /// entities by node index.
+    #[inline]
+    pub fn spawn(&self, world: &mut World, parent: Option<Entity>) -> Vec<Entity> {
+        let mut entities: Vec<Entity> = Vec::with_capacity(self.nodes.len());
+
+        for node in &self.nodes {
+            let entity = world.push((
+                node.name.clone(),
+                node.transform.clone(),
+                GlobalTransform::default(),
+            ));
+
+            let mut entry = world.entry(entity).unwrap();
+
+            if let Some(parent) = node.parent.map(|parent| entities[parent]).or(parent) {
+                entry.add_component(Parent(parent));
+            }
+
+            if let Some(camera) = &node.camera {
+                entry.add_component(camera.clone());
+            }
+
+            match &node.light {
+                Some(GltfLight::Point(light)) => entry.add_component(light.clone()),
+                Some(GltfLight::Directional(light)) => entry.add_component(light.clone()),
+                Some(GltfLight::Spot(light)) => entry.add_component(light.clone()),
+                None => {}
+            }
+
+            match node.primitives.as_slice() {
+                [] => {}
+                [primitive] => {
+                    entry.add_component(mesh_instance(primitive));
+                    entry.add_component(primitive.material.clone());
+                }
+                // an entity has a single mesh, so every primitive gets a child
+                primitives => {
+                    for (i, primitive) in primitives.iter().enumerate() {
+                        world.push((
+                            format!("{} {}", node.name, i),
+                            Transform::IDENTITY,
+                            GlobalTransform::default(),
+                            Parent(entity),
+                            mesh_instance(primitive),
+                            primitive.material.clone(),
+                        ));
+                    }
+                }
+            }
+
+            entities.push(entity);
+        }
+
+        entities
+    }
+}
+
+#[inline]
+fn mesh_instance(primitive: &GltfPrimitive) -> MeshInstance {
+    MeshInstance {
+        mesh_data: primitive.mesh.clone(),
+        pipeline: PBR_PIPELINE_HANDLE,
+        bindings: BTreeMap::new(),
+    }
+}
+
+/// Spawns the nodes of the scene as children of the entity once the scene is loaded, then
+/// removes itself.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SpawnScene(pub Handle<GltfScene>);
+
+pub fn spawn_scene_system() -> impl Runnable {
+    SystemBuilder::new("spawn_scene_system")
+        .read_resource::<Assets<GltfScene>>()
+        .with_query(<(Entity, &SpawnScene)>::query())
+        .build(|commands, world, scenes, query| {
+            for (entity, spawn) in query.iter(world) {
+                let scene = match scenes.get(&spawn.0) {
+                    Some(scene) => scene.clone(),
+                    None => continue,
+                };
+
+                let entity = *entity;
+                commands.exec_mut(move |world, _resources| {
+                    scene.spawn(world, Some(entity));
+                });
+                commands.remove_component::<SpawnScene>(entity);
+            }
+        })
+}
+
+/// Reads the buffers of the file, `blob` is the binary chunk of `.glb` files.
+#[inline]
+fn read_buffers(
+    document: &Document,
+    blob: Option<Vec<u8>>,
+    ctx: &mut LoadContext<'_>,
+) -> Result<Vec<Vec<u8>>, AssetError> {
+    let mut blob = blob;
+    let mut buffers = Vec::new();
+
+    for buffer in document.buffers() {
+        let data = match buffer.source() {
+            Source::Bin => blob.take().ok_or("missing binary chunk")?,
+            Source::Uri(uri) if uri.starts_with("data:") => {
+                let data = uri
+                    .split_once(";base64,")
+                    .ok_or("only base64 data uris are supported")?
+                    .1;
+
+                base64::decode(data)?
+            }
+            Source::Uri(uri) => {
+                let path = ctx.relative(uri);
+                ctx.read(path)
+                    .map_err(|err| format!("'{}': {}", uri, err))?
+            }
+        };
+
+        if data.len() < buffer.length() {
+            return Err(format!("buffer {} is too short", buffer.index()).into());
+        }
+
+        buffers.push(data);
+    }
+
+    Ok(buffers)
+}
+
+#[inline]
+fn load_primitive(
+    primitive: &::gltf::Primitive<'_>,
+    buffers: &[Vec<u8>],
+    scale: f32,
+    generate_normals: bool,
+) -> Result<Mesh<Vertex3d>, AssetError> {
+    let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+
+    let mut mesh = Mesh::default();
+
+    mesh.vertices = reader
+        .read_positions()
+        .ok_or("primitive without positions")?
+        .map(|position| Vertex3d {
+            position: Vec3::from(position) * scale,
+            normal: Vec3::ZERO,
+            uv: Vec2::ZERO,
+            color: Color::WHITE,
+        })
+        .collect();
+
+    let normals = reader.read_normals().filter(|_| !generate_normals);
+    let has_normals = normals.is_some();
+
+    for (vertex, normal) in mesh.vertices.iter_mut().zip(normals.into_iter().flatten()) {
+        vertex.normal = normal.into();
+    }
+
+    if let Some(uvs) = reader.read_tex_coords(0) {
+        for (vertex, uv) in mesh.vertices.iter_mut().zip(uvs.into_f32()) {
+            vertex.uv = uv.into();
+        }
+    }
+
+    if let Some(colors) = reader.read_colors(0) {
+        for (vertex, color) in mesh.vertices.iter_mut().zip(colors.into_rgba_f32()) {
+            vertex.color = color.into();
+        }
+    }
+
+    mesh.indices = match reader.read_indices() {
+        Some(indices) => indices.into_u32().collect(),
+        None => (0..mesh.vertices.len() as u32).collect(),
+    };
+
+    if !has_normals {
+        mesh.compute_smooth_normals();
+    }
+
+    Ok(mesh)
+}
+
+#[inline]
+fn load_camera(camera: &::gltf::Camera<'_>) -> Camera {
+    match camera.projection() {
+        Projection::Perspective(perspective) => Camera::Perspective {
+            fov: perspective.yfov(),
+            // the aspect is set to the one of the target when rendering
+            aspect: perspective.aspect_ratio().unwrap_or(1.0),
+            near: perspective.znear(),
+        },
+        Projection::Orthographic(orthographic) => Camera::Orthographic {
+            left: -orthographic.xmag(),
+            bottom: -orthographic.ymag(),
+            right: orthographic.xmag(),
+            top: orthographic.ymag(),
+            near: orthographic.znear(),
+            far: orthographic.zfar(),
+        },
+    }
+}
+
+/// Builds the [`GltfScene`] while walking the node tree.
+struct SceneBuilder<'a, 'b> {
+    ctx: &'a mut LoadContext<'b>,
+    buffers: Vec<Vec<u8>>,
+    scale: f32,
+    generate_normals: bool,
+    /// Handles of the meshes by glTF mesh index.
+    meshes: Vec<Option<Vec<GltfPrimitive>>>,
+    materials: Vec<Option<Handle<StandardMaterial>>>,
+    default_material: Option<Handle<StandardMaterial>>,
+    textures: Vec<Option<Handle<Image>>>,
+    mipmaps: bool,
+    nodes: Vec<GltfNode>,
+}
+
+impl<'a, 'b> SceneBuilder<'a, 'b> {
+    /// Decodes the image of the texture, a texture used both as color and as data gets the
+    /// color space of its first use.
+    #[inline]
+    fn texture(
+        &mut self,
+        texture: &::gltf::Texture<'_>,
+        srgb: bool,
+    ) -> Result<Handle<Image>, AssetError> {
+        if let Some(handle) = &self.textures[texture.index()] {
+            return Ok(handle.clone());
+        }
+
+        let bytes = match texture.source().source() {
+            ImageSource::View { view, .. } => {
+                let buffer = &self.buffers[view.buffer().index()];
+                buffer[view.offset()..view.offset() + view.length()].to_vec()
+            }
+            ImageSource::Uri { uri, .. } if uri.starts_with("data:") => {
+                let data = uri
+                    .split_once(";base64,")
+                    .ok_or("only base64 data uris are supported")?
+                    .1;
+
+                base64::decode(data)?
+            }
+            ImageSource::Uri { uri, .. } => {
+                let path = self.ctx.relative(uri);
+                self.ctx
+                    .read(path)
+                    .map_err(|err| format!("'{}': {}", uri, err))?
+            }
+        };
+
+        let mut image = Image::decode(&bytes, srgb)?;
+
+        let sampler = texture.sampler();
+        // the image has one address mode for both axes
+        image.address_mode = match sampler.wrap_s() {
+            WrappingMode::ClampToEdge => AddressMode::ClampToEdge,
+            WrappingMode::MirroredRepeat => AddressMode::MirrorRepeat,
+            WrappingMode::Repeat => AddressMode::Repeat,
+        };
+        image.filter = match sampler.mag_filter() {
+            Some(MagFilter::Nearest) => FilterMode::Nearest,
+            Some(MagFilter::Linear) | None => FilterMode::Linear,
+        };
+
+        if self.mipmaps {
+            image.generate_mipmaps();
+        }
+
+        let label = format!("Texture{}", texture.index());
+        let handle = self.ctx.add_labeled(&label, image);
+        self.textures[texture.index()] = Some(handle.clone());
+
+        Ok(handle)
+    }
+
+    #[inline]
+    fn load_material(
+        &mut self,
+        material: &::gltf::Material<'_>,
+    ) -> Result<StandardMaterial, AssetError> {
+        let pbr = material.pbr_metallic_roughness();
+        let [r, g, b, a] = pbr.base_color_factor();
+        let [er, eg, eb] = material.emissive_factor();
+
+        let base_color_texture = match pbr.base_color_texture() {
+            Some(info) => Some(self.texture(&info.texture(), true)?),
+            None => None,
+        };
+
+        let metallic_roughness_texture = match pbr.metallic_roughness_texture() {
+            Some(info) => Some(self.texture(&info.texture(), false)?),
+            None => None,
+        };
+
+        let normal_texture = match material.normal_texture() {
+            Some(normal) => Some(self.texture(&normal.texture(), false)?),
+            None => None,
+        };
+
+        Ok(StandardMaterial {
+            base_color: Color::rgba(r, g, b, a),
+            base_color_texture,
+            metallic: pbr.metallic_factor(),
+            roughness: pbr.roughness_factor(),
+            metallic_roughness_texture,
+            normal_texture,
+            emissive: Color::rgb(er, eg, eb),
+            alpha_mode: match material.alpha_mode() {
+                GltfAlphaMode::Opaque => AlphaMode::Opaque,
+                GltfAlphaMode::Mask => AlphaMode::Mask(material.alpha_cutoff().unwrap_or(0.5)),
+                GltfAlphaMode::Blend => AlphaMode::Blend,
+            },
+            double_sided: material.double_sided(),
+        })
+    }
+
+    #[inline]
+    fn material(
+        &mut self,
+        material: &::gltf::Material<'_>,
+    ) -> Result<Handle<StandardMaterial>, AssetError> {
+        let index = match material.index() {
+            Some(index) => index,
+            None => {
+                let ctx = &mut *self.ctx;
+
+                return Ok(self
+                    .default_material
+                    .get_or_insert_with(|| {
+                        ctx.add_labeled("DefaultMaterial", StandardMaterial::default())
+                    })
+                    .clone());
+            }
+        };
+
+        if let Some(handle) = &self.materials[index] {
+            return Ok(handle.clone());
+        }
+
+        let label = format!("Material{}", index);
+        let material = self.load_material(material)?;
+        let handle = self.ctx.add_labeled(&label, material);
+        self.materials[index] = Some(handle.clone());
+
+        Ok(handle)
+    }
+
+    #[inline]
+    fn mesh(&mut self, mesh: &::gltf::Mesh<'_>) -> Result<Vec<GltfPrimitive>, AssetError> {
+        if let Some(primitives) = &self.meshes[mesh.index()] {
+            return Ok(primitives.clone());
+        }
+
+        let mut primitives = Vec::new();
+
+        for primitive in mesh.primitives() {
+            if primitive.mode() != Mode::Triangles {
+                log::warn!(
+                    "skipped primitive {} of mesh {} of '{}', only triangles are supported",
+                    primitive.index(),
+                    mesh.index(),
+                    self.ctx.path().display(),
+                );
+
+                continue;
+            }
+
+            let data =
+                load_primitive(&primitive, &self.buffers, self.scale, self.generate_normals)?;
+            let label = format!("Mesh{}/Primitive{}", mesh.index(), primitive.index());
+
+            primitives.push(GltfPrimitive {
+                mesh: self.ctx.add_labeled(&label, MeshData::from(data)),
+                material: self.material(&primitive.material())?,
+            });
+        }
+
+        self.meshes[mesh.index()] = Some(primitives.clone());
+
+        Ok(primitives)
+    }
+
+    #[inline]
+    fn node(&mut self, node: &Node<'_>, parent: Option<usize>) -> Result<(), AssetError> {
+        let (translation, rotation, scale) = node.transform().decomposed();
+
+        let light = node.light().map(|light| {
+            let [r, g, b] = light.color();
+            let color = Color::rgb(r, g, b);
+            // lights without a range reach infinitely far in glTF
+            let range = light.range().unwrap_or(f32::INFINITY);
+
+            match light.kind() {
+                Kind::Point => GltfLight::Point(PointLight {
+                    color,
+                    intensity: light.intensity(),
+                    range,
+                    ..Default::default()
+                }),
+                Kind::Directional => GltfLight::Directional(DirectionalLight {
+                    color,
+                    intensity: light.intensity(),
+                    ..Default::default()
+                }),
+                Kind::Spot {
+                    inner_cone_angle,
+                    outer_cone_angle,
+                } => GltfLight::Spot(SpotLight {
+                    color,
+                    intensity: light.intensity(),
+                    range,
+                    inner_angle: inner_cone_angle,
+                    outer_angle: outer_cone_angle,
+                }),
+            }
+        });
+
+        let primitives = match node.mesh() {
+            Some(mesh) => self.mesh(&mesh)?,
+            None => Vec::new(),
+        };
+
+        let index = self.nodes.len();
+
+        self.nodes.push(GltfNode {
+            name: node
+                .name()
+                .map_or_else(|| format!("Node{}", node.index()), String::from),
+            transform: Transform {
+                translation: Vec3::from(translation) * self.scale,
+                rotation: Quat::from_array(rotation),
+                scale: scale.into(),
+            },
+            parent,
+            primitives,
+            camera: node.camera().map(|camera| load_camera(&camera)),
+            light,
+        });
+
+        for child in node.children() {
+            self.node(&child, Some(index))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Loads the default scene of `.gltf` and `.glb` files as a [`GltfScene`].
+///
+/// Primitives are labeled `Mesh{mesh}/Primitive{primitive}`, materials
+/// `Material{material}` and their images `Texture{texture}`, see
+/// [`label_path`](nova_assets::loader::label_path). The
+/// [`MeshSettings`](nova_assets::meta::MeshSettings) of the asset scale the positions, and
+/// normals are generated if asked or missing. Images get mipmaps if the
+/// [`TextureSettings`](nova_assets::meta::TextureSettings) ask for them.
+pub struct GltfLoader;
+
+impl AssetLoader for GltfLoader {
+    type Asset = GltfScene;
+
+    #[inline]
+    fn extensions(&self) -> &[&str] {
+        &["gltf", "glb"]
+    }
+
+    #[inline]
+    fn load(&self, bytes: &[u8], ctx: &mut LoadContext<'_>) -> Result<GltfScene, AssetError> {
+        let Gltf { document, blob } = Gltf::from_slice(bytes)?;
+        let buffers = read_buffers(&document, blob, ctx)?;
+        let settings = ctx.settings().mesh.clone();
+        let mipmaps = ctx.settings().texture.mipmaps;
+
+        let scene = match document
+            .default_scene()
+            .or_else(|| document.scenes().next())
+        {
+            Some(scene) => scene,
+            None => return Ok(GltfScene::default()),
+        };
+
+        let mut builder = SceneBuilder {
+            ctx,
+            buffers,
+            scale: settings.scale,
+            generate_normals: settings.generate_normals,
+            meshes: vec![None; document.meshes().len()],
+            materials: vec![None; document.materials().len()],
+            default_material: None,
+            textures: vec![None; document.textures().len()],
+            mipmaps,
+            nodes: Vec::new(),
+        };
+
+        for node in scene.nodes() {
+            builder.node(&node, None)?;
+        }
+
+        Ok(GltfScene {
+            nodes: builder.nodes,
+        })
+    }
+}
+
+/// Spawns an entity with a [`SpawnScene`] for glTF files dropped into the editor viewport.
+#[cfg(feature = "editor")]
+pub fn spawn_gltf(
+    world: &mut World,
+    resources: &mut nova_core::Resources,
+    path: &std::path::Path,
+) -> Entity {
+    let server = resources.get::<nova_assets::server::AssetServer>().unwrap();
+    let name = path
+        .file_stem()
+        .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+
+    world.push((
+        name,
+        Transform::IDENTITY,
+        GlobalTransform::default(),
+        SpawnScene(server.load(path)),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        path::PathBuf,
+        time::{Duration, Instant},
+    };
+
+    use nova_assets::server::{AssetServer, LoadState};
+    use nova_core::{app, AppBuilder, Resources};
+
+    use super::*;
+
+    /// Writes a glTF file with a red triangle named `Triangle`, returns its directory.
+    fn write_triangle() -> PathBuf {
+        let positions: [f32; 9] = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+        let buffer = base64::encode(bytemuck::cast_slice(&positions));
+
+        let gltf = format!(
+            r#"{{
+                "asset": {{ "version": "2.0" }},
+                "scene": 0,
+                "scenes": [{{ "nodes": [0] }}],
+                "nodes": [{{ "name": "Triangle", "mesh": 0 }}],
+                "meshes": [{{
+                    "primitives": [{{ "attributes": {{ "POSITION": 0 }}, "material": 0 }}]
+                }}],
+                "materials": [{{ "pbrMetallicRoughness": {{ "baseColorFactor": [1, 0, 0, 1] }} }}],
+                "buffers": [{{
+                    "byteLength": 36,
+                    "uri": "data:application/octet-stream;base64,{}"
+                }}],
+                "bufferViews": [{{ "buffer": 0, "byteLength": 36 }}],
+                "accessors": [{{
+                    "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+                    "min": [0, 0, 0], "max": [1, 1, 0]
+                }}]
+            }}"#,
+            buffer
+        );
+
+        let dir = std::env::temp_dir().join(format!("nova-gltf-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("triangle.gltf"), gltf).unwrap();
+
+        dir
+    }
+
+    /// Resources of an app loading assets from `dir`, like after opening the project.
+    fn resources(dir: &std::path::Path) -> Resources {
+        let mut server = AssetServer::new(vec![dir.to_path_buf()]);
+        server.add_loader(GltfLoader);
+
+        let mut resources = Resources::default();
+        resources.insert(server);
+        resources.insert(Assets::<GltfScene>::new());
+        resources.insert(Assets::<MeshData>::new());
+        resources.insert(Assets::<StandardMaterial>::new());
+        resources.insert(Assets::<Image>::new());
+
+        resources
+    }
+
+    /// Delivers finished loads until `done` or the loads time out.
+    fn update_until(resources: &Resources, done: impl Fn(&Resources) -> bool) {
+        let start = Instant::now();
+
+        while !done(resources) {
+            assert!(start.elapsed() < Duration::from_secs(5), "loads timed out");
+            std::thread::sleep(Duration::from_millis(1));
+
+            let server = resources.get::<AssetServer>().unwrap();
+            server.update(&mut resources.get_mut::<Assets<GltfScene>>().unwrap());
+            server.update(&mut resources.get_mut::<Assets<MeshData>>().unwrap());
+            server.update(&mut resources.get_mut::<Assets<StandardMaterial>>().unwrap());
+            server.update(&mut resources.get_mut::<Assets<Image>>().unwrap());
+        }
+    }
+
+    #[test]
+    fn spawned_scene_survives_saving() {
+        let dir = write_triangle();
+
+        let mut builder = AppBuilder::new();
+        builder
+            .register_component::<String>()
+            .register_component::<Transform>()
+            .register_component::<GlobalTransform>()
+            .register_component::<Parent>()
+            .register_component::<MeshInstance>()
+            .register_component::<Handle<StandardMaterial>>();
+        let registry = builder.registry;
+
+        // spawn the scene and save the world
+        let saved = {
+            let resources = resources(&dir);
+            let scene = resources
+                .get::<AssetServer>()
+                .unwrap()
+                .load::<GltfScene>("triangle.gltf");
+
+            update_until(&resources, |resources| {
+                resources.get::<Assets<GltfScene>>().unwrap().contains(&scene)
+            });
+
+            let mut world = World::default();
+            let scenes = resources.get::<Assets<GltfScene>>().unwrap();
+            scenes.get(&scene).unwrap().spawn(&mut world, None);
+
+            let mut saved = Vec::new();
+            let mut serializer = ron::Serializer::new(&mut saved, None, false).unwrap();
+            let mut serializer = <dyn erased_serde::Serializer>::erase(&mut serializer);
+            app::serialize(&world, &registry, &mut serializer).unwrap();
+
+            String::from_utf8(saved).unwrap()
+        };
+
+        assert!(saved.contains("Path(\"triangle.gltf#Mesh0/Primitive0\")"));
+        assert!(saved.contains("Path(\"triangle.gltf#Material0\")"));
+
+        // load it in a new app, the handles load their assets again
+        let resources = resources(&dir);
+        let mut world = World::default();
+
+        let mut deserializer = ron::Deserializer::from_str(&saved).unwrap();
+        let mut deserializer = <dyn erased_serde::Deserializer>::erase(&mut deserializer);
+        app::deserialize(&mut world, &resources, &registry, &mut deserializer).unwrap();
+
+        let (mesh, material) = <(&String, &MeshInstance, &Handle<StandardMaterial>)>::query()
+            .iter(&world)
+            .find(|(name, _, _)| name.as_str() == "Triangle")
+            .map(|(_, instance, material)| (instance.mesh_data.clone(), material.clone()))
+            .unwrap();
+
+        assert!(mesh.is_tracked());
+        assert!(material.is_tracked());
+
+        update_until(&resources, |resources| {
+            let server = resources.get::<AssetServer>().unwrap();
+
+            server.load_state(&mesh) != LoadState::Loading
+                && server.load_state(&material) != LoadState::Loading
+        });
+
+        let meshes = resources.get::<Assets<MeshData>>().unwrap();
+        let materials = resources.get::<Assets<StandardMaterial>>().unwrap();
+
+        assert_eq!(meshes.get(&mesh).unwrap().indices, [0, 1, 2]);
+        assert_eq!(
+            materials.get(&material).unwrap().base_color,
+            Color::rgba(1.0, 0.0, 0.0, 1.0)
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,21 +1,48 @@
+pub mod brdf;
+pub mod gltf;
+pub mod material;
+pub mod obj;
+#[cfg(feature = "editor")]
+pub mod preview;
+pub mod shadow;
 pub mod shape;
 pub mod stage;
 
 use glam::{Vec2, Vec3};
-use nova_assets::{Assets, Handle};
-use nova_core::{plugin::Plugin, AppBuilder};
+use nova_assets::{Assets, AssetsAppExt, Handle};
+use nova_core::{plugin::Plugin, stage::PRE_UPDATE, AppBuilder};
 use nova_render::{
-    color::Color, render_settings::RenderSettings, render_target::RenderTarget, renderer::Renderer,
+    color::Color,
+    render_settings::RenderSettings,
+    render_target::RenderTarget,
+    renderer::Renderer,
+    vertex::{VertexNormal, VertexPosition, VertexUv},
     Vertex,
 };
 use nova_wgpu::*;
 
-use crate::stage::D3PassNode;
+use crate::{
+    gltf::{spawn_scene_system, GltfLoader, GltfScene, SpawnScene},
+    material::{
+        material_bind_group_layout, MaterialLoader, StandardMaterial, DEFAULT_MATERIAL_HANDLE,
+    },
+    obj::ObjLoader,
+    shadow::{NotShadowCaster, ShadowNode, ShadowSettings},
+    stage::{view_bind_group_layout, D3PassNode},
+};
+
+/// Pipeline of meshes drawn with their [`StandardMaterial`], the pass picks the variant the
+/// material needs, see [`pbr_pipeline_handle`].
+pub const PBR_PIPELINE_HANDLE: Handle<RenderPipeline> = pbr_pipeline_handle(false, false);
 
-pub const PBR_PIPELINE_HANDLE: Handle<RenderPipeline> = Handle::from_u64(1246823428346);
+/// Handle of the variant of the pbr pipeline with alpha blending and without culling.
+#[inline]
+pub const fn pbr_pipeline_handle(blend: bool, double_sided: bool) -> Handle<RenderPipeline> {
+    Handle::from_u64(1246823428346 + blend as u64 * 2 + double_sided as u64)
+}
 
 #[repr(C)]
-#[derive(Clone, Copy, Vertex)]
+#[derive(Clone, Copy, Debug, Vertex)]
 pub struct Vertex3d {
     pub position: Vec3,
     pub normal: Vec3,
@@ -26,6 +53,37 @@ pub struct Vertex3d {
 unsafe impl bytemuck::Zeroable for Vertex3d {}
 unsafe impl bytemuck::Pod for Vertex3d {}
 
+impl VertexPosition for Vertex3d {
+    #[inline]
+    fn position(&self) -> Vec3 {
+        self.position
+    }
+
+    #[inline]
+    fn set_position(&mut self, position: Vec3) {
+        self.position = position;
+    }
+}
+
+impl VertexNormal for Vertex3d {
+    #[inline]
+    fn normal(&self) -> Vec3 {
+        self.normal
+    }
+
+    #[inline]
+    fn set_normal(&mut self, normal: Vec3) {
+        self.normal = normal;
+    }
+}
+
+impl VertexUv for Vertex3d {
+    #[inline]
+    fn uv(&self) -> Vec2 {
+        self.uv
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct D3System {
     pub msaa: u32,
@@ -34,11 +92,31 @@ pub struct D3Plugin;
 
 impl Plugin for D3Plugin {
     fn build(self, app: &mut AppBuilder) {
+        app.add_asset_loader(ObjLoader)
+            .add_asset_loader(GltfLoader)
+            .add_asset_loader(MaterialLoader)
+            .register_asset::<GltfScene>()
+            .register_asset::<StandardMaterial>()
+            .register_resource::<ShadowSettings>()
+            .add_system_to_stage(PRE_UPDATE, spawn_scene_system())
+            .register_component::<SpawnScene>()
+            .register_component::<Handle<StandardMaterial>>()
+            .register_component::<NotShadowCaster>();
+
+        #[cfg(feature = "editor")]
+        app.add_editor_system_to_stage(PRE_UPDATE, spawn_scene_system())
+            .register_inspectable::<Handle<StandardMaterial>>()
+            .register_asset_spawner(&["gltf", "glb"], gltf::spawn_gltf)
+            .register_asset_spawner(&["obj"], obj::spawn_obj)
+            .register_asset_preview(&["obj", "gltf", "glb"], preview::preview_meshes)
+            .register_asset_editor(&[material::MATERIAL_EXTENSION], material::edit_material);
+
         let instance = app.resources.get::<Instance>().unwrap();
         let target = app.resources.get::<RenderTarget>().unwrap();
         let mut render_system = app.resources.get_mut::<Renderer>().unwrap();
         let settings = app.resources.get::<RenderSettings>().unwrap();
 
+        render_system.add_node_to_stage(Renderer::PRE_RENDER, ShadowNode::new(&instance));
         render_system.add_node_to_stage(Renderer::RENDER, D3PassNode::default());
 
         let shader_module = instance.create_shader_module(&ShaderModuleDescriptor {
@@ -47,103 +125,119 @@ impl Plugin for D3Plugin {
             flags: ShaderFlags::all(),
         });
 
-        let transform_view_proj = instance.create_bind_group_layout(&BindGroupLayoutDescriptor {
-            label: Some("pbr"),
-            entries: &[
-                BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: ShaderStage::VERTEX_FRAGMENT,
-                    ty: BindingType::Buffer {
-                        ty: BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-                BindGroupLayoutEntry {
-                    binding: 1,
-                    visibility: ShaderStage::VERTEX_FRAGMENT,
-                    ty: BindingType::Buffer {
-                        ty: BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-            ],
-        });
+        let view_layout = view_bind_group_layout(&instance);
+        let material_layout = material_bind_group_layout(&instance);
 
         let layout = instance.create_pipeline_layout(&PipelineLayoutDescriptor {
             label: Some("pbr"),
-            bind_group_layouts: &[&transform_view_proj],
+            bind_group_layouts: &[&view_layout, &material_layout],
             push_constant_ranges: &[],
         });
 
-        let pipeline = instance.create_render_pipeline(&RenderPipelineDescriptor {
-            label: Some("pbr"),
-            layout: Some(&layout),
-            vertex: VertexState {
-                module: &shader_module,
-                buffers: &[
-                    Vertex3d::layout(),
-                    VertexBufferLayout {
-                        array_stride: 64,
-                        step_mode: InputStepMode::Instance,
-                        attributes: &[
-                            VertexAttribute {
-                                offset: 0,
-                                shader_location: 4,
-                                format: VertexFormat::Float32x4,
-                            },
-                            VertexAttribute {
-                                offset: 16,
-                                shader_location: 5,
-                                format: VertexFormat::Float32x4,
-                            },
-                            VertexAttribute {
-                                offset: 32,
-                                shader_location: 6,
-                                format: VertexFormat::Float32x4,
-                            },
-                            VertexAttribute {
-                                offset: 48,
-                                shader_location: 7,
-                                format: VertexFormat::Float32x4,
-                            },
-                        ],
-                    },
-                ],
-                entry_point: "main",
-            },
-            fragment: Some(FragmentState {
-                module: &shader_module,
-                targets: &[ColorTargetState {
-                    format: target.format(),
-                    blend: None,
-                    write_mask: ColorWrite::ALL,
-                }],
-                entry_point: "main",
-            }),
-            primitive: PrimitiveState {
-                cull_mode: Some(Face::Back),
-                ..Default::default()
-            },
-            multisample: MultisampleState {
-                count: settings.msaa,
-                ..Default::default()
-            },
-            depth_stencil: Some(DepthStencilState {
-                format: TextureFormat::Depth24Plus,
-                depth_write_enabled: true,
-                depth_compare: CompareFunction::LessEqual,
-                stencil: StencilState::default(),
-                bias: DepthBiasState::default(),
-            }),
-        });
+        let mut pipelines = app.resources.get_mut::<Assets<RenderPipeline>>().unwrap();
+
+        for &blend in &[false, true] {
+            for &double_sided in &[false, true] {
+                let pipeline = create_pbr_pipeline(
+                    &instance,
+                    &layout,
+                    &shader_module,
+                    target.format(),
+                    settings.msaa,
+                    blend,
+                    double_sided,
+                );
+
+                pipelines.insert_untracked(pbr_pipeline_handle(blend, double_sided), pipeline);
+            }
+        }
 
         app.resources
-            .get_mut::<Assets<RenderPipeline>>()
+            .get_mut::<Assets<StandardMaterial>>()
             .unwrap()
-            .insert_untracked(PBR_PIPELINE_HANDLE, pipeline);
+            .insert_untracked(DEFAULT_MATERIAL_HANDLE, StandardMaterial::default());
+    }
+}
+
+const INSTANCE_ATTRIBUTES: [VertexAttribute; 4] = [
+    VertexAttribute {
+        offset: 0,
+        shader_location: 4,
+        format: VertexFormat::Float32x4,
+    },
+    VertexAttribute {
+        offset: 16,
+        shader_location: 5,
+        format: VertexFormat::Float32x4,
+    },
+    VertexAttribute {
+        offset: 32,
+        shader_location: 6,
+        format: VertexFormat::Float32x4,
+    },
+    VertexAttribute {
+        offset: 48,
+        shader_location: 7,
+        format: VertexFormat::Float32x4,
+    },
+];
+
+/// Model matrices of the instances at locations 4 to 7.
+#[inline]
+pub(crate) fn instance_layout() -> VertexBufferLayout<'static> {
+    VertexBufferLayout {
+        array_stride: 64,
+        step_mode: InputStepMode::Instance,
+        attributes: &INSTANCE_ATTRIBUTES,
     }
 }
+
+#[inline]
+fn create_pbr_pipeline(
+    instance: &Instance,
+    layout: &PipelineLayout,
+    shader_module: &ShaderModule,
+    format: TextureFormat,
+    msaa: u32,
+    blend: bool,
+    double_sided: bool,
+) -> RenderPipeline {
+    instance.create_render_pipeline(&RenderPipelineDescriptor {
+        label: Some("pbr"),
+        layout: Some(layout),
+        vertex: VertexState {
+            module: shader_module,
+            buffers: &[Vertex3d::layout(), instance_layout()],
+            entry_point: "main",
+        },
+        fragment: Some(FragmentState {
+            module: shader_module,
+            targets: &[ColorTargetState {
+                format,
+                blend: if blend {
+                    Some(BlendState::ALPHA_BLENDING)
+                } else {
+                    None
+                },
+                write_mask: ColorWrite::ALL,
+            }],
+            entry_point: "main",
+        }),
+        primitive: PrimitiveState {
+            cull_mode: if double_sided { None } else { Some(Face::Back) },
+            ..Default::default()
+        },
+        multisample: MultisampleState {
+            count: msaa,
+            ..Default::default()
+        },
+        depth_stencil: Some(DepthStencilState {
+            format: TextureFormat::Depth24Plus,
+            // blended meshes are drawn after the opaque ones and don't hide each other
+            depth_write_enabled: !blend,
+            depth_compare: CompareFunction::LessEqual,
+            stencil: StencilState::default(),
+            bias: DepthBiasState::default(),
+        }),
+    })
+}
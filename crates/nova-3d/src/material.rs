@@ -0,0 +1,499 @@
+use nova_assets::{
+    inspect_optional,
+    loader::{AssetError, AssetLoader, LoadContext},
+    Handle,
+};
+use nova_inspect::{
+    egui::{ComboBox, DragValue, Response, Ui},
+    Inspectable,
+};
+use nova_render::{color::Color, image::Image};
+use nova_wgpu::*;
+use serde::{Deserialize, Serialize};
+
+use crate::pbr_pipeline_handle;
+
+/// Material of mesh entities without a `Handle<StandardMaterial>`.
+pub const DEFAULT_MATERIAL_HANDLE: Handle<StandardMaterial> = Handle::from_u64(8217364502981);
+
+/// Extension of material files, see [`MaterialLoader`].
+pub const MATERIAL_EXTENSION: &str = "mat";
+
+/// How the alpha of the base color is used.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum AlphaMode {
+    Opaque,
+    /// Fragments with alpha below the cutoff are discarded.
+    Mask(f32),
+    Blend,
+}
+
+impl Default for AlphaMode {
+    #[inline]
+    fn default() -> Self {
+        Self::Opaque
+    }
+}
+
+impl AlphaMode {
+    /// Cutoff of [`AlphaMode::Mask`] when it's picked in the inspector.
+    pub const DEFAULT_CUTOFF: f32 = 0.5;
+
+    #[inline]
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Opaque => "Opaque",
+            Self::Mask(_) => "Mask",
+            Self::Blend => "Blend",
+        }
+    }
+}
+
+impl Inspectable for AlphaMode {
+    #[inline]
+    fn name(&self) -> &'static str {
+        "AlphaMode"
+    }
+
+    /// Picks the mode from a combo box, with the cutoff next to it when masking.
+    #[inline]
+    fn inspect(&mut self, ui: &mut Ui) -> Option<Response> {
+        ui.horizontal(|ui| {
+            let mut picked = None;
+
+            let mut response = ComboBox::from_id_source(ui.id().with("alpha_mode"))
+                .selected_text(AlphaMode::name(self))
+                .show_ui(ui, |ui| {
+                    let cutoff = match *self {
+                        Self::Mask(cutoff) => cutoff,
+                        _ => Self::DEFAULT_CUTOFF,
+                    };
+
+                    for mode in &[Self::Opaque, Self::Mask(cutoff), Self::Blend] {
+                        if ui
+                            .selectable_label(AlphaMode::name(self) == mode.name(), mode.name())
+                            .clicked()
+                        {
+                            picked = Some(*mode);
+                        }
+                    }
+                });
+
+            if let Some(mode) = picked.filter(|mode| mode != self) {
+                *self = mode;
+                response.mark_changed();
+            }
+
+            if let Self::Mask(cutoff) = self {
+                let drag = DragValue::new(cutoff).speed(0.01).clamp_range(0.0..=1.0);
+                response |= ui.add(drag);
+            }
+
+            Some(response)
+        })
+        .inner
+    }
+}
+
+/// Metallic roughness material, mesh entities refer to it with a `Handle<StandardMaterial>`
+/// component.
+///
+/// Textures are multiplied with the factors, meshes are drawn once all of them are loaded.
+/// Materials are saved in `.mat` files, see [`MaterialLoader`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StandardMaterial {
+    pub base_color: Color,
+    pub base_color_texture: Option<Handle<Image>>,
+    pub metallic: f32,
+    pub roughness: f32,
+    /// Roughness in the green channel and metallic in the blue channel, like glTF.
+    pub metallic_roughness_texture: Option<Handle<Image>>,
+    /// Tangent space normals, the tangents are derived from the uvs in the shader.
+    pub normal_texture: Option<Handle<Image>>,
+    pub emissive: Color,
+    pub alpha_mode: AlphaMode,
+    /// Don't cull back faces.
+    pub double_sided: bool,
+}
+
+impl Default for StandardMaterial {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            base_color: Color::WHITE,
+            base_color_texture: None,
+            metallic: 0.0,
+            roughness: 0.5,
+            metallic_roughness_texture: None,
+            normal_texture: None,
+            emissive: Color::BLACK,
+            alpha_mode: AlphaMode::Opaque,
+            double_sided: false,
+        }
+    }
+}
+
+impl StandardMaterial {
+    /// Set in [`StandardMaterialRaw::flags`] when the material has a normal texture.
+    pub const NORMAL_TEXTURE: u32 = 1;
+
+    /// The variant of the pbr pipeline for the alpha mode and culling of the material.
+    #[inline]
+    pub fn pipeline(&self) -> Handle<RenderPipeline> {
+        pbr_pipeline_handle(self.alpha_mode == AlphaMode::Blend, self.double_sided)
+    }
+
+    #[inline]
+    pub fn textures(&self) -> impl Iterator<Item = &Handle<Image>> {
+        self.base_color_texture
+            .iter()
+            .chain(&self.metallic_roughness_texture)
+            .chain(&self.normal_texture)
+    }
+
+    #[inline]
+    pub fn textures_mut(&mut self) -> impl Iterator<Item = &mut Handle<Image>> {
+        self.base_color_texture
+            .iter_mut()
+            .chain(&mut self.metallic_roughness_texture)
+            .chain(&mut self.normal_texture)
+    }
+
+    #[inline]
+    pub fn raw(&self) -> StandardMaterialRaw {
+        let mut flags = 0;
+
+        if self.normal_texture.is_some() {
+            flags |= Self::NORMAL_TEXTURE;
+        }
+
+        StandardMaterialRaw {
+            base_color: self.base_color.into(),
+            emissive: self.emissive.into(),
+            metallic: self.metallic,
+            roughness: self.roughness,
+            alpha_cutoff: match self.alpha_mode {
+                AlphaMode::Mask(cutoff) => cutoff,
+                AlphaMode::Opaque | AlphaMode::Blend => 0.0,
+            },
+            flags,
+        }
+    }
+}
+
+/// Shows `add` after `label` on one line.
+#[inline]
+fn labeled(
+    ui: &mut Ui,
+    label: &str,
+    add: impl FnOnce(&mut Ui) -> Option<Response>,
+) -> Option<Response> {
+    ui.horizontal(|ui| {
+        ui.label(label);
+        add(ui)
+    })
+    .inner
+}
+
+/// Factors are dragged within `0..=1`.
+#[inline]
+fn factor_ui(ui: &mut Ui, factor: &mut f32) -> Option<Response> {
+    Some(ui.add(DragValue::new(factor).speed(0.01).clamp_range(0.0..=1.0)))
+}
+
+impl Inspectable for StandardMaterial {
+    #[inline]
+    fn name(&self) -> &'static str {
+        "StandardMaterial"
+    }
+
+    /// Shows every field with its name, textures can be picked like other handles.
+    #[inline]
+    fn inspect(&mut self, ui: &mut Ui) -> Option<Response> {
+        let mut response = labeled(ui, "Base color", |ui| self.base_color.inspect(ui))?;
+        response |= labeled(ui, "Base color texture", |ui| {
+            inspect_optional(&mut self.base_color_texture, ui)
+        })?;
+        response |= labeled(ui, "Metallic", |ui| factor_ui(ui, &mut self.metallic))?;
+        response |= labeled(ui, "Roughness", |ui| factor_ui(ui, &mut self.roughness))?;
+        response |= labeled(ui, "Metallic roughness texture", |ui| {
+            inspect_optional(&mut self.metallic_roughness_texture, ui)
+        })?;
+        response |= labeled(ui, "Normal texture", |ui| {
+            inspect_optional(&mut self.normal_texture, ui)
+        })?;
+        response |= labeled(ui, "Emissive", |ui| self.emissive.inspect(ui))?;
+        response |= labeled(ui, "Alpha mode", |ui| self.alpha_mode.inspect(ui))?;
+        response |= labeled(ui, "Double sided", |ui| self.double_sided.inspect(ui))?;
+
+        Some(response)
+    }
+}
+
+/// Loads [`StandardMaterial`]s from `.mat` files, the material in ron. Textures are handles
+/// like in scenes and are loaded with the material.
+pub struct MaterialLoader;
+
+impl AssetLoader for MaterialLoader {
+    type Asset = StandardMaterial;
+
+    #[inline]
+    fn extensions(&self) -> &[&str] {
+        &[MATERIAL_EXTENSION]
+    }
+
+    #[inline]
+    fn load(&self, bytes: &[u8], ctx: &mut LoadContext) -> Result<StandardMaterial, AssetError> {
+        let mut material: StandardMaterial = ron::de::from_bytes(bytes)?;
+
+        for texture in material.textures_mut() {
+            *texture = ctx.load_handle(texture);
+        }
+
+        Ok(material)
+    }
+}
+
+/// Keeps the material shown in the editor's inspector loaded.
+#[cfg(feature = "editor")]
+struct EditedMaterial {
+    path: std::path::PathBuf,
+    handle: Handle<StandardMaterial>,
+}
+
+/// Shows the `.mat` file at `path` in the editor's inspector and saves it when it's changed,
+/// see [`AppBuilder::register_asset_editor`](nova_core::AppBuilder::register_asset_editor).
+#[cfg(feature = "editor")]
+pub fn edit_material(
+    _world: &mut nova_core::World,
+    resources: &mut nova_core::Resources,
+    path: &std::path::Path,
+    ui: &mut Ui,
+) -> bool {
+    use nova_assets::{server::AssetServer, server::LoadState, Assets};
+
+    let opened = resources
+        .get::<EditedMaterial>()
+        .map_or(false, |edited| edited.path == path);
+
+    if !opened {
+        let handle = resources.get::<AssetServer>().unwrap().load(path);
+
+        resources.insert(EditedMaterial {
+            path: path.to_path_buf(),
+            handle,
+        });
+    }
+
+    let handle = resources.get::<EditedMaterial>().unwrap().handle.clone();
+    let server = resources.get::<AssetServer>().unwrap();
+
+    // edited as a copy, getting the material mutably marks it modified
+    let material = resources
+        .get::<Assets<StandardMaterial>>()
+        .unwrap()
+        .get(&handle)
+        .cloned();
+
+    let mut material = match material {
+        Some(material) => material,
+        None => {
+            match server.load_state(&handle) {
+                LoadState::Failed(err) => ui.label(format!("failed to load: {}", err)),
+                _ => ui.label("loading"),
+            };
+
+            return false;
+        }
+    };
+
+    let response = nova_inspect::context::inspect_with(resources, || material.inspect(ui));
+
+    if !response.map_or(false, |response| response.changed()) {
+        return false;
+    }
+
+    let source = ron::ser::to_string_pretty(&material, Default::default());
+
+    if let Err(err) = source.map_err(AssetError::from).and_then(|source| {
+        std::fs::write(server.resolve(path), source)?;
+        Ok(())
+    }) {
+        log::error!("failed to save '{}': {}", path.display(), err);
+    }
+
+    if let Some(edited) = resources
+        .get_mut::<Assets<StandardMaterial>>()
+        .unwrap()
+        .get_mut(&handle)
+    {
+        *edited = material;
+    }
+
+    true
+}
+
+/// Layout of the `Material` uniform in `pbr.wgsl`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StandardMaterialRaw {
+    pub base_color: [f32; 4],
+    pub emissive: [f32; 4],
+    pub metallic: f32,
+    pub roughness: f32,
+    pub alpha_cutoff: f32,
+    pub flags: u32,
+}
+
+unsafe impl bytemuck::Zeroable for StandardMaterialRaw {}
+unsafe impl bytemuck::Pod for StandardMaterialRaw {}
+
+/// Entries of a texture at `binding` and its sampler at `binding + 1`.
+#[inline]
+fn texture_entries(binding: u32) -> [BindGroupLayoutEntry; 2] {
+    [
+        BindGroupLayoutEntry {
+            binding,
+            visibility: ShaderStage::FRAGMENT,
+            ty: BindingType::Texture {
+                sample_type: TextureSampleType::Float { filterable: true },
+                view_dimension: TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        },
+        BindGroupLayoutEntry {
+            binding: binding + 1,
+            visibility: ShaderStage::FRAGMENT,
+            ty: BindingType::Sampler {
+                filtering: true,
+                comparison: false,
+            },
+            count: None,
+        },
+    ]
+}
+
+/// Layout of the material bind group, group 1 of the pbr pipelines.
+#[inline]
+pub fn material_bind_group_layout(instance: &Instance) -> BindGroupLayout {
+    let [base_color, base_color_sampler] = texture_entries(1);
+    let [metallic_roughness, metallic_roughness_sampler] = texture_entries(3);
+    let [normal, normal_sampler] = texture_entries(5);
+
+    instance.create_bind_group_layout(&BindGroupLayoutDescriptor {
+        label: Some("standard_material"),
+        entries: &[
+            BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStage::FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            base_color,
+            base_color_sampler,
+            metallic_roughness,
+            metallic_roughness_sampler,
+            normal,
+            normal_sampler,
+        ],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use nova_assets::{
+        meta::AssetMeta,
+        server::{AssetServer, LoadState},
+        Assets, InnerHandle, Uuid,
+    };
+
+    use super::*;
+
+    /// Loads single pixels from `.px` files, so the test needs no image files.
+    struct PixelLoader;
+
+    impl AssetLoader for PixelLoader {
+        type Asset = Image;
+
+        fn extensions(&self) -> &[&str] {
+            &["px"]
+        }
+
+        fn load(&self, bytes: &[u8], _ctx: &mut LoadContext) -> Result<Image, AssetError> {
+            Ok(Image::new((1, 1), bytes.to_vec(), true))
+        }
+    }
+
+    #[test]
+    fn missing_fields_are_defaults() {
+        let material: StandardMaterial =
+            ron::de::from_str("(roughness: 0.2, alpha_mode: Mask(0.3))").unwrap();
+
+        assert_eq!(
+            material,
+            StandardMaterial {
+                roughness: 0.2,
+                alpha_mode: AlphaMode::Mask(0.3),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn textures_load_with_the_material() {
+        let dir = std::env::temp_dir().join(format!("nova-material-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let uuid = Uuid::new_v4();
+        std::fs::write(dir.join("white.px"), [255u8; 4]).unwrap();
+        AssetMeta {
+            uuid,
+            settings: Default::default(),
+        }
+        .write(&dir.join("white.px"))
+        .unwrap();
+
+        let source = format!("(base_color_texture: Some(Uuid(\"{}\")), metallic: 1.0)", uuid);
+        std::fs::write(dir.join("white.mat"), source).unwrap();
+
+        let mut server = AssetServer::new(vec![dir.clone()]);
+        server.add_loader(MaterialLoader);
+        server.add_loader(PixelLoader);
+
+        let mut materials = Assets::<StandardMaterial>::new();
+        let mut images = Assets::<Image>::new();
+
+        let handle = server.load::<StandardMaterial>("white.mat");
+        let start = Instant::now();
+
+        while server.recursive_load_state(&handle) != LoadState::Loaded {
+            assert!(start.elapsed() < Duration::from_secs(5), "loads timed out");
+            std::thread::sleep(Duration::from_millis(1));
+
+            server.start_requested();
+            server.update(&mut materials);
+            server.update(&mut images);
+        }
+
+        let material = materials.get(&handle).unwrap();
+        let texture = material.base_color_texture.as_ref().unwrap();
+
+        assert_eq!(material.metallic, 1.0);
+        assert_eq!(texture.inner(), &InnerHandle::Uuid(uuid));
+        assert_eq!(images.get(texture).unwrap().mips[0], vec![255; 4]);
+
+        // saved back by uuid, like it was written
+        let saved = ron::ser::to_string(material).unwrap();
+        assert!(saved.contains(&uuid.to_string()));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
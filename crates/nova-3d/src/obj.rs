@@ -0,0 +1,530 @@
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+};
+
+use glam::{Vec2, Vec3};
+use nova_assets::{
+    loader::{AssetError, AssetLoader, LoadContext},
+    meta::MeshSettings,
+};
+use nova_render::{color::Color, mesh::Mesh, mesh::MeshData};
+
+use crate::Vertex3d;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjError {
+    /// Line of the error, starting at 1.
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for ObjError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ObjError {}
+
+/// How normals are generated for faces without them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Normals {
+    /// Every face gets its own normal, for hard edges.
+    Flat,
+    /// Normals are averaged over the faces sharing a position.
+    Smooth,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjOptions {
+    /// Multiplies the positions.
+    pub scale: f32,
+    /// Normals generated when the file lacks them.
+    pub normals: Normals,
+    /// Generate normals even if the file has them.
+    pub generate_normals: bool,
+}
+
+impl Default for ObjOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            normals: Normals::Smooth,
+            generate_normals: false,
+        }
+    }
+}
+
+impl From<&MeshSettings> for ObjOptions {
+    #[inline]
+    fn from(settings: &MeshSettings) -> Self {
+        Self {
+            scale: settings.scale,
+            normals: if settings.smooth_normals {
+                Normals::Smooth
+            } else {
+                Normals::Flat
+            },
+            generate_normals: settings.generate_normals,
+        }
+    }
+}
+
+/// An object or group of an obj file.
+#[derive(Clone, Debug)]
+pub struct ObjObject {
+    pub name: String,
+    pub mesh: Mesh<Vertex3d>,
+}
+
+/// Indices of the position, uv and normal of a face corner.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Corner {
+    position: usize,
+    uv: Option<usize>,
+    normal: Option<usize>,
+}
+
+#[derive(Default)]
+struct Group {
+    name: String,
+    triangles: Vec<[Corner; 3]>,
+}
+
+#[derive(Default)]
+struct Parser {
+    positions: Vec<Vec3>,
+    uvs: Vec<Vec2>,
+    normals: Vec<Vec3>,
+    groups: Vec<Group>,
+}
+
+/// Parses up to `N` numbers, the ones after the first `required` are optional and 0 if
+/// they're missing.
+#[inline]
+fn parse_floats<const N: usize>(
+    args: &[&str],
+    required: usize,
+    line: usize,
+) -> Result<[f32; N], ObjError> {
+    let mut floats = [0.0; N];
+
+    if args.len() < required {
+        return Err(ObjError {
+            line,
+            message: format!("expected {} numbers, found {}", required, args.len()),
+        });
+    }
+
+    for (float, arg) in floats.iter_mut().zip(args) {
+        *float = arg.parse().map_err(|_| ObjError {
+            line,
+            message: format!("invalid number '{}'", arg),
+        })?;
+    }
+
+    Ok(floats)
+}
+
+/// Resolves a 1 based or negative relative index into a list of length `len`.
+#[inline]
+fn resolve_index(index: &str, len: usize, line: usize) -> Result<usize, ObjError> {
+    let invalid = || ObjError {
+        line,
+        message: format!("invalid index '{}'", index),
+    };
+
+    let parsed: isize = index.parse().map_err(|_| invalid())?;
+
+    let resolved = if parsed < 0 {
+        len as isize + parsed
+    } else {
+        parsed - 1
+    };
+
+    if resolved < 0 || resolved as usize >= len {
+        return Err(invalid());
+    }
+
+    Ok(resolved as usize)
+}
+
+impl Parser {
+    #[inline]
+    fn group(&mut self) -> &mut Group {
+        if self.groups.is_empty() {
+            self.groups.push(Group::default());
+        }
+
+        self.groups.last_mut().unwrap()
+    }
+
+    #[inline]
+    fn corner(&self, arg: &str, line: usize) -> Result<Corner, ObjError> {
+        let mut parts = arg.split('/');
+
+        let position = resolve_index(parts.next().unwrap_or(""), self.positions.len(), line)?;
+
+        let uv = match parts.next() {
+            Some(uv) if !uv.is_empty() => Some(resolve_index(uv, self.uvs.len(), line)?),
+            _ => None,
+        };
+
+        let normal = match parts.next() {
+            Some(normal) if !normal.is_empty() => {
+                Some(resolve_index(normal, self.normals.len(), line)?)
+            }
+            _ => None,
+        };
+
+        Ok(Corner {
+            position,
+            uv,
+            normal,
+        })
+    }
+
+    #[inline]
+    fn parse_line(&mut self, text: &str, line: usize, scale: f32) -> Result<(), ObjError> {
+        let text = text.split('#').next().unwrap_or("");
+        let mut words = text.split_whitespace();
+
+        let keyword = match words.next() {
+            Some(keyword) => keyword,
+            None => return Ok(()),
+        };
+
+        let args = words.collect::<Vec<_>>();
+
+        match keyword {
+            "v" => {
+                let [x, y, z] = parse_floats(&args, 3, line)?;
+                self.positions.push(Vec3::new(x, y, z) * scale);
+            }
+            "vt" => {
+                let [u, v] = parse_floats(&args, 1, line)?;
+                // obj uvs start at the bottom left
+                self.uvs.push(Vec2::new(u, 1.0 - v));
+            }
+            "vn" => {
+                let [x, y, z] = parse_floats(&args, 3, line)?;
+                self.normals.push(Vec3::new(x, y, z).normalize_or_zero());
+            }
+            "f" => {
+                if args.len() < 3 {
+                    return Err(ObjError {
+                        line,
+                        message: String::from("faces need at least 3 vertices"),
+                    });
+                }
+
+                let corners = args
+                    .iter()
+                    .map(|arg| self.corner(arg, line))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                // polygons are assumed to be convex and fanned from the first corner
+                let group = self.group();
+                for i in 1..corners.len() - 1 {
+                    group
+                        .triangles
+                        .push([corners[0], corners[i], corners[i + 1]]);
+                }
+            }
+            "o" | "g" => {
+                let name = args.join(" ");
+
+                // a group right after its object names the same geometry
+                match self.groups.last_mut() {
+                    Some(group) if group.triangles.is_empty() => group.name = name,
+                    _ => self.groups.push(Group {
+                        name,
+                        triangles: Vec::new(),
+                    }),
+                }
+            }
+            // materials, smoothing groups, lines and points aren't supported
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn build(&self, group: &Group, options: &ObjOptions) -> Mesh<Vertex3d> {
+        let has_normals = group
+            .triangles
+            .iter()
+            .flatten()
+            .all(|corner| corner.normal.is_some());
+
+        if has_normals && !options.generate_normals {
+            return self.build_indexed(group, |corner| self.normals[corner.normal.unwrap()]);
+        }
+
+        let mut mesh = self.build_indexed(group, |_| Vec3::ZERO);
+
+        match options.normals {
+            Normals::Flat => mesh.compute_flat_normals(),
+            Normals::Smooth => mesh.compute_smooth_normals(),
+        }
+
+        mesh
+    }
+
+    #[inline]
+    fn vertex(&self, corner: &Corner, normal: Vec3) -> Vertex3d {
+        Vertex3d {
+            position: self.positions[corner.position],
+            normal,
+            uv: corner.uv.map_or(Vec2::ZERO, |uv| self.uvs[uv]),
+            color: Color::rgb(1.0, 1.0, 1.0),
+        }
+    }
+
+    /// Builds a mesh sharing vertices between corners with the same indices.
+    #[inline]
+    fn build_indexed(&self, group: &Group, normal: impl Fn(&Corner) -> Vec3) -> Mesh<Vertex3d> {
+        let mut mesh = Mesh::default();
+        let mut vertices = HashMap::new();
+
+        for corner in group.triangles.iter().flatten() {
+            let index = *vertices.entry(*corner).or_insert_with(|| {
+                mesh.vertices.push(self.vertex(corner, normal(corner)));
+                mesh.vertices.len() as u32 - 1
+            });
+
+            mesh.indices.push(index);
+        }
+
+        mesh
+    }
+}
+
+/// Parses the objects and groups in the obj `source`, objects without faces are skipped.
+#[inline]
+pub fn parse(source: &str, options: &ObjOptions) -> Result<Vec<ObjObject>, ObjError> {
+    let mut parser = Parser::default();
+
+    for (i, text) in source.lines().enumerate() {
+        parser.parse_line(text, i + 1, options.scale)?;
+    }
+
+    Ok(parser
+        .groups
+        .iter()
+        .filter(|group| !group.triangles.is_empty())
+        .map(|group| ObjObject {
+            name: group.name.clone(),
+            mesh: parser.build(group, options),
+        })
+        .collect())
+}
+
+/// Parses the obj `source` into a single mesh holding every object.
+#[inline]
+pub fn load_mesh(source: &str, options: &ObjOptions) -> Result<Mesh<Vertex3d>, ObjError> {
+    let mut mesh = Mesh::default();
+
+    for object in parse(source, options)? {
+        let offset = mesh.vertices.len() as u32;
+
+        mesh.vertices.extend(object.mesh.vertices);
+        mesh.indices
+            .extend(object.mesh.indices.iter().map(|index| index + offset));
+    }
+
+    Ok(mesh)
+}
+
+/// Loads `.obj` files as a single [`MeshData`] of [`Vertex3d`]s, using the
+/// [`MeshSettings`] of the asset.
+pub struct ObjLoader;
+
+impl AssetLoader for ObjLoader {
+    type Asset = MeshData;
+
+    #[inline]
+    fn extensions(&self) -> &[&str] {
+        &["obj"]
+    }
+
+    #[inline]
+    fn load(&self, bytes: &[u8], ctx: &mut LoadContext<'_>) -> Result<MeshData, AssetError> {
+        let source = std::str::from_utf8(bytes)?;
+        let options = ObjOptions::from(&ctx.settings().mesh);
+
+        Ok(load_mesh(source, &options)?.into())
+    }
+}
+
+/// Spawns a mesh drawn with the default material for `.obj` files dropped into the editor
+/// viewport.
+#[cfg(feature = "editor")]
+pub fn spawn_obj(
+    world: &mut nova_core::World,
+    resources: &mut nova_core::Resources,
+    path: &std::path::Path,
+) -> nova_core::Entity {
+    let server = resources.get::<nova_assets::server::AssetServer>().unwrap();
+    let name = path
+        .file_stem()
+        .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+
+    world.push((
+        name,
+        nova_transform::component::Transform::IDENTITY,
+        nova_transform::component::GlobalTransform::default(),
+        nova_render::component::MeshInstance {
+            mesh_data: server.load(path),
+            pipeline: crate::PBR_PIPELINE_HANDLE,
+            bindings: Default::default(),
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 0.0001;
+
+    const QUAD: &str = "
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+vn 0 0 1
+f 1/1/1 2/2/1 3/3/1 4/4/1
+";
+
+    #[test]
+    fn quad_is_fanned() {
+        let mesh = load_mesh(QUAD, &ObjOptions::default()).unwrap();
+
+        assert_eq!(mesh.vertices.len(), 4);
+        assert_eq!(mesh.indices, vec![0, 1, 2, 0, 2, 3]);
+
+        // uvs are flipped to start at the top left
+        assert_eq!(mesh.vertices[0].uv, Vec2::new(0.0, 1.0));
+        assert_eq!(mesh.vertices[2].uv, Vec2::new(1.0, 0.0));
+        assert!(mesh.vertices.iter().all(|vertex| vertex.normal == Vec3::Z));
+    }
+
+    #[test]
+    fn ngon_is_fanned() {
+        let source = "
+v 0 0 0
+v 1 0 0
+v 2 1 0
+v 1 2 0
+v 0 1 0
+f 1 2 3 4 5
+";
+        let mesh = load_mesh(source, &ObjOptions::default()).unwrap();
+
+        assert_eq!(mesh.indices, vec![0, 1, 2, 0, 2, 3, 0, 3, 4]);
+    }
+
+    #[test]
+    fn negative_indices() {
+        let source = "
+v 0 0 0
+v 1 0 0
+v 0 1 0
+vn 0 0 1
+f -3//-1 -2//-1 -1//-1
+v 5 5 5
+";
+        let mesh = load_mesh(source, &ObjOptions::default()).unwrap();
+
+        assert_eq!(mesh.vertices.len(), 3);
+        assert_eq!(mesh.vertices[0].position, Vec3::ZERO);
+        assert_eq!(mesh.vertices[2].position, Vec3::Y);
+    }
+
+    #[test]
+    fn position_normal_corners() {
+        let source = "
+v 0 0 0
+v 1 0 0
+v 0 1 0
+vn 0 0 -1
+f 1//1 2//1 3//1
+";
+        let mesh = load_mesh(source, &ObjOptions::default()).unwrap();
+
+        // the file's normals are kept even if they disagree with the winding
+        assert!(mesh.vertices.iter().all(|vertex| vertex.normal == -Vec3::Z));
+        assert!(mesh.vertices.iter().all(|vertex| vertex.uv == Vec2::ZERO));
+    }
+
+    #[test]
+    fn missing_normals_are_generated() {
+        let source = "
+v 0 0 0
+v 1 0 0
+v 0 1 0
+f 1 2 3
+";
+        let mesh = load_mesh(source, &ObjOptions::default()).unwrap();
+
+        for vertex in &mesh.vertices {
+            assert!((vertex.normal - Vec3::Z).length() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn objects_and_groups_are_split() {
+        let source = "
+v 0 0 0
+v 1 0 0
+v 0 1 0
+o first
+g first_group
+f 1 2 3
+g second
+f 3 2 1
+o empty
+";
+        let objects = parse(source, &ObjOptions::default()).unwrap();
+
+        let names = objects
+            .iter()
+            .map(|object| object.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["first_group", "second"]);
+        assert!(objects.iter().all(|object| object.mesh.indices.len() == 3));
+    }
+
+    #[test]
+    fn missing_v_of_uv_is_zero() {
+        let source = "
+v 0 0 0
+v 1 0 0
+v 0 1 0
+vt 0.5
+f 1/1 2/1 3/1
+";
+        let mesh = load_mesh(source, &ObjOptions::default()).unwrap();
+
+        assert_eq!(mesh.vertices[0].uv, Vec2::new(0.5, 1.0));
+    }
+
+    #[test]
+    fn errors_have_line_numbers() {
+        let error = |source: &str| parse(source, &ObjOptions::default()).unwrap_err().line;
+
+        assert_eq!(error("v 0 0 0\nv 1 x 0"), 2);
+        assert_eq!(error("v 0 0 0\n\n# comment\nv 1 0"), 4);
+        assert_eq!(error("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4"), 4);
+        assert_eq!(error("v 0 0 0\nf 1 1"), 2);
+        assert_eq!(error("vt"), 1);
+    }
+}
@@ -13,12 +13,14 @@ struct VertexOutput {
 	[[builtin(position)]] position: vec4<f32>;
 	[[location(0)]] w_position: vec3<f32>;
 	[[location(1)]] w_normal: vec3<f32>;
-	[[location(2)]] color: vec4<f32>;
+	[[location(2)]] uv: vec2<f32>;
+	[[location(3)]] color: vec4<f32>;
 };
 
 [[block]]
 struct Camera {
 	view_proj: mat4x4<f32>;
+	position: vec4<f32>;
 };
 
 [[group(0), binding(0)]]
@@ -27,7 +29,28 @@ var<uniform> camera: Camera;
 struct PointLight {
 	position: vec3<f32>;
 	intensity: f32;
-	color: vec4<f32>;
+	color: vec3<f32>;
+	range: f32;
+	// index of the cube shadow map, negative without shadows
+	shadow: i32;
+};
+
+struct DirectionalLight {
+	direction: vec3<f32>;
+	intensity: f32;
+	color: vec3<f32>;
+	// zero for the cascaded shadow map, negative without shadows
+	shadow: i32;
+};
+
+struct SpotLight {
+	position: vec3<f32>;
+	intensity: f32;
+	color: vec3<f32>;
+	range: f32;
+	direction: vec3<f32>;
+	inner_cos: f32;
+	outer_cos: f32;
 };
 
 [[block]]
@@ -35,12 +58,91 @@ struct Lights {
 	ambient_color: vec4<f32>;
 	ambient_intensity: f32;
 	point_lights_len: u32;
-	point_lights: array<PointLight, 64>;
+	directional_lights_len: u32;
+	spot_lights_len: u32;
+};
+
+[[block]]
+struct PointLights {
+	lights: [[stride(48)]] array<PointLight>;
+};
+
+[[block]]
+struct DirectionalLights {
+	lights: [[stride(32)]] array<DirectionalLight>;
+};
+
+[[block]]
+struct SpotLights {
+	lights: [[stride(64)]] array<SpotLight>;
 };
 
 [[group(0), binding(1)]]
 var<uniform> lights: Lights;
 
+// the storage buffers have room for more lights than there are, the lengths are in `lights`
+[[group(0), binding(2)]]
+var<storage> point_lights: [[access(read)]] PointLights;
+
+[[group(0), binding(3)]]
+var<storage> directional_lights: [[access(read)]] DirectionalLights;
+
+[[group(0), binding(4)]]
+var<storage> spot_lights: [[access(read)]] SpotLights;
+
+// the array sizes are `CASCADES` and `MAX_POINT_SHADOWS * CUBE_FACES`
+[[block]]
+struct Shadows {
+	cascades: [[stride(64)]] array<mat4x4<f32>, 4>;
+	point_faces: [[stride(64)]] array<mat4x4<f32>, 24>;
+	cascade_splits: vec4<f32>;
+	directional_size: f32;
+	point_size: f32;
+};
+
+[[group(0), binding(5)]]
+var<uniform> shadows: Shadows;
+
+[[group(0), binding(6)]]
+var directional_shadow_map: texture_depth_2d_array;
+
+[[group(0), binding(7)]]
+var point_shadow_map: texture_depth_2d_array;
+
+[[group(0), binding(8)]]
+var shadow_sampler: sampler_comparison;
+
+[[block]]
+struct Material {
+	base_color: vec4<f32>;
+	emissive: vec4<f32>;
+	metallic: f32;
+	roughness: f32;
+	alpha_cutoff: f32;
+	flags: u32;
+};
+
+[[group(1), binding(0)]]
+var<uniform> material: Material;
+
+[[group(1), binding(1)]]
+var base_color_texture: texture_2d<f32>;
+
+[[group(1), binding(2)]]
+var base_color_sampler: sampler;
+
+[[group(1), binding(3)]]
+var metallic_roughness_texture: texture_2d<f32>;
+
+[[group(1), binding(4)]]
+var metallic_roughness_sampler: sampler;
+
+[[group(1), binding(5)]]
+var normal_texture: texture_2d<f32>;
+
+[[group(1), binding(6)]]
+var normal_sampler: sampler;
+
 [[stage(vertex)]]
 fn main(in: VertexInput) -> VertexOutput {
 	var out: VertexOutput;
@@ -57,37 +159,301 @@ fn main(in: VertexInput) -> VertexOutput {
 	out.position = camera.view_proj * out.position;
 
 	out.w_normal = (model * vec4<f32>(in.normal, 0.0)).xyz;
+	out.uv = in.uv;
 	out.color = in.color;
 
 	return out;
 }
 
+// tangent space from the screen space derivatives of the position and uv, so meshes don't
+// need tangents
+fn perturb_normal(normal: vec3<f32>, position: vec3<f32>, uv: vec2<f32>, map: vec3<f32>) -> vec3<f32> {
+	let dp1 = dpdx(position);
+	let dp2 = dpdy(position);
+	let duv1 = dpdx(uv);
+	let duv2 = dpdy(uv);
+
+	let dp2_perp = cross(dp2, normal);
+	let dp1_perp = cross(normal, dp1);
+	let tangent = dp2_perp * duv1.x + dp1_perp * duv2.x;
+	let bitangent = dp2_perp * duv1.y + dp1_perp * duv2.y;
+
+	let scale = inverseSqrt(max(dot(tangent, tangent), dot(bitangent, bitangent)));
+	let tbn = mat3x3<f32>(tangent * scale, bitangent * scale, normal);
+
+	return normalize(tbn * (map * 2.0 - 1.0));
+}
+
+// the lighting functions match the reference implementation in `brdf.rs`
+
+fn distribution_ggx(n_dot_h: f32, alpha: f32) -> f32 {
+	let alpha2 = alpha * alpha;
+	let d = n_dot_h * n_dot_h * (alpha2 - 1.0) + 1.0;
+
+	return alpha2 / (3.14159265 * d * d);
+}
+
+fn visibility_smith_ggx(n_dot_l: f32, n_dot_v: f32, alpha: f32) -> f32 {
+	let alpha2 = alpha * alpha;
+	let ggx_v = n_dot_l * sqrt(n_dot_v * n_dot_v * (1.0 - alpha2) + alpha2);
+	let ggx_l = n_dot_v * sqrt(n_dot_l * n_dot_l * (1.0 - alpha2) + alpha2);
+
+	return 0.5 / (ggx_v + ggx_l);
+}
+
+fn fresnel_schlick(f0: vec3<f32>, v_dot_h: f32) -> vec3<f32> {
+	return f0 + (vec3<f32>(1.0, 1.0, 1.0) - f0) * pow(clamp(1.0 - v_dot_h, 0.0, 1.0), 5.0);
+}
+
+fn attenuation(dist: f32, range: f32) -> f32 {
+	let window = clamp(1.0 - pow(dist / range, 4.0), 0.0, 1.0);
+
+	return window * window / max(dist * dist, 0.0001);
+}
+
+fn spot_cone(cos_angle: f32, inner_cos: f32, outer_cos: f32) -> f32 {
+	let t = clamp((cos_angle - outer_cos) / max(inner_cos - outer_cos, 0.0001), 0.0, 1.0);
+
+	return t * t;
+}
+
+fn brdf(
+	diffuse_color: vec3<f32>,
+	f0: vec3<f32>,
+	alpha: f32,
+	normal: vec3<f32>,
+	view: vec3<f32>,
+	light: vec3<f32>
+) -> vec3<f32> {
+	let halfway = normalize(view + light);
+
+	let n_dot_l = max(dot(normal, light), 0.0);
+	let n_dot_v = max(dot(normal, view), 0.0001);
+	let n_dot_h = max(dot(normal, halfway), 0.0);
+	let v_dot_h = max(dot(view, halfway), 0.0);
+
+	let f = fresnel_schlick(f0, v_dot_h);
+	let d = distribution_ggx(n_dot_h, alpha);
+	let v = visibility_smith_ggx(n_dot_l, n_dot_v, alpha);
+
+	let specular = f * d * v;
+	let diffuse = (vec3<f32>(1.0, 1.0, 1.0) - f) * diffuse_color / 3.14159265;
+
+	return diffuse + specular;
+}
+
+// 3x3 percentage closer filtering of the cascades
+fn pcf_directional(uv: vec2<f32>, layer: i32, depth: f32) -> f32 {
+	let texel = 1.0 / shadows.directional_size;
+
+	var sum: f32 = 0.0;
+	var x: i32 = -1;
+	loop {
+		if (x > 1) { break; }
+
+		var y: i32 = -1;
+		loop {
+			if (y > 1) { break; }
+
+			let offset = vec2<f32>(f32(x), f32(y)) * texel;
+			sum = sum + textureSampleCompare(directional_shadow_map, shadow_sampler, uv + offset, layer, depth);
+
+			y = y + 1;
+		}
+
+		x = x + 1;
+	}
+
+	return sum / 9.0;
+}
+
+// 3x3 percentage closer filtering of the cube faces
+fn pcf_point(uv: vec2<f32>, layer: i32, depth: f32) -> f32 {
+	let texel = 1.0 / shadows.point_size;
+
+	var sum: f32 = 0.0;
+	var x: i32 = -1;
+	loop {
+		if (x > 1) { break; }
+
+		var y: i32 = -1;
+		loop {
+			if (y > 1) { break; }
+
+			let offset = vec2<f32>(f32(x), f32(y)) * texel;
+			sum = sum + textureSampleCompare(point_shadow_map, shadow_sampler, uv + offset, layer, depth);
+
+			y = y + 1;
+		}
+
+		x = x + 1;
+	}
+
+	return sum / 9.0;
+}
+
+// the fragment is offset along the normal by about a texel of the map against shadow acne
+
+fn directional_shadow(position: vec3<f32>, normal: vec3<f32>) -> f32 {
+	let dist = length(position - camera.position.xyz);
+	let splits = shadows.cascade_splits;
+
+	if (dist >= splits.w) {
+		return 1.0;
+	}
+
+	var cascade: i32 = 3;
+	var radius: f32 = splits.w;
+	if (dist < splits.z) {
+		cascade = 2;
+		radius = splits.z;
+	}
+	if (dist < splits.y) {
+		cascade = 1;
+		radius = splits.y;
+	}
+	if (dist < splits.x) {
+		cascade = 0;
+		radius = splits.x;
+	}
+
+	let offset_position = position + normal * (3.0 * radius / shadows.directional_size);
+	let clip = shadows.cascades[cascade] * vec4<f32>(offset_position, 1.0);
+	let uv = clip.xy * vec2<f32>(0.5, -0.5) + vec2<f32>(0.5, 0.5);
+
+	return pcf_directional(uv, cascade, clip.z);
+}
+
+fn point_shadow(shadow: i32, light_position: vec3<f32>, position: vec3<f32>, normal: vec3<f32>) -> f32 {
+	let dist = length(position - light_position);
+	let offset_position = position + normal * (3.0 * dist / shadows.point_size);
+
+	let to_fragment = offset_position - light_position;
+	let axis = abs(to_fragment);
+
+	// faces in the order of `cube_face_matrix` in `shadow.rs`
+	var face: i32 = 0;
+	if (axis.x >= axis.y && axis.x >= axis.z) {
+		if (to_fragment.x < 0.0) { face = 1; }
+	} else {
+		if (axis.y >= axis.z) {
+			face = 2;
+			if (to_fragment.y < 0.0) { face = 3; }
+		} else {
+			face = 4;
+			if (to_fragment.z < 0.0) { face = 5; }
+		}
+	}
+
+	let layer = shadow * 6 + face;
+	let clip = shadows.point_faces[layer] * vec4<f32>(offset_position, 1.0);
+	let ndc = clip.xyz / clip.w;
+	let uv = ndc.xy * vec2<f32>(0.5, -0.5) + vec2<f32>(0.5, 0.5);
+
+	return pcf_point(uv, layer, ndc.z);
+}
+
 [[stage(fragment)]]
 fn main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
-	var light: vec3<f32>;
+	let base_color = material.base_color * in.color
+		* textureSample(base_color_texture, base_color_sampler, in.uv);
+
+	let metallic_roughness = textureSample(metallic_roughness_texture, metallic_roughness_sampler, in.uv);
+	let metallic = material.metallic * metallic_roughness.b;
+	let roughness = clamp(material.roughness * metallic_roughness.g, 0.045, 1.0);
+	let alpha = roughness * roughness;
+
+	let normal_map = textureSample(normal_texture, normal_sampler, in.uv).rgb;
+
+	let geometric_normal = normalize(in.w_normal);
+
+	var normal: vec3<f32> = geometric_normal;
+	// `StandardMaterial::NORMAL_TEXTURE`
+	if ((material.flags & 1u32) != 0u32) {
+		normal = perturb_normal(normal, in.w_position, in.uv, normal_map);
+	}
+
+	let view = normalize(camera.position.xyz - in.w_position);
+
+	let diffuse_color = base_color.rgb * (1.0 - metallic);
+	let f0 = mix(vec3<f32>(0.04, 0.04, 0.04), base_color.rgb, vec3<f32>(metallic, metallic, metallic));
 
-	light = lights.ambient_color.rgb * lights.ambient_intensity;
+	// without environment lighting the ambient light is reflected as a whole
+	var light: vec3<f32> = lights.ambient_color.rgb * lights.ambient_intensity
+		* (diffuse_color + f0);
 
 	var i: u32 = 0u32;
 	loop {
 		if (i >= lights.point_lights_len) { break; }
 
-		let point_light: PointLight = lights.point_lights[i];
+		let point_light: PointLight = point_lights.lights[i];
 
-		let pos_to_light = point_light.position - in.w_position;
-		let dist = length(pos_to_light);
-		let light_dir = normalize(pos_to_light);
-		
-		let diffuse = max(dot(light_dir, in.w_normal), 0.0);
+		let to_light = point_light.position - in.w_position;
+		let dist = length(to_light);
+		let light_dir = to_light / dist;
 
-		let intensity = 1.0 / pow(dist, 2.0) * point_light.intensity;
+		let n_dot_l = max(dot(normal, light_dir), 0.0);
+		let radiance = point_light.color * point_light.intensity
+			* attenuation(dist, point_light.range);
 
-		light = light + point_light.color.rgb * diffuse * intensity;
+		var shadow: f32 = 1.0;
+		if (point_light.shadow >= 0) {
+			shadow = point_shadow(point_light.shadow, point_light.position, in.w_position, geometric_normal);
+		}
+
+		light = light + brdf(diffuse_color, f0, alpha, normal, view, light_dir) * radiance * n_dot_l * shadow;
 
 		i = i + 1u32;
 	}
 
-	let color = vec4<f32>(light, 1.0) * in.color;
+	i = 0u32;
+	loop {
+		if (i >= lights.directional_lights_len) { break; }
+
+		let directional_light: DirectionalLight = directional_lights.lights[i];
+
+		let light_dir = -directional_light.direction;
 
-	return color;
-}
\ No newline at end of file
+		let n_dot_l = max(dot(normal, light_dir), 0.0);
+		let radiance = directional_light.color * directional_light.intensity;
+
+		var shadow: f32 = 1.0;
+		if (directional_light.shadow >= 0) {
+			shadow = directional_shadow(in.w_position, geometric_normal);
+		}
+
+		light = light + brdf(diffuse_color, f0, alpha, normal, view, light_dir) * radiance * n_dot_l * shadow;
+
+		i = i + 1u32;
+	}
+
+	i = 0u32;
+	loop {
+		if (i >= lights.spot_lights_len) { break; }
+
+		let spot_light: SpotLight = spot_lights.lights[i];
+
+		let to_light = spot_light.position - in.w_position;
+		let dist = length(to_light);
+		let light_dir = to_light / dist;
+
+		let cone = spot_cone(dot(-light_dir, spot_light.direction), spot_light.inner_cos, spot_light.outer_cos);
+
+		let n_dot_l = max(dot(normal, light_dir), 0.0);
+		let radiance = spot_light.color * spot_light.intensity
+			* attenuation(dist, spot_light.range) * cone;
+
+		light = light + brdf(diffuse_color, f0, alpha, normal, view, light_dir) * radiance * n_dot_l;
+
+		i = i + 1u32;
+	}
+
+	let color = light + material.emissive.rgb;
+
+	// discarded last, textures and derivatives can't be sampled after it
+	if (base_color.a < material.alpha_cutoff) {
+		discard;
+	}
+
+	return vec4<f32>(color, base_color.a);
+}
@@ -0,0 +1,165 @@
+use glam::Vec3;
+use nova_assets::{
+    server::{AssetServer, LoadState},
+    Assets, Handle, InnerHandle,
+};
+use nova_core::{spawners::Preview, IntoQuery, Resources, World};
+use nova_render::{
+    bounds::Aabb,
+    camera::{Camera, Cameras, MainCamera},
+    color::Color,
+    component::MeshInstance,
+    light::{AmbientLight, DirectionalLight},
+    mesh::MeshData,
+};
+use nova_transform::component::{GlobalTransform, Transform};
+
+use crate::{gltf::SpawnScene, material::StandardMaterial, Vertex3d, PBR_PIPELINE_HANDLE};
+
+/// Vertical field of view of the thumbnail camera.
+const FOV: f32 = 0.8;
+
+/// Direction the thumbnail camera looks at the meshes from.
+const VIEW_DIRECTION: Vec3 = Vec3::new(1.0, 0.8, 1.4);
+
+/// Marks the light spawned for a thumbnail.
+struct PreviewLight;
+
+/// State of `handle` and its dependencies, handles made in code aren't loaded by the server
+/// and count as loaded.
+#[inline]
+fn handle_state<T: 'static>(server: &AssetServer, handle: &Handle<T>) -> LoadState {
+    match handle.inner() {
+        InnerHandle::Id(_) => LoadState::Loaded,
+        _ => server.recursive_load_state(handle),
+    }
+}
+
+/// State of the assets spawned for the thumbnail, only their own loads are checked so
+/// other loads of the server don't affect it.
+#[inline]
+fn spawned_state(world: &World, server: &AssetServer) -> LoadState {
+    let scenes = <&SpawnScene>::query()
+        .iter(world)
+        .map(|spawn| handle_state(server, &spawn.0));
+    let meshes = <&MeshInstance>::query()
+        .iter(world)
+        .map(|mesh_instance| handle_state(server, &mesh_instance.mesh_data));
+    let materials = <&Handle<StandardMaterial>>::query()
+        .iter(world)
+        .map(|material| handle_state(server, material));
+
+    let mut state = LoadState::Loaded;
+
+    for handle_state in scenes.chain(meshes).chain(materials) {
+        match handle_state {
+            LoadState::Loaded => {}
+            LoadState::Failed(err) => return LoadState::Failed(err),
+            _ => state = LoadState::Loading,
+        }
+    }
+
+    state
+}
+
+/// Bounds of the pbr meshes in `world`, in world space.
+#[inline]
+fn mesh_bounds(world: &World, meshes: &Assets<MeshData>) -> Option<Aabb> {
+    let mut points = Vec::new();
+
+    for (mesh_instance, global_transform) in
+        <(&MeshInstance, &GlobalTransform)>::query().iter(world)
+    {
+        if mesh_instance.pipeline != PBR_PIPELINE_HANDLE {
+            continue;
+        }
+
+        let mesh_data = match meshes.get(&mesh_instance.mesh_data) {
+            Some(mesh_data) => mesh_data,
+            None => continue,
+        };
+
+        let matrix = global_transform.matrix();
+
+        // the vertex data isn't aligned for casting, the position comes first in each vertex
+        for vertex in mesh_data
+            .vertices
+            .chunks_exact(std::mem::size_of::<Vertex3d>())
+        {
+            let mut position = [0.0f32; 3];
+            bytemuck::bytes_of_mut(&mut position).copy_from_slice(&vertex[..12]);
+
+            points.push(matrix.transform_point3(Vec3::from(position)));
+        }
+    }
+
+    Aabb::from_points(points)
+}
+
+/// Frames the pbr meshes spawned for an `.obj` or glTF thumbnail with a camera and lights
+/// them, see [`AppBuilder::register_asset_preview`](nova_core::AppBuilder::register_asset_preview).
+pub fn preview_meshes(world: &mut World, resources: &mut Resources) -> Preview {
+    let state = spawned_state(world, &resources.get::<AssetServer>().unwrap());
+
+    match state {
+        LoadState::Failed(err) => return Preview::Failed(format!("failed to load: {}", err)),
+        LoadState::Loaded => {}
+        _ => return Preview::Loading,
+    }
+
+    // scenes are spawned in the update after they load
+    if <&SpawnScene>::query().iter(world).next().is_some() {
+        return Preview::Loading;
+    }
+
+    // the transforms of spawned scenes are propagated in the next update
+    if <&PreviewLight>::query().iter(world).next().is_none() {
+        let mut transform = Transform::from_xyz(0.6, 1.0, 0.8);
+        transform.look_at(Vec3::ZERO, Vec3::Y);
+
+        world.push((
+            PreviewLight,
+            transform.clone(),
+            GlobalTransform(transform),
+            DirectionalLight {
+                color: Color::WHITE,
+                intensity: 3.0,
+                casts_shadows: false,
+            },
+        ));
+
+        resources.insert(AmbientLight {
+            color: Color::WHITE,
+            intensity: 0.3,
+        });
+
+        return Preview::Loading;
+    }
+
+    let bounds = match mesh_bounds(world, &resources.get::<Assets<MeshData>>().unwrap()) {
+        Some(bounds) => bounds,
+        None => return Preview::Failed(String::from("the asset has no meshes")),
+    };
+
+    let radius = bounds.half_extents().length().max(0.001);
+    let distance = radius / (FOV / 2.0).sin();
+
+    let mut transform =
+        Transform::from_translation(bounds.center() + VIEW_DIRECTION.normalize() * distance);
+    transform.look_at(bounds.center(), Vec3::Y);
+
+    let camera = world.push((
+        transform.clone(),
+        GlobalTransform(transform),
+        Camera::Perspective {
+            fov: FOV,
+            aspect: 1.0,
+            near: (distance - radius).max(distance * 0.01),
+        },
+        MainCamera,
+    ));
+
+    resources.get_mut::<Cameras>().unwrap().main = Some(camera);
+
+    Preview::Ready
+}
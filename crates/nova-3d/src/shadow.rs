@@ -0,0 +1,762 @@
+use std::{
+    collections::HashMap,
+    f32::consts::FRAC_PI_2,
+    num::{NonZeroU32, NonZeroU64},
+    ops::Range,
+};
+
+use bytemuck::{bytes_of, cast_slice};
+use glam::{Mat4, Vec3};
+use nova_assets::{
+    event::{AssetEvent, AssetEventReader},
+    Assets, Handle, WeakHandle,
+};
+use nova_core::{component, IntoQuery, Resources, World};
+use nova_render::{
+    camera::Cameras,
+    component::MeshInstance,
+    image::Image,
+    light::{ShadowCasters, MAX_POINT_SHADOWS},
+    light_node::LightNode,
+    mesh::MeshData,
+    render_node::{RenderData, RenderNode, Target},
+    Vertex,
+};
+use nova_transform::component::GlobalTransform;
+use nova_wgpu::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    instance_layout,
+    material::{AlphaMode, StandardMaterial, DEFAULT_MATERIAL_HANDLE},
+    stage::{MaterialData, MaterialDefaults, MeshBuffers},
+    Vertex3d, PBR_PIPELINE_HANDLE,
+};
+
+/// Cascades of the directional shadow map, each covering a larger distance from the camera.
+pub const CASCADES: usize = 4;
+
+/// Faces of the cube shadow map of a point light, in the order of [`cube_face_matrix`].
+pub const CUBE_FACES: usize = 6;
+
+pub const SHADOW_FORMAT: TextureFormat = TextureFormat::Depth32Float;
+
+/// Light matrices rendered with, the cascades followed by the faces of the point lights.
+const LIGHT_MATRICES: usize = CASCADES + MAX_POINT_SHADOWS * CUBE_FACES;
+
+/// Dynamic offsets into the light matrix buffer have to be aligned to this.
+const MATRIX_ALIGNMENT: usize = 256;
+
+/// Near plane of the point light faces.
+const POINT_NEAR: f32 = 0.05;
+
+/// Mesh entities with this component don't cast shadows, they still receive them.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NotShadowCaster;
+
+#[derive(Clone, Debug)]
+pub struct ShadowSettings {
+    /// Width and height of each cascade of the directional shadow map.
+    pub directional_size: u32,
+    /// Width and height of each face of the point light shadow maps.
+    pub point_size: u32,
+    /// Distance from the camera covered by each cascade, increasing. Fragments past the
+    /// last cascade aren't shadowed.
+    pub cascades: [f32; CASCADES],
+    /// How far behind a cascade, seen from the light, meshes still cast shadows into it.
+    pub caster_distance: f32,
+}
+
+impl Default for ShadowSettings {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            directional_size: 2048,
+            point_size: 512,
+            cascades: [8.0, 24.0, 64.0, 160.0],
+            caster_distance: 100.0,
+        }
+    }
+}
+
+/// Layout of the `Shadows` uniform in `pbr.wgsl`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct ShadowsRaw {
+    pub cascades: [Mat4; CASCADES],
+    pub point_faces: [Mat4; MAX_POINT_SHADOWS * CUBE_FACES],
+    pub cascade_splits: [f32; CASCADES],
+    pub directional_size: f32,
+    pub point_size: f32,
+    pub _padding: [f32; 2],
+}
+
+unsafe impl bytemuck::Zeroable for ShadowsRaw {}
+unsafe impl bytemuck::Pod for ShadowsRaw {}
+
+/// Shadow maps sampled by the pbr pipelines.
+pub struct ShadowMaps {
+    /// A layer per cascade.
+    pub directional: TextureView<'static>,
+    /// [`CUBE_FACES`] layers per point light.
+    pub point: TextureView<'static>,
+    /// Comparison sampler filtering the results of the depth comparison.
+    pub sampler: Sampler,
+}
+
+/// Textures of the [`ShadowMaps`] and views of their layers to render to.
+struct ShadowTextures {
+    directional_size: u32,
+    point_size: u32,
+    #[allow(unused)]
+    directional: Texture,
+    #[allow(unused)]
+    point: Texture,
+    /// A view for each light matrix.
+    layers: Vec<TextureView<'static>>,
+}
+
+impl ShadowTextures {
+    #[inline]
+    fn new(instance: &Instance, settings: &ShadowSettings) -> (Self, ShadowMaps) {
+        let directional = depth_texture(
+            instance,
+            "directional_shadow_map",
+            settings.directional_size,
+            CASCADES as u32,
+        );
+        let point = depth_texture(
+            instance,
+            "point_shadow_map",
+            settings.point_size,
+            (MAX_POINT_SHADOWS * CUBE_FACES) as u32,
+        );
+
+        let layer_view = |texture: &Texture, layer| {
+            texture.create_view(&TextureViewDescriptor {
+                label: Some("shadow_map_layer"),
+                dimension: Some(TextureViewDimension::D2),
+                base_array_layer: layer as u32,
+                array_layer_count: NonZeroU32::new(1),
+                ..Default::default()
+            })
+        };
+
+        let array_view = |texture: &Texture| {
+            texture.create_view(&TextureViewDescriptor {
+                label: Some("shadow_map"),
+                dimension: Some(TextureViewDimension::D2Array),
+                ..Default::default()
+            })
+        };
+
+        let layers = (0..CASCADES)
+            .map(|layer| layer_view(&directional, layer))
+            .chain((0..MAX_POINT_SHADOWS * CUBE_FACES).map(|layer| layer_view(&point, layer)))
+            .collect();
+
+        let sampler = instance.create_sampler(&SamplerDescriptor {
+            label: Some("shadow_map"),
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            address_mode_w: AddressMode::ClampToEdge,
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            compare: Some(CompareFunction::LessEqual),
+            ..Default::default()
+        });
+
+        let maps = ShadowMaps {
+            directional: array_view(&directional),
+            point: array_view(&point),
+            sampler,
+        };
+
+        let textures = Self {
+            directional_size: settings.directional_size,
+            point_size: settings.point_size,
+            directional,
+            point,
+            layers,
+        };
+
+        (textures, maps)
+    }
+}
+
+#[inline]
+fn depth_texture(instance: &Instance, label: &str, size: u32, layers: u32) -> Texture {
+    instance.create_texture(&TextureDescriptor {
+        label: Some(label),
+        size: Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: layers,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: SHADOW_FORMAT,
+        usage: TextureUsage::RENDER_ATTACHMENT | TextureUsage::SAMPLED,
+    })
+}
+
+/// Orthographic projection along `direction` covering a sphere of `radius` around
+/// `center`. The center is snapped to texels so the shadow doesn't shimmer when the camera
+/// moves.
+#[inline]
+pub fn cascade_matrix(
+    center: Vec3,
+    direction: Vec3,
+    radius: f32,
+    caster_distance: f32,
+    size: u32,
+) -> Mat4 {
+    let up = if direction.y.abs() > 0.99 {
+        Vec3::Z
+    } else {
+        Vec3::Y
+    };
+
+    let rotation = Mat4::look_at_rh(Vec3::ZERO, direction, up);
+    let texel = 2.0 * radius / size as f32;
+
+    let mut light_center = rotation.transform_point3(center);
+    light_center.x = (light_center.x / texel).floor() * texel;
+    light_center.y = (light_center.y / texel).floor() * texel;
+    let center = rotation.inverse().transform_point3(light_center);
+
+    let eye = center - direction * (radius + caster_distance);
+    let view = Mat4::look_at_rh(eye, center, up);
+    let proj = Mat4::orthographic_rh(
+        -radius,
+        radius,
+        -radius,
+        radius,
+        0.0,
+        2.0 * radius + caster_distance,
+    );
+
+    proj * view
+}
+
+/// Perspective projection of a face of the cube around a point light, the faces look
+/// along +x, -x, +y, -y, +z and -z like the face picked in `pbr.wgsl`.
+#[inline]
+pub fn cube_face_matrix(position: Vec3, range: f32, face: usize) -> Mat4 {
+    let (forward, up) = match face {
+        0 => (Vec3::X, Vec3::Y),
+        1 => (-Vec3::X, Vec3::Y),
+        2 => (Vec3::Y, Vec3::Z),
+        3 => (-Vec3::Y, Vec3::Z),
+        4 => (Vec3::Z, Vec3::Y),
+        _ => (-Vec3::Z, Vec3::Y),
+    };
+
+    let view = Mat4::look_at_rh(position, position + forward, up);
+    let proj = if range.is_finite() {
+        Mat4::perspective_rh(FRAC_PI_2, 1.0, POINT_NEAR, range)
+    } else {
+        Mat4::perspective_infinite_rh(FRAC_PI_2, 1.0, POINT_NEAR)
+    };
+
+    proj * view
+}
+
+/// Shadow casting meshes drawn with the same pipeline and bind groups.
+/// Fills the light matrices of `raw` for the shadow casting lights and returns the indices of
+/// the maps rendered this frame. Each of them is cleared even when nothing casts a shadow,
+/// so maps never keep the depth of removed casters.
+#[inline]
+fn light_passes(
+    raw: &mut ShadowsRaw,
+    casters: &ShadowCasters,
+    settings: &ShadowSettings,
+    camera_position: Vec3,
+) -> Vec<usize> {
+    raw.cascade_splits = settings.cascades;
+    raw.directional_size = settings.directional_size as f32;
+    raw.point_size = settings.point_size as f32;
+
+    let mut passes = Vec::new();
+
+    if let Some(direction) = casters.directional {
+        for (cascade, &radius) in settings.cascades.iter().enumerate() {
+            raw.cascades[cascade] = cascade_matrix(
+                camera_position,
+                direction,
+                radius,
+                settings.caster_distance,
+                settings.directional_size,
+            );
+
+            passes.push(cascade);
+        }
+    }
+
+    for (light, caster) in casters.point.iter().enumerate() {
+        for face in 0..CUBE_FACES {
+            let index = light * CUBE_FACES + face;
+            raw.point_faces[index] = cube_face_matrix(caster.position, caster.range, face);
+
+            passes.push(CASCADES + index);
+        }
+    }
+
+    passes
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct CasterHandle {
+    mesh_data: WeakHandle<MeshData>,
+    /// Only set for masked materials, the others don't need their material.
+    material: Option<WeakHandle<StandardMaterial>>,
+}
+
+/// Renders the depth of the shadow casting meshes from the lights in
+/// [`LightNode::SHADOW_CASTERS`] into the [`ShadowMaps`]. Meshes with masked materials
+/// discard the same fragments as in the pbr pipelines, blended meshes don't cast shadows.
+pub struct ShadowNode {
+    pipeline: RenderPipeline,
+    masked_pipeline: RenderPipeline,
+    matrix_buffer: Buffer,
+    matrix_bind_group: BindGroup,
+    textures: Option<ShadowTextures>,
+    generation: u64,
+    mesh_buffers: HashMap<WeakHandle<MeshData>, MeshBuffers>,
+    materials: HashMap<WeakHandle<StandardMaterial>, MaterialData>,
+    material_defaults: MaterialDefaults,
+    casters: HashMap<CasterHandle, Vec<Mat4>>,
+    instance_buffer: Option<Buffer>,
+    instance_capacity: usize,
+    mesh_events: AssetEventReader<MeshData>,
+    material_events: AssetEventReader<StandardMaterial>,
+    image_events: AssetEventReader<Image>,
+}
+
+impl ShadowNode {
+    /// [`ShadowsRaw`] uniform with the light matrices.
+    pub const BUFFER: &'static str = "shadows_buffer";
+    /// [`ShadowMaps`] of the lights.
+    pub const MAPS: &'static str = "shadow_maps";
+    /// `u64` incremented whenever the shadow maps are created again.
+    pub const GENERATION: &'static str = "shadow_maps_generation";
+
+    #[inline]
+    pub fn new(instance: &Instance) -> Self {
+        let shader_module = instance.create_shader_module(&ShaderModuleDescriptor {
+            label: Some("shadow"),
+            source: ShaderSource::Wgsl(include_str!("shadow.wgsl").into()),
+            flags: ShaderFlags::all(),
+        });
+
+        let matrix_layout = instance.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("shadow_light"),
+            entries: &[BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStage::VERTEX,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: true,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let layout = instance.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("shadow"),
+            bind_group_layouts: &[&matrix_layout],
+            push_constant_ranges: &[],
+        });
+
+        let material_defaults = MaterialDefaults::new(instance);
+
+        let masked_layout = instance.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("shadow_masked"),
+            bind_group_layouts: &[&matrix_layout, &material_defaults.layout],
+            push_constant_ranges: &[],
+        });
+
+        let depth_stencil = DepthStencilState {
+            format: SHADOW_FORMAT,
+            depth_write_enabled: true,
+            depth_compare: CompareFunction::LessEqual,
+            stencil: StencilState::default(),
+            bias: DepthBiasState {
+                constant: 2,
+                slope_scale: 2.0,
+                clamp: 0.0,
+            },
+        };
+
+        let pipeline = instance.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("shadow"),
+            layout: Some(&layout),
+            vertex: VertexState {
+                module: &shader_module,
+                buffers: &[Vertex3d::layout(), instance_layout()],
+                entry_point: "main",
+            },
+            fragment: None,
+            // both faces cast shadows, for double sided materials and open meshes
+            primitive: PrimitiveState::default(),
+            multisample: MultisampleState::default(),
+            depth_stencil: Some(depth_stencil.clone()),
+        });
+
+        let masked_pipeline = instance.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("shadow_masked"),
+            layout: Some(&masked_layout),
+            vertex: VertexState {
+                module: &shader_module,
+                buffers: &[Vertex3d::layout(), instance_layout()],
+                entry_point: "masked",
+            },
+            fragment: Some(FragmentState {
+                module: &shader_module,
+                entry_point: "main",
+                targets: &[],
+            }),
+            primitive: PrimitiveState::default(),
+            multisample: MultisampleState::default(),
+            depth_stencil: Some(depth_stencil),
+        });
+
+        let matrix_buffer = instance.create_buffer(&BufferDescriptor {
+            label: Some("shadow_light_matrices"),
+            size: (LIGHT_MATRICES * MATRIX_ALIGNMENT) as u64,
+            usage: BufferUsage::COPY_DST | BufferUsage::UNIFORM,
+            mapped_at_creation: false,
+        });
+
+        let matrix_bind_group = instance.create_bind_group(&BindGroupDescriptor {
+            label: Some("shadow_light"),
+            layout: &matrix_layout,
+            entries: &[BindGroupEntry {
+                binding: 0,
+                resource: BindingResource::Buffer(BufferBinding {
+                    buffer: &matrix_buffer,
+                    offset: 0,
+                    size: NonZeroU64::new(64),
+                }),
+            }],
+        });
+
+        Self {
+            pipeline,
+            masked_pipeline,
+            matrix_buffer,
+            matrix_bind_group,
+            textures: None,
+            generation: 0,
+            mesh_buffers: HashMap::new(),
+            materials: HashMap::new(),
+            material_defaults,
+            casters: HashMap::new(),
+            instance_buffer: None,
+            instance_capacity: 0,
+            mesh_events: AssetEventReader::default(),
+            material_events: AssetEventReader::default(),
+            image_events: AssetEventReader::default(),
+        }
+    }
+
+    /// Groups the model matrices of the shadow casting meshes by mesh, and by material for
+    /// masked materials.
+    #[inline]
+    fn collect_casters(&mut self, world: &World, resources: &Resources) {
+        self.casters.clear();
+
+        let meshes = resources.get::<Assets<MeshData>>().unwrap();
+        let materials = resources.get::<Assets<StandardMaterial>>().unwrap();
+
+        let default_material = DEFAULT_MATERIAL_HANDLE;
+
+        for (mesh_instance, global_transform, material_handle) in <(
+            &MeshInstance,
+            &GlobalTransform,
+            Option<&Handle<StandardMaterial>>,
+        )>::query()
+        .filter(!component::<NotShadowCaster>())
+        .iter(world)
+        {
+            if mesh_instance.pipeline != PBR_PIPELINE_HANDLE
+                || !meshes.contains(&mesh_instance.mesh_data)
+            {
+                continue;
+            }
+
+            // blended meshes are see-through and meshes with loading materials aren't drawn
+            let material_handle = material_handle.unwrap_or(&default_material);
+            let material = match materials.get(material_handle) {
+                Some(material) => material,
+                None => continue,
+            };
+
+            let material = match material.alpha_mode {
+                AlphaMode::Opaque => None,
+                AlphaMode::Mask(_) => Some(material_handle.downgrade()),
+                AlphaMode::Blend => continue,
+            };
+
+            let handle = CasterHandle {
+                mesh_data: mesh_instance.mesh_data.downgrade(),
+                material,
+            };
+
+            self.casters
+                .entry(handle)
+                .or_default()
+                .push(global_transform.matrix());
+        }
+    }
+}
+
+impl RenderNode for ShadowNode {
+    #[inline]
+    fn run(
+        &mut self,
+        world: &World,
+        resources: &Resources,
+        _target: &Target,
+        data: &mut RenderData,
+    ) {
+        let settings = resources.get::<ShadowSettings>().unwrap().clone();
+        let instance = resources.get::<Instance>().unwrap();
+
+        if self.textures.as_ref().map_or(true, |textures| {
+            textures.directional_size != settings.directional_size
+                || textures.point_size != settings.point_size
+        }) {
+            let (textures, maps) = ShadowTextures::new(&instance, &settings);
+
+            self.textures = Some(textures);
+            self.generation += 1;
+
+            data.insert(Self::MAPS, maps);
+        }
+
+        data.insert(Self::GENERATION, self.generation);
+
+        let casters = data
+            .get::<ShadowCasters>(LightNode::SHADOW_CASTERS)
+            .unwrap()
+            .clone();
+
+        let camera_position = resources
+            .get::<Cameras>()
+            .unwrap()
+            .main
+            .and_then(|main| <&GlobalTransform>::query().get(world, main).ok())
+            .map_or(Vec3::ZERO, |transform| transform.translation);
+
+        let mut raw: ShadowsRaw = bytemuck::Zeroable::zeroed();
+        let passes = light_passes(&mut raw, &casters, &settings, camera_position);
+
+        if let Some(buffer) = data.get::<Buffer>(Self::BUFFER) {
+            instance.write_buffer(buffer, 0, bytes_of(&raw));
+        } else {
+            let buffer = instance.create_buffer_init(&BufferInitDescriptor {
+                label: Some("shadows"),
+                contents: bytes_of(&raw),
+                usage: BufferUsage::COPY_DST | BufferUsage::UNIFORM,
+            });
+
+            data.insert(Self::BUFFER, buffer);
+        }
+
+        if passes.is_empty() {
+            return;
+        }
+
+        let mut matrix_data = vec![0; LIGHT_MATRICES * MATRIX_ALIGNMENT];
+        let matrices = raw.cascades.iter().chain(raw.point_faces.iter());
+
+        for (index, matrix) in matrices.enumerate() {
+            let offset = index * MATRIX_ALIGNMENT;
+            matrix_data[offset..offset + 64].copy_from_slice(bytes_of(matrix));
+        }
+
+        instance.write_buffer(&self.matrix_buffer, 0, &matrix_data);
+
+        let meshes = resources.get::<Assets<MeshData>>().unwrap();
+
+        let materials = resources.get::<Assets<StandardMaterial>>().unwrap();
+        let images = resources.get::<Assets<Image>>().unwrap();
+
+        for event in self.mesh_events.read(meshes.events()) {
+            if let AssetEvent::Modified(handle) | AssetEvent::Removed(handle) = event {
+                self.mesh_buffers.remove(handle);
+            }
+        }
+
+        for event in self.material_events.read(materials.events()) {
+            if let AssetEvent::Modified(handle) | AssetEvent::Removed(handle) = event {
+                self.materials.remove(handle);
+            }
+        }
+
+        for event in self.image_events.read(images.events()) {
+            if let AssetEvent::Modified(_) | AssetEvent::Removed(_) = event {
+                self.materials.clear();
+            }
+        }
+
+        self.collect_casters(world, resources);
+
+        // instances of all meshes share a buffer, each mesh draws its range of it
+        let mut transforms = Vec::new();
+        let mut ranges: Vec<(&CasterHandle, Range<u32>)> = Vec::new();
+
+        for (handle, transform) in &self.casters {
+            let start = transforms.len() as u32;
+            transforms.extend_from_slice(transform);
+            ranges.push((handle, start..transforms.len() as u32));
+
+            if !self.mesh_buffers.contains_key(&handle.mesh_data) {
+                let mesh_data = meshes.get(&handle.mesh_data).unwrap();
+                let buffers = MeshBuffers::new(&instance, mesh_data);
+                self.mesh_buffers.insert(handle.mesh_data.clone(), buffers);
+            }
+
+            if let Some(material) = &handle.material {
+                if !self.materials.contains_key(material) {
+                    let data = MaterialData::new(
+                        &instance,
+                        materials.get(material).unwrap(),
+                        &self.material_defaults,
+                        &images,
+                    );
+                    self.materials.insert(material.clone(), data);
+                }
+            }
+        }
+
+        // opaque casters first, so the pipeline only changes once per pass
+        ranges.sort_by_key(|(handle, _)| handle.material.is_some());
+
+        // without casters the maps are still cleared, sampling stale or zeroed depth would
+        // shadow everything
+        if !transforms.is_empty()
+            && (self.instance_buffer.is_none() || transforms.len() > self.instance_capacity)
+        {
+            self.instance_capacity = transforms.len().next_power_of_two();
+
+            self.instance_buffer = Some(instance.create_buffer(&BufferDescriptor {
+                label: Some("shadow_instances"),
+                size: (self.instance_capacity * 64) as u64,
+                usage: BufferUsage::COPY_DST | BufferUsage::VERTEX,
+                mapped_at_creation: false,
+            }));
+        }
+
+        let instance_buffer = self
+            .instance_buffer
+            .as_ref()
+            .filter(|_| !transforms.is_empty());
+
+        if let Some(instance_buffer) = instance_buffer {
+            instance.write_buffer(instance_buffer, 0, cast_slice(&transforms));
+        }
+
+        let textures = self.textures.as_ref().unwrap();
+
+        let mut encoder = instance.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("shadow pass"),
+        });
+
+        for &index in &passes {
+            let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+                label: Some("shadow pass"),
+                color_attachments: &[],
+                depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
+                    view: &textures.layers[index],
+                    depth_ops: Some(Operations {
+                        load: LoadOp::Clear(1.0),
+                        store: true,
+                    }),
+                    stencil_ops: None,
+                }),
+            });
+
+            let instance_buffer = match instance_buffer {
+                Some(instance_buffer) => instance_buffer,
+                None => continue,
+            };
+
+            render_pass.set_pipeline(&self.pipeline);
+            render_pass.set_bind_group(
+                0,
+                &self.matrix_bind_group,
+                &[(index * MATRIX_ALIGNMENT) as u32],
+            );
+            render_pass.set_vertex_buffer(1, instance_buffer.slice(..));
+
+            let mut masked = false;
+
+            for (handle, range) in &ranges {
+                if let Some(material) = &handle.material {
+                    if !masked {
+                        render_pass.set_pipeline(&self.masked_pipeline);
+                        masked = true;
+                    }
+
+                    let material = self.materials.get(material).unwrap();
+                    render_pass.set_bind_group(1, &material.bind_group, &[]);
+                }
+
+                let mesh_buffers = self.mesh_buffers.get(&handle.mesh_data).unwrap();
+                let indices = meshes.get(&handle.mesh_data).unwrap().indices.len() as u32;
+
+                render_pass.set_vertex_buffer(0, mesh_buffers.vertex.slice(..));
+                render_pass.set_index_buffer(mesh_buffers.index.slice(..), IndexFormat::Uint32);
+
+                render_pass.draw_indexed(0..indices, 0, range.clone());
+            }
+        }
+
+        instance.submit(encoder);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nova_render::light::PointShadowCaster;
+
+    use super::*;
+
+    #[test]
+    fn lights_render_every_map_without_casters() {
+        let casters = ShadowCasters {
+            directional: Some(-Vec3::Y),
+            point: vec![PointShadowCaster {
+                position: Vec3::ZERO,
+                range: 10.0,
+            }],
+        };
+
+        let mut raw: ShadowsRaw = bytemuck::Zeroable::zeroed();
+        let passes = light_passes(&mut raw, &casters, &ShadowSettings::default(), Vec3::ZERO);
+
+        // the passes only depend on the lights, the node clears each of them before drawing
+        assert_eq!(passes, (0..CASCADES + CUBE_FACES).collect::<Vec<_>>());
+        assert!(raw.cascades.iter().all(|matrix| *matrix != Mat4::ZERO));
+        assert!(raw.point_faces[..CUBE_FACES]
+            .iter()
+            .all(|matrix| *matrix != Mat4::ZERO));
+    }
+
+    #[test]
+    fn no_lights_render_no_maps() {
+        let mut raw: ShadowsRaw = bytemuck::Zeroable::zeroed();
+        let passes = light_passes(
+            &mut raw,
+            &ShadowCasters::default(),
+            &ShadowSettings::default(),
+            Vec3::ZERO,
+        );
+
+        assert!(passes.is_empty());
+    }
+}
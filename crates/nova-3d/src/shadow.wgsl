@@ -0,0 +1,92 @@
+struct VertexInput {
+	[[location(0)]] position: vec3<f32>;
+	[[location(4)]] model_0: vec4<f32>;
+	[[location(5)]] model_1: vec4<f32>;
+	[[location(6)]] model_2: vec4<f32>;
+	[[location(7)]] model_3: vec4<f32>;
+};
+
+// matrix of the cascade or cube face rendered, at a dynamic offset
+[[block]]
+struct Light {
+	view_proj: mat4x4<f32>;
+};
+
+[[group(0), binding(0)]]
+var<uniform> light: Light;
+
+[[stage(vertex)]]
+fn main(in: VertexInput) -> [[builtin(position)]] vec4<f32> {
+	let model = mat4x4<f32>(
+		in.model_0,
+		in.model_1,
+		in.model_2,
+		in.model_3,
+	);
+
+	return light.view_proj * model * vec4<f32>(in.position, 1.0);
+}
+
+// masked materials, see `AlphaMode::Mask`, discard the fragments pbr.wgsl discards
+struct MaskedInput {
+	[[location(0)]] position: vec3<f32>;
+	[[location(2)]] uv: vec2<f32>;
+	[[location(3)]] color: vec4<f32>;
+	[[location(4)]] model_0: vec4<f32>;
+	[[location(5)]] model_1: vec4<f32>;
+	[[location(6)]] model_2: vec4<f32>;
+	[[location(7)]] model_3: vec4<f32>;
+};
+
+struct MaskedOutput {
+	[[builtin(position)]] position: vec4<f32>;
+	[[location(0)]] uv: vec2<f32>;
+	[[location(1)]] color: vec4<f32>;
+};
+
+// same layout as in pbr.wgsl, only the base color is used
+[[block]]
+struct Material {
+	base_color: vec4<f32>;
+	emissive: vec4<f32>;
+	metallic: f32;
+	roughness: f32;
+	alpha_cutoff: f32;
+	flags: u32;
+};
+
+[[group(1), binding(0)]]
+var<uniform> material: Material;
+
+[[group(1), binding(1)]]
+var base_color_texture: texture_2d<f32>;
+
+[[group(1), binding(2)]]
+var base_color_sampler: sampler;
+
+[[stage(vertex)]]
+fn masked(in: MaskedInput) -> MaskedOutput {
+	let model = mat4x4<f32>(
+		in.model_0,
+		in.model_1,
+		in.model_2,
+		in.model_3,
+	);
+
+	var out: MaskedOutput;
+	out.position = light.view_proj * model * vec4<f32>(in.position, 1.0);
+	out.uv = in.uv;
+	out.color = in.color;
+
+	return out;
+}
+
+[[stage(fragment)]]
+fn main(in: MaskedOutput) {
+	let alpha = material.base_color.a * in.color.a
+		* textureSample(base_color_texture, base_color_sampler, in.uv).a;
+
+	if (alpha < material.alpha_cutoff) {
+		discard;
+	}
+}
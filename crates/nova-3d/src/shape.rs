@@ -1,8 +1,509 @@
+use std::{
+    collections::HashMap,
+    f32::consts::{FRAC_PI_2, PI, TAU},
+};
+
 use glam::*;
 use nova_render::{color::Color, mesh::Mesh};
 
 use crate::Vertex3d;
 
+// shapes are centered on the origin with y up, triangles are counter clockwise seen from
+// outside like the back face culling of the pbr pipeline expects
+
+#[inline]
+fn vertex(position: Vec3, normal: Vec3, uv: Vec2) -> Vertex3d {
+    Vertex3d {
+        position,
+        normal,
+        uv,
+        color: Color::rgb(1.0, 1.0, 1.0),
+    }
+}
+
+/// Adds a grid of `(columns + 1) * (rows + 1)` vertices, with `u` going along the columns
+/// and `v` along the rows. The grid faces `dv x du`, triangles collapsed into a point,
+/// like at the poles of a sphere, are skipped.
+#[inline]
+fn grid(mesh: &mut Mesh<Vertex3d>, columns: u32, rows: u32, vertex: impl Fn(f32, f32) -> Vertex3d) {
+    let start = mesh.vertices.len() as u32;
+
+    for row in 0..=rows {
+        for column in 0..=columns {
+            let u = column as f32 / columns as f32;
+            let v = row as f32 / rows as f32;
+
+            mesh.vertices.push(vertex(u, v));
+        }
+    }
+
+    let index = |column: u32, row: u32| start + row * (columns + 1) + column;
+
+    for row in 0..rows {
+        for column in 0..columns {
+            let a = index(column, row);
+            let b = index(column + 1, row);
+            let c = index(column, row + 1);
+            let d = index(column + 1, row + 1);
+
+            for triangle in [[a, c, b], [b, c, d]] {
+                let [pa, pb, pc] = triangle.map(|i| mesh.vertices[i as usize].position);
+
+                if pa != pb && pb != pc && pc != pa {
+                    mesh.indices.extend_from_slice(&triangle);
+                }
+            }
+        }
+    }
+}
+
+/// Point of a profile revolved around the y axis.
+#[derive(Clone, Copy)]
+struct ProfilePoint {
+    radius: f32,
+    y: f32,
+    /// Normal in the radial direction and along y.
+    normal: Vec2,
+}
+
+/// Revolves a profile going down the outside of the shape around the y axis.
+#[inline]
+fn revolve(mesh: &mut Mesh<Vertex3d>, segments: u32, profile: &[ProfilePoint]) {
+    // v is proportional to the length along the profile
+    let mut lengths = vec![0.0];
+    for points in profile.windows(2) {
+        let length = Vec2::new(
+            points[1].radius - points[0].radius,
+            points[1].y - points[0].y,
+        );
+        lengths.push(lengths.last().unwrap() + length.length());
+    }
+
+    let total = lengths.last().copied().unwrap_or(0.0).max(f32::EPSILON);
+    let rows = profile.len() as u32 - 1;
+
+    grid(mesh, segments, rows, |u, v| {
+        let row = (v * rows as f32).round() as usize;
+        let point = profile[row];
+
+        let angle = u * TAU;
+        let direction = Vec3::new(angle.cos(), 0.0, -angle.sin());
+
+        vertex(
+            direction * point.radius + Vec3::Y * point.y,
+            direction * point.normal.x + Vec3::Y * point.normal.y,
+            Vec2::new(u, lengths[row] / total),
+        )
+    });
+}
+
+/// Square facing +z.
+pub struct Quad {
+    pub size: Vec2,
+}
+
+impl Default for Quad {
+    #[inline]
+    fn default() -> Self {
+        Self { size: Vec2::ONE }
+    }
+}
+
+impl Into<Mesh<Vertex3d>> for Quad {
+    #[inline]
+    fn into(self) -> Mesh<Vertex3d> {
+        let mut mesh = Mesh::default();
+
+        grid(&mut mesh, 1, 1, |u, v| {
+            let position = Vec3::new(u - 0.5, 0.5 - v, 0.0) * self.size.extend(1.0);
+
+            vertex(position, Vec3::Z, Vec2::new(u, v))
+        });
+
+        mesh
+    }
+}
+
+/// Flat grid facing +y, split into `subdivisions + 1` squares along each side.
+pub struct Plane {
+    pub size: Vec2,
+    pub subdivisions: u32,
+}
+
+impl Default for Plane {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            size: Vec2::ONE,
+            subdivisions: 0,
+        }
+    }
+}
+
+impl Into<Mesh<Vertex3d>> for Plane {
+    #[inline]
+    fn into(self) -> Mesh<Vertex3d> {
+        let mut mesh = Mesh::default();
+        let cells = self.subdivisions + 1;
+
+        grid(&mut mesh, cells, cells, |u, v| {
+            let position =
+                Vec3::new(u - 0.5, 0.0, v - 0.5) * Vec3::new(self.size.x, 0.0, self.size.y);
+
+            vertex(position, Vec3::Y, Vec2::new(u, v))
+        });
+
+        mesh
+    }
+}
+
+/// Sphere made of `sectors` around and `stacks` from pole to pole.
+pub struct UvSphere {
+    pub radius: f32,
+    pub sectors: u32,
+    pub stacks: u32,
+}
+
+impl Default for UvSphere {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            radius: 0.5,
+            sectors: 32,
+            stacks: 16,
+        }
+    }
+}
+
+impl Into<Mesh<Vertex3d>> for UvSphere {
+    #[inline]
+    fn into(self) -> Mesh<Vertex3d> {
+        let mut mesh = Mesh::default();
+
+        let profile = (0..=self.stacks)
+            .map(|stack| {
+                let angle = stack as f32 / self.stacks as f32 * PI;
+                // clamped so the bottom pole is a single point
+                let normal = Vec2::new(angle.sin().max(0.0), angle.cos());
+
+                ProfilePoint {
+                    radius: normal.x * self.radius,
+                    y: normal.y * self.radius,
+                    normal,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        revolve(&mut mesh, self.sectors, &profile);
+
+        mesh
+    }
+}
+
+/// Sphere made of evenly sized triangles by subdividing an icosahedron.
+///
+/// Vertices aren't split along the seam of the uvs, so textures are stretched across one
+/// column of triangles, use a [`UvSphere`] for textured spheres.
+pub struct Icosphere {
+    pub radius: f32,
+    pub subdivisions: u32,
+}
+
+impl Default for Icosphere {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            radius: 0.5,
+            subdivisions: 3,
+        }
+    }
+}
+
+impl Into<Mesh<Vertex3d>> for Icosphere {
+    #[inline]
+    fn into(self) -> Mesh<Vertex3d> {
+        let t = (1.0 + 5.0f32.sqrt()) / 2.0;
+
+        let mut positions = vec![
+            Vec3::new(-1.0, t, 0.0),
+            Vec3::new(1.0, t, 0.0),
+            Vec3::new(-1.0, -t, 0.0),
+            Vec3::new(1.0, -t, 0.0),
+            Vec3::new(0.0, -1.0, t),
+            Vec3::new(0.0, 1.0, t),
+            Vec3::new(0.0, -1.0, -t),
+            Vec3::new(0.0, 1.0, -t),
+            Vec3::new(t, 0.0, -1.0),
+            Vec3::new(t, 0.0, 1.0),
+            Vec3::new(-t, 0.0, -1.0),
+            Vec3::new(-t, 0.0, 1.0),
+        ];
+
+        for position in &mut positions {
+            *position = position.normalize();
+        }
+
+        let mut triangles: Vec<[u32; 3]> = vec![
+            [0, 11, 5],
+            [0, 5, 1],
+            [0, 1, 7],
+            [0, 7, 10],
+            [0, 10, 11],
+            [1, 5, 9],
+            [5, 11, 4],
+            [11, 10, 2],
+            [10, 7, 6],
+            [7, 1, 8],
+            [3, 9, 4],
+            [3, 4, 2],
+            [3, 2, 6],
+            [3, 6, 8],
+            [3, 8, 9],
+            [4, 9, 5],
+            [2, 4, 11],
+            [6, 2, 10],
+            [8, 6, 7],
+            [9, 8, 1],
+        ];
+
+        for _ in 0..self.subdivisions {
+            // every edge is split once, shared by the triangles on both sides
+            let mut midpoints = HashMap::new();
+            let mut midpoint = |a: u32, b: u32| {
+                *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    let position = (positions[a as usize] + positions[b as usize]).normalize();
+                    positions.push(position);
+                    positions.len() as u32 - 1
+                })
+            };
+
+            triangles = triangles
+                .iter()
+                .flat_map(|&[a, b, c]| {
+                    let ab = midpoint(a, b);
+                    let bc = midpoint(b, c);
+                    let ca = midpoint(c, a);
+
+                    [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+                })
+                .collect();
+        }
+
+        let vertices = positions
+            .iter()
+            .map(|&normal| {
+                let uv = Vec2::new(0.5 - normal.z.atan2(normal.x) / TAU, normal.y.acos() / PI);
+
+                vertex(normal * self.radius, normal, uv)
+            })
+            .collect();
+
+        Mesh {
+            vertices,
+            indices: triangles.into_iter().flatten().collect(),
+        }
+    }
+}
+
+/// Cylinder along the y axis with flat caps.
+pub struct Cylinder {
+    pub radius: f32,
+    pub height: f32,
+    pub segments: u32,
+}
+
+impl Default for Cylinder {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            radius: 0.5,
+            height: 1.0,
+            segments: 32,
+        }
+    }
+}
+
+impl Into<Mesh<Vertex3d>> for Cylinder {
+    #[inline]
+    fn into(self) -> Mesh<Vertex3d> {
+        let mut mesh = Mesh::default();
+        let top = self.height / 2.0;
+        let point = |radius, y, normal: Vec2| ProfilePoint { radius, y, normal };
+
+        // the caps and the side are separate so the edges are sharp
+        revolve(
+            &mut mesh,
+            self.segments,
+            &[point(0.0, top, Vec2::Y), point(self.radius, top, Vec2::Y)],
+        );
+        revolve(
+            &mut mesh,
+            self.segments,
+            &[
+                point(self.radius, top, Vec2::X),
+                point(self.radius, -top, Vec2::X),
+            ],
+        );
+        revolve(
+            &mut mesh,
+            self.segments,
+            &[
+                point(self.radius, -top, -Vec2::Y),
+                point(0.0, -top, -Vec2::Y),
+            ],
+        );
+
+        mesh
+    }
+}
+
+/// Cone along the y axis with the tip up.
+pub struct Cone {
+    pub radius: f32,
+    pub height: f32,
+    pub segments: u32,
+}
+
+impl Default for Cone {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            radius: 0.5,
+            height: 1.0,
+            segments: 32,
+        }
+    }
+}
+
+impl Into<Mesh<Vertex3d>> for Cone {
+    #[inline]
+    fn into(self) -> Mesh<Vertex3d> {
+        let mut mesh = Mesh::default();
+        let top = self.height / 2.0;
+        let point = |radius, y, normal: Vec2| ProfilePoint { radius, y, normal };
+
+        let side = Vec2::new(self.height, self.radius).normalize();
+
+        revolve(
+            &mut mesh,
+            self.segments,
+            &[point(0.0, top, side), point(self.radius, -top, side)],
+        );
+        revolve(
+            &mut mesh,
+            self.segments,
+            &[
+                point(self.radius, -top, -Vec2::Y),
+                point(0.0, -top, -Vec2::Y),
+            ],
+        );
+
+        mesh
+    }
+}
+
+/// Cylinder along the y axis with hemispheres at the ends, `depth` is the length of the
+/// cylinder so the capsule is `depth + 2 * radius` tall.
+pub struct Capsule {
+    pub radius: f32,
+    pub depth: f32,
+    pub segments: u32,
+    /// Rings of each hemisphere.
+    pub rings: u32,
+}
+
+impl Default for Capsule {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            radius: 0.5,
+            depth: 1.0,
+            segments: 32,
+            rings: 8,
+        }
+    }
+}
+
+impl Into<Mesh<Vertex3d>> for Capsule {
+    #[inline]
+    fn into(self) -> Mesh<Vertex3d> {
+        let mut mesh = Mesh::default();
+        let top = self.depth / 2.0;
+        let (radius, rings) = (self.radius, self.rings);
+
+        let hemisphere = |start: f32, center: f32| {
+            (0..=rings).map(move |ring| {
+                let angle = start + ring as f32 / rings as f32 * FRAC_PI_2;
+                // clamped so the bottom pole is a single point
+                let normal = Vec2::new(angle.sin().max(0.0), angle.cos());
+
+                ProfilePoint {
+                    radius: normal.x * radius,
+                    y: center + normal.y * radius,
+                    normal,
+                }
+            })
+        };
+
+        // the cylinder is the row between the equators of the hemispheres
+        let profile = hemisphere(0.0, top)
+            .chain(hemisphere(FRAC_PI_2, -top))
+            .collect::<Vec<_>>();
+
+        revolve(&mut mesh, self.segments, &profile);
+
+        mesh
+    }
+}
+
+/// Ring around the y axis, `radius` is the distance from the center to the middle of the
+/// tube.
+pub struct Torus {
+    pub radius: f32,
+    pub tube_radius: f32,
+    /// Segments around the y axis.
+    pub segments: u32,
+    /// Segments around the tube.
+    pub sides: u32,
+}
+
+impl Default for Torus {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            radius: 0.5,
+            tube_radius: 0.25,
+            segments: 32,
+            sides: 16,
+        }
+    }
+}
+
+impl Into<Mesh<Vertex3d>> for Torus {
+    #[inline]
+    fn into(self) -> Mesh<Vertex3d> {
+        let mut mesh = Mesh::default();
+
+        // starts on the outside going down, so the profile goes down the outside
+        let profile = (0..=self.sides)
+            .map(|side| {
+                let angle = side as f32 / self.sides as f32 * TAU;
+                let normal = Vec2::new(angle.cos(), -angle.sin());
+
+                ProfilePoint {
+                    radius: self.radius + normal.x * self.tube_radius,
+                    y: normal.y * self.tube_radius,
+                    normal,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        revolve(&mut mesh, self.segments, &profile);
+
+        mesh
+    }
+}
+
 pub struct Cube {
     pub size: Vec3,
 }
@@ -209,3 +710,129 @@ impl Into<Mesh<Vertex3d>> for Cube {
         mesh
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 0.0001;
+
+    /// Checks the counts, that the normals are unit length and that the triangles are
+    /// counter clockwise seen from the side their normals point to, so back face culling
+    /// keeps them.
+    fn check(mesh: impl Into<Mesh<Vertex3d>>, vertices: usize, triangles: usize) -> Mesh<Vertex3d> {
+        let mesh = mesh.into();
+
+        assert_eq!(mesh.vertices.len(), vertices);
+        assert_eq!(mesh.indices.len(), triangles * 3);
+        assert!(mesh.indices.iter().all(|&index| (index as usize) < vertices));
+
+        for vertex in &mesh.vertices {
+            assert!((vertex.normal.length() - 1.0).abs() < EPSILON);
+        }
+
+        for triangle in mesh.triangles() {
+            let [a, b, c] = triangle.map(|index| mesh.vertices[index as usize]);
+            let face = (b.position - a.position).cross(c.position - a.position);
+
+            assert!(face.dot(a.normal + b.normal + c.normal) > 0.0);
+        }
+
+        mesh
+    }
+
+    /// Checks that the triangles of a convex shape around the origin face outwards.
+    fn check_outwards(mesh: &Mesh<Vertex3d>) {
+        for triangle in mesh.triangles() {
+            let [a, b, c] = triangle.map(|index| mesh.vertices[index as usize].position);
+
+            assert!((b - a).cross(c - a).dot(a + b + c) > 0.0);
+        }
+    }
+
+    #[test]
+    fn quad() {
+        let mesh = check(Quad::default(), 4, 2);
+
+        assert!(mesh.vertices.iter().all(|vertex| vertex.normal == Vec3::Z));
+    }
+
+    #[test]
+    fn plane() {
+        let mesh = check(
+            Plane {
+                size: Vec2::new(2.0, 3.0),
+                subdivisions: 2,
+            },
+            16,
+            18,
+        );
+
+        assert!(mesh.vertices.iter().all(|vertex| vertex.normal == Vec3::Y));
+    }
+
+    #[test]
+    fn uv_sphere() {
+        // the triangles touching the poles collapse into one
+        let mesh = check(UvSphere::default(), 33 * 17, 32 * 2 * 16 - 32 * 2);
+        check_outwards(&mesh);
+
+        for vertex in &mesh.vertices {
+            assert!((vertex.position.length() - 0.5).abs() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn icosphere() {
+        let mesh = check(Icosphere::default(), 10 * 4usize.pow(3) + 2, 20 * 4usize.pow(3));
+        check_outwards(&mesh);
+
+        for vertex in &mesh.vertices {
+            assert!((vertex.position.length() - 0.5).abs() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn cylinder() {
+        let mesh = check(Cylinder::default(), 3 * 2 * 33, 4 * 32);
+        check_outwards(&mesh);
+    }
+
+    #[test]
+    fn cone() {
+        let mesh = check(Cone::default(), 2 * 2 * 33, 2 * 32);
+        check_outwards(&mesh);
+    }
+
+    #[test]
+    fn capsule() {
+        let mesh = check(Capsule::default(), 33 * 18, 4 * 32 * 8);
+        check_outwards(&mesh);
+    }
+
+    #[test]
+    fn torus() {
+        let mesh = check(Torus::default(), 33 * 17, 2 * 32 * 16);
+
+        // normals point away from the circle in the middle of the tube
+        for vertex in &mesh.vertices {
+            let radial = (vertex.position * Vec3::new(1.0, 0.0, 1.0)).normalize();
+            let tube = vertex.position - radial * 0.5;
+
+            assert!((tube.length() - 0.25).abs() < EPSILON);
+            assert!(tube.normalize().abs_diff_eq(vertex.normal, EPSILON));
+        }
+    }
+
+    #[test]
+    fn cube() {
+        let mesh = check(
+            Cube {
+                size: Vec3::new(1.0, 2.0, 3.0),
+            },
+            24,
+            12,
+        );
+        check_outwards(&mesh);
+    }
+}
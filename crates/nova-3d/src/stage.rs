@@ -1,13 +1,17 @@
 use std::collections::HashMap;
 
-use bytemuck::cast_slice;
-use glam::Mat4;
-use nova_assets::{Assets, Handle};
+use bytemuck::{bytes_of, cast_slice};
+use glam::{Mat4, UVec2};
+use nova_assets::{
+    event::{AssetEvent, AssetEventReader},
+    Assets, Handle, WeakHandle,
+};
 use nova_core::{App, IntoQuery, Resources, World};
 use nova_render::{
     camera_node::CameraNode,
     component::MeshInstance,
     depth_node::DepthNode,
+    image::Image,
     light_node::LightNode,
     mesh::MeshData,
     msaa_node::MsaaNode,
@@ -18,27 +22,322 @@ use nova_render::{
 use nova_transform::component::GlobalTransform;
 use nova_wgpu::*;
 
+use crate::{
+    material::{
+        material_bind_group_layout, AlphaMode, StandardMaterial, DEFAULT_MATERIAL_HANDLE,
+    },
+    shadow::{ShadowMaps, ShadowNode},
+    PBR_PIPELINE_HANDLE,
+};
+
 #[derive(Clone, PartialEq, Eq, Hash)]
 struct InstanceHandle {
-    pipeline: Handle<RenderPipeline>,
-    mesh_data: Handle<MeshData>,
+    pipeline: WeakHandle<RenderPipeline>,
+    mesh_data: WeakHandle<MeshData>,
+    material: WeakHandle<StandardMaterial>,
+}
+
+pub(crate) struct MeshBuffers {
+    pub vertex: Buffer,
+    pub index: Buffer,
+}
+
+impl MeshBuffers {
+    #[inline]
+    pub(crate) fn new(instance: &Instance, mesh_data: &MeshData) -> Self {
+        let vertex = instance.create_buffer_init(&BufferInitDescriptor {
+            label: Some("mesh_data_vertex"),
+            contents: &mesh_data.vertices,
+            usage: BufferUsage::COPY_DST | BufferUsage::VERTEX,
+        });
+
+        let index = instance.create_buffer_init(&BufferInitDescriptor {
+            label: Some("mesh_data_index"),
+            contents: cast_slice(&mesh_data.indices),
+            usage: BufferUsage::COPY_DST | BufferUsage::INDEX,
+        });
+
+        Self { vertex, index }
+    }
 }
 
 #[derive(Default)]
 struct InstanceGroup {
     transform: Vec<Mat4>,
+    /// Blended groups are drawn after the opaque ones.
+    blend: bool,
 }
 
 struct InstanceData {
     buffer: Buffer,
     buffer_data: Vec<u8>,
+}
+
+/// Camera, lights and shadows, group 0 of the pbr pipelines.
+struct ViewBindGroup {
+    /// [`LightNode::GENERATION`] of the light buffers and [`ShadowNode::GENERATION`] of
+    /// the shadow maps in the bind group.
+    generation: (u64, u64),
     bind_group: BindGroup,
 }
 
+/// Material bind group, group 1 of the pbr pipelines and the masked shadow pipeline.
+pub(crate) struct MaterialData {
+    #[allow(unused)]
+    buffer: Buffer,
+    pub bind_group: BindGroup,
+}
+
+impl MaterialData {
+    #[inline]
+    pub(crate) fn new(
+        instance: &Instance,
+        material: &StandardMaterial,
+        defaults: &MaterialDefaults,
+        images: &Assets<Image>,
+    ) -> Self {
+        let raw = material.raw();
+
+        let buffer = instance.create_buffer_init(&BufferInitDescriptor {
+            label: Some("standard_material"),
+            contents: bytes_of(&raw),
+            usage: BufferUsage::COPY_DST | BufferUsage::UNIFORM,
+        });
+
+        let find = |image: &Option<Handle<Image>>| {
+            image.as_ref().and_then(|image| images.get(image))
+        };
+
+        let base_color = find(&material.base_color_texture)
+            .unwrap_or(&defaults.white)
+            .gpu(instance);
+        let metallic_roughness = find(&material.metallic_roughness_texture)
+            .unwrap_or(&defaults.white)
+            .gpu(instance);
+        let normal = find(&material.normal_texture)
+            .unwrap_or(&defaults.normal)
+            .gpu(instance);
+
+        let bind_group = instance.create_bind_group(&BindGroupDescriptor {
+            label: Some("standard_material"),
+            layout: &defaults.layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::Buffer(BufferBinding {
+                        buffer: &buffer,
+                        offset: 0,
+                        size: None,
+                    }),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::TextureView(&base_color.view),
+                },
+                BindGroupEntry {
+                    binding: 2,
+                    resource: BindingResource::Sampler(&base_color.sampler),
+                },
+                BindGroupEntry {
+                    binding: 3,
+                    resource: BindingResource::TextureView(&metallic_roughness.view),
+                },
+                BindGroupEntry {
+                    binding: 4,
+                    resource: BindingResource::Sampler(&metallic_roughness.sampler),
+                },
+                BindGroupEntry {
+                    binding: 5,
+                    resource: BindingResource::TextureView(&normal.view),
+                },
+                BindGroupEntry {
+                    binding: 6,
+                    resource: BindingResource::Sampler(&normal.sampler),
+                },
+            ],
+        });
+
+        Self { buffer, bind_group }
+    }
+}
+
+/// Images bound in place of the ones a material doesn't have.
+pub(crate) struct MaterialDefaults {
+    pub layout: BindGroupLayout,
+    white: Image,
+    normal: Image,
+}
+
+impl MaterialDefaults {
+    #[inline]
+    pub(crate) fn new(instance: &Instance) -> Self {
+        Self {
+            layout: material_bind_group_layout(instance),
+            white: Image::new(UVec2::ONE, vec![255, 255, 255, 255], false),
+            normal: Image::new(UVec2::ONE, vec![128, 128, 255, 255], false),
+        }
+    }
+}
+
+#[inline]
+fn storage_entry(binding: u32) -> BindGroupLayoutEntry {
+    BindGroupLayoutEntry {
+        binding,
+        visibility: ShaderStage::FRAGMENT,
+        ty: BindingType::Buffer {
+            ty: BufferBindingType::Storage { read_only: true },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    }
+}
+
+#[inline]
+fn shadow_map_entry(binding: u32) -> BindGroupLayoutEntry {
+    BindGroupLayoutEntry {
+        binding,
+        visibility: ShaderStage::FRAGMENT,
+        ty: BindingType::Texture {
+            sample_type: TextureSampleType::Depth,
+            view_dimension: TextureViewDimension::D2Array,
+            multisampled: false,
+        },
+        count: None,
+    }
+}
+
+/// Layout of the camera, lights and shadows bind group, group 0 of the pbr pipelines.
+#[inline]
+pub fn view_bind_group_layout(instance: &Instance) -> BindGroupLayout {
+    instance.create_bind_group_layout(&BindGroupLayoutDescriptor {
+        label: Some("pbr_view"),
+        entries: &[
+            BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStage::VERTEX_FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            BindGroupLayoutEntry {
+                binding: 1,
+                visibility: ShaderStage::FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            storage_entry(2),
+            storage_entry(3),
+            storage_entry(4),
+            BindGroupLayoutEntry {
+                binding: 5,
+                visibility: ShaderStage::FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            shadow_map_entry(6),
+            shadow_map_entry(7),
+            BindGroupLayoutEntry {
+                binding: 8,
+                visibility: ShaderStage::FRAGMENT,
+                ty: BindingType::Sampler {
+                    filtering: true,
+                    comparison: true,
+                },
+                count: None,
+            },
+        ],
+    })
+}
+
+#[inline]
+fn buffer_entry(binding: u32, buffer: &Buffer) -> BindGroupEntry<'_> {
+    BindGroupEntry {
+        binding,
+        resource: BindingResource::Buffer(BufferBinding {
+            buffer,
+            offset: 0,
+            size: None,
+        }),
+    }
+}
+
 #[derive(Default)]
 pub struct D3PassNode {
     groups: HashMap<InstanceHandle, InstanceGroup>,
     data: HashMap<InstanceHandle, InstanceData>,
+    mesh_buffers: HashMap<WeakHandle<MeshData>, MeshBuffers>,
+    materials: HashMap<WeakHandle<StandardMaterial>, MaterialData>,
+    material_defaults: Option<MaterialDefaults>,
+    view_layout: Option<BindGroupLayout>,
+    view_bind_group: Option<ViewBindGroup>,
+    mesh_events: AssetEventReader<MeshData>,
+    pipeline_events: AssetEventReader<RenderPipeline>,
+    material_events: AssetEventReader<StandardMaterial>,
+    image_events: AssetEventReader<Image>,
+}
+
+impl D3PassNode {
+    /// Frees the gpu data of modified and removed assets.
+    #[inline]
+    fn invalidate(
+        &mut self,
+        meshes: &Assets<MeshData>,
+        pipelines: &Assets<RenderPipeline>,
+        materials: &Assets<StandardMaterial>,
+        images: &Assets<Image>,
+    ) {
+        for event in self.mesh_events.read(meshes.events()) {
+            match event {
+                AssetEvent::Created(_) => {}
+                AssetEvent::Modified(handle) => {
+                    self.mesh_buffers.remove(handle);
+                }
+                AssetEvent::Removed(handle) => {
+                    self.mesh_buffers.remove(handle);
+                    self.data
+                        .retain(|instance, _| instance.mesh_data != *handle);
+                }
+            }
+        }
+
+        for event in self.pipeline_events.read(pipelines.events()) {
+            if let AssetEvent::Removed(handle) = event {
+                self.data.retain(|instance, _| instance.pipeline != *handle);
+            }
+        }
+
+        for event in self.material_events.read(materials.events()) {
+            match event {
+                AssetEvent::Created(_) => {}
+                AssetEvent::Modified(handle) => {
+                    self.materials.remove(handle);
+                }
+                AssetEvent::Removed(handle) => {
+                    self.materials.remove(handle);
+                    self.data.retain(|instance, _| instance.material != *handle);
+                }
+            }
+        }
+
+        // few materials share an image, so every bind group is rebuilt when one changes
+        for event in self.image_events.read(images.events()) {
+            if let AssetEvent::Modified(_) | AssetEvent::Removed(_) = event {
+                self.materials.clear();
+            }
+        }
+    }
 }
 
 impl RenderNode for D3PassNode {
@@ -55,8 +354,16 @@ impl RenderNode for D3PassNode {
         let settings = resources.get_mut::<RenderSettings>().unwrap().clone();
         let instance = resources.get::<Instance>().unwrap();
 
-        let mut meshes = resources.get_mut::<Assets<MeshData>>().unwrap();
+        let meshes = resources.get::<Assets<MeshData>>().unwrap();
         let pipelines = resources.get::<Assets<RenderPipeline>>().unwrap();
+        let materials = resources.get::<Assets<StandardMaterial>>().unwrap();
+        let images = resources.get::<Assets<Image>>().unwrap();
+
+        self.invalidate(&meshes, &pipelines, &materials, &images);
+
+        let defaults = self
+            .material_defaults
+            .get_or_insert_with(|| MaterialDefaults::new(&instance));
 
         let color_attachment = if settings.msaa > 1 {
             let msaa_texture = render_data.get::<RenderTexture>(MsaaNode::TEXTURE).unwrap();
@@ -101,42 +408,61 @@ impl RenderNode for D3PassNode {
             }),
         });
 
-        for (mesh_instance, global_transform) in
-            <(&MeshInstance, &GlobalTransform)>::query().iter(world)
+        let default_material = DEFAULT_MATERIAL_HANDLE;
+
+        for (mesh_instance, global_transform, material_handle) in <(
+            &MeshInstance,
+            &GlobalTransform,
+            Option<&Handle<StandardMaterial>>,
+        )>::query()
+        .iter(world)
         {
-            let handle = InstanceHandle {
-                pipeline: mesh_instance.pipeline.clone(),
-                mesh_data: mesh_instance.mesh_data.clone(),
-            };
+            let material_handle = material_handle.unwrap_or(&default_material);
 
-            self.groups
-                .entry(handle)
-                .or_default()
-                .transform
-                .push(global_transform.matrix());
-        }
+            // skip meshes that are still loading
+            let material = match materials.get(material_handle) {
+                Some(material) => material,
+                None => continue,
+            };
 
-        for (handle, group) in &self.groups {
-            let mesh_data = meshes.get_mut(&handle.mesh_data).unwrap();
+            // the pbr pipeline stands for the variant the material needs
+            let pipeline = if mesh_instance.pipeline == PBR_PIPELINE_HANDLE {
+                material.pipeline()
+            } else {
+                mesh_instance.pipeline.clone()
+            };
 
-            if mesh_data.vertex_buffer.is_none() {
-                let buffer = instance.create_buffer_init(&BufferInitDescriptor {
-                    label: Some("mesh_data_vertex"),
-                    contents: &mesh_data.vertices,
-                    usage: BufferUsage::COPY_DST | BufferUsage::VERTEX,
-                });
+            if !meshes.contains(&mesh_instance.mesh_data)
+                || !pipelines.contains(&pipeline)
+                || !material.textures().all(|image| images.contains(image))
+            {
+                continue;
+            }
 
-                mesh_data.vertex_buffer = Some(buffer);
+            if !self.materials.contains_key(&material_handle.downgrade()) {
+                let data = MaterialData::new(&instance, material, defaults, &images);
+                self.materials.insert(material_handle.downgrade(), data);
             }
 
-            if mesh_data.index_buffer.is_none() {
-                let buffer = instance.create_buffer_init(&BufferInitDescriptor {
-                    label: Some("mesh_data_index"),
-                    contents: cast_slice(&mesh_data.indices),
-                    usage: BufferUsage::COPY_DST | BufferUsage::INDEX,
-                });
+            let handle = InstanceHandle {
+                pipeline: pipeline.downgrade(),
+                mesh_data: mesh_instance.mesh_data.downgrade(),
+                material: material_handle.downgrade(),
+            };
+
+            let group = self.groups.entry(handle).or_default();
+            group.transform.push(global_transform.matrix());
+            group.blend = material.alpha_mode == AlphaMode::Blend;
+        }
 
-                mesh_data.index_buffer = Some(buffer);
+        for (handle, group) in &self.groups {
+            if !self.mesh_buffers.contains_key(&handle.mesh_data) {
+                let mesh_data = meshes.get(&handle.mesh_data).unwrap();
+
+                self.mesh_buffers.insert(
+                    handle.mesh_data.clone(),
+                    MeshBuffers::new(&instance, mesh_data),
+                );
             }
 
             let data: &[u8] = cast_slice(&group.transform);
@@ -162,81 +488,87 @@ impl RenderNode for D3PassNode {
                     usage: BufferUsage::COPY_DST | BufferUsage::VERTEX,
                 });
 
-                let layout = instance.create_bind_group_layout(&BindGroupLayoutDescriptor {
-                    label: Some("instance_group_bind"),
-                    entries: &[
-                        BindGroupLayoutEntry {
-                            binding: 0,
-                            visibility: ShaderStage::VERTEX_FRAGMENT,
-                            ty: BindingType::Buffer {
-                                ty: BufferBindingType::Uniform,
-                                has_dynamic_offset: false,
-                                min_binding_size: None,
-                            },
-                            count: None,
-                        },
-                        BindGroupLayoutEntry {
-                            binding: 1,
-                            visibility: ShaderStage::VERTEX_FRAGMENT,
-                            ty: BindingType::Buffer {
-                                ty: BufferBindingType::Uniform,
-                                has_dynamic_offset: false,
-                                min_binding_size: None,
-                            },
-                            count: None,
-                        },
-                    ],
-                });
-
-                let bind_group = instance.create_bind_group(&BindGroupDescriptor {
-                    label: Some("instance_group_bind"),
-                    layout: &layout,
-                    entries: &[
-                        BindGroupEntry {
-                            binding: 0,
-                            resource: BindingResource::Buffer(BufferBinding {
-                                buffer: render_data.get::<Buffer>(CameraNode::BUFFER).unwrap(),
-                                offset: 0,
-                                size: None,
-                            }),
-                        },
-                        BindGroupEntry {
-                            binding: 1,
-                            resource: BindingResource::Buffer(BufferBinding {
-                                buffer: render_data.get::<Buffer>(LightNode::BUFFER).unwrap(),
-                                offset: 0,
-                                size: None,
-                            }),
-                        },
-                    ],
-                });
-
                 self.data.insert(
                     handle.clone(),
                     InstanceData {
                         buffer,
                         buffer_data: data.to_vec(),
-                        bind_group,
                     },
                 );
             };
         }
 
-        for (handle, group) in &self.groups {
+        let generation = (
+            *render_data.get::<u64>(LightNode::GENERATION).unwrap(),
+            *render_data.get::<u64>(ShadowNode::GENERATION).unwrap(),
+        );
+
+        // the light buffers are created again when they run out of room and the shadow
+        // maps when their size changes
+        if self
+            .view_bind_group
+            .as_ref()
+            .map_or(true, |view| view.generation != generation)
+        {
+            let layout = self
+                .view_layout
+                .get_or_insert_with(|| view_bind_group_layout(&instance));
+
+            let buffer = |name| render_data.get::<Buffer>(name).unwrap();
+            let shadow_maps = render_data.get::<ShadowMaps>(ShadowNode::MAPS).unwrap();
+
+            let bind_group = instance.create_bind_group(&BindGroupDescriptor {
+                label: Some("pbr_view"),
+                layout,
+                entries: &[
+                    buffer_entry(0, buffer(CameraNode::BUFFER)),
+                    buffer_entry(1, buffer(LightNode::BUFFER)),
+                    buffer_entry(2, buffer(LightNode::POINT_LIGHTS_BUFFER)),
+                    buffer_entry(3, buffer(LightNode::DIRECTIONAL_LIGHTS_BUFFER)),
+                    buffer_entry(4, buffer(LightNode::SPOT_LIGHTS_BUFFER)),
+                    buffer_entry(5, buffer(ShadowNode::BUFFER)),
+                    BindGroupEntry {
+                        binding: 6,
+                        resource: BindingResource::TextureView(&shadow_maps.directional),
+                    },
+                    BindGroupEntry {
+                        binding: 7,
+                        resource: BindingResource::TextureView(&shadow_maps.point),
+                    },
+                    BindGroupEntry {
+                        binding: 8,
+                        resource: BindingResource::Sampler(&shadow_maps.sampler),
+                    },
+                ],
+            });
+
+            self.view_bind_group = Some(ViewBindGroup {
+                generation,
+                bind_group,
+            });
+        }
+
+        let view = self.view_bind_group.as_ref().unwrap();
+        render_pass.set_bind_group(0, &view.bind_group, &[]);
+
+        let opaque = self.groups.iter().filter(|(_, group)| !group.blend);
+        let blended = self.groups.iter().filter(|(_, group)| group.blend);
+
+        for (handle, group) in opaque.chain(blended) {
             let data = self.data.get(handle).unwrap();
+            let material = self.materials.get(&handle.material).unwrap();
             let pipeline = pipelines.get(&handle.pipeline).unwrap();
             let mesh_data = meshes.get(&handle.mesh_data).unwrap();
+            let mesh_buffers = self.mesh_buffers.get(&handle.mesh_data).unwrap();
 
             render_pass.set_pipeline(pipeline);
 
-            let vertex_buffer = mesh_data.vertex_buffer.as_ref().unwrap().slice(..);
-            render_pass.set_vertex_buffer(0, vertex_buffer);
+            render_pass.set_vertex_buffer(0, mesh_buffers.vertex.slice(..));
             render_pass.set_vertex_buffer(1, data.buffer.slice(..));
 
-            let index_buffer = mesh_data.index_buffer.as_ref().unwrap().slice(..);
-            render_pass.set_index_buffer(index_buffer, IndexFormat::Uint32);
+            render_pass.set_index_buffer(mesh_buffers.index.slice(..), IndexFormat::Uint32);
 
-            render_pass.set_bind_group(0, &data.bind_group, &[]);
+            render_pass.set_bind_group(1, &material.bind_group, &[]);
 
             render_pass.draw_indexed(
                 0..mesh_data.indices.len() as u32,
@@ -0,0 +1,113 @@
+use std::marker::PhantomData;
+
+use crate::WeakHandle;
+
+/// Change to an asset in [`Assets<T>`](crate::Assets).
+pub enum AssetEvent<T: 'static> {
+    Created(WeakHandle<T>),
+    /// The asset was replaced or accessed mutably, e.g. by a hot reload.
+    Modified(WeakHandle<T>),
+    Removed(WeakHandle<T>),
+}
+
+impl<T> AssetEvent<T> {
+    #[inline]
+    pub fn handle(&self) -> &WeakHandle<T> {
+        match self {
+            Self::Created(handle) | Self::Modified(handle) | Self::Removed(handle) => handle,
+        }
+    }
+}
+
+impl<T> Clone for AssetEvent<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        match self {
+            Self::Created(handle) => Self::Created(handle.clone()),
+            Self::Modified(handle) => Self::Modified(handle.clone()),
+            Self::Removed(handle) => Self::Removed(handle.clone()),
+        }
+    }
+}
+
+impl<T> PartialEq for AssetEvent<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Created(a), Self::Created(b)) => a == b,
+            (Self::Modified(a), Self::Modified(b)) => a == b,
+            (Self::Removed(a), Self::Removed(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// Events of the current and the last frame, read them with an [`AssetEventReader`] to see
+/// each event once.
+pub struct AssetEvents<T: 'static> {
+    events: Vec<AssetEvent<T>>,
+    /// Number of events dropped before `events[0]`.
+    offset: usize,
+    last_frame: usize,
+}
+
+impl<T> Default for AssetEvents<T> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            events: Vec::new(),
+            offset: 0,
+            last_frame: 0,
+        }
+    }
+}
+
+impl<T> AssetEvents<T> {
+    #[inline]
+    pub fn send(&mut self, event: AssetEvent<T>) {
+        self.events.push(event);
+    }
+
+    /// Drops the events of the last frame, called at the start of each frame.
+    #[inline]
+    pub fn update(&mut self) {
+        self.events.drain(..self.last_frame);
+        self.offset += self.last_frame;
+        self.last_frame = self.events.len();
+    }
+
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &AssetEvent<T>> {
+        self.events.iter()
+    }
+}
+
+pub struct AssetEventReader<T: 'static> {
+    cursor: usize,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for AssetEventReader<T> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            cursor: 0,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> AssetEventReader<T> {
+    /// Returns the events sent since the last read, events are missed if not read for
+    /// two frames.
+    #[inline]
+    pub fn read<'a>(
+        &mut self,
+        events: &'a AssetEvents<T>,
+    ) -> impl Iterator<Item = &'a AssetEvent<T>> {
+        let start = self.cursor.max(events.offset) - events.offset;
+        self.cursor = events.offset + events.events.len();
+
+        events.events[start..].iter()
+    }
+}
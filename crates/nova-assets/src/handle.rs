@@ -0,0 +1,430 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::{Arc, Weak},
+};
+
+use nova_core::scene;
+use nova_inspect::Inspectable;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{loader::split_label, server::AssetServer};
+
+#[derive(
+    Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, Inspectable,
+)]
+pub enum InnerHandle {
+    Id(u64),
+    Path(PathBuf),
+    /// Asset with this uuid in its [`AssetMeta`](crate::meta::AssetMeta), survives the file
+    /// being renamed or moved.
+    Uuid(Uuid),
+}
+
+impl std::fmt::Display for InnerHandle {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Id(id) => write!(f, "{}", id),
+            Self::Path(path) => write!(f, "{}", path.display()),
+            Self::Uuid(uuid) => write!(f, "{}", uuid),
+        }
+    }
+}
+
+/// Key of an asset in [`Assets`](crate::Assets), path handles are identified by a hash of
+/// the path so comparing and hashing handles never touches the path.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum HandleId {
+    Id(u64),
+    Path(u64),
+    Uuid(Uuid),
+}
+
+impl HandleId {
+    #[inline]
+    pub fn path(path: &Path) -> Self {
+        let mut hasher = DefaultHasher::new();
+        path.hash(&mut hasher);
+
+        Self::Path(hasher.finish())
+    }
+}
+
+impl From<&InnerHandle> for HandleId {
+    #[inline]
+    fn from(inner: &InnerHandle) -> Self {
+        match inner {
+            InnerHandle::Id(id) => Self::Id(*id),
+            InnerHandle::Path(path) => Self::path(path),
+            InnerHandle::Uuid(uuid) => Self::Uuid(*uuid),
+        }
+    }
+}
+
+/// Implemented by [`Handle`] and [`WeakHandle`] for looking up assets.
+pub trait AssetHandle<T> {
+    fn id(&self) -> HandleId;
+}
+
+/// Strong reference to an asset in [`Assets<T>`](crate::Assets).
+///
+/// Handles returned by [`Assets::add`](crate::Assets::add), [`Assets::make_handle`](crate::Assets::make_handle)
+/// and [`AssetServer::load`](crate::server::AssetServer::load) keep their asset alive, as do
+/// their clones and casts. Handles built from an id or path, with [`Handle::from_u64`],
+/// [`Handle::from_inner`], `From` or by deserializing outside of a scene, are untracked and
+/// only refer to whatever asset is at that id. Handles deserialized in a scene, see
+/// [`scene::deserialize_with`], are loaded with [`AssetServer::load_handle`].
+pub struct Handle<T: 'static> {
+    id: HandleId,
+    inner: InnerHandle,
+    refs: Option<Arc<()>>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    /// Creates an untracked handle.
+    #[inline]
+    pub const fn from_u64(id: u64) -> Self {
+        Self {
+            id: HandleId::Id(id),
+            inner: InnerHandle::Id(id),
+            refs: None,
+            marker: PhantomData,
+        }
+    }
+
+    /// Creates an untracked handle.
+    #[inline]
+    pub fn from_inner(inner: InnerHandle) -> Self {
+        Self {
+            id: HandleId::from(&inner),
+            inner,
+            refs: None,
+            marker: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn tracked(inner: InnerHandle, refs: Arc<()>) -> Self {
+        Self {
+            id: HandleId::from(&inner),
+            inner,
+            refs: Some(refs),
+            marker: PhantomData,
+        }
+    }
+
+    /// Creates a tracked handle to the asset at `id` that serializes as `inner`, for uuid
+    /// handles to assets stored at their path.
+    #[inline]
+    pub(crate) fn tracked_at(inner: InnerHandle, id: HandleId, refs: Arc<()>) -> Self {
+        Self {
+            id,
+            inner,
+            refs: Some(refs),
+            marker: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn id(&self) -> HandleId {
+        self.id
+    }
+
+    #[inline]
+    pub fn inner(&self) -> &InnerHandle {
+        &self.inner
+    }
+
+    #[inline]
+    pub(crate) fn refs(&self) -> Option<&Arc<()>> {
+        self.refs.as_ref()
+    }
+
+    #[inline]
+    pub fn is_tracked(&self) -> bool {
+        self.refs.is_some()
+    }
+
+    /// Number of strong handles to the asset, 0 if untracked.
+    #[inline]
+    pub fn strong_count(&self) -> usize {
+        self.refs.as_ref().map_or(0, Arc::strong_count)
+    }
+
+    #[inline]
+    pub fn downgrade(&self) -> WeakHandle<T> {
+        WeakHandle {
+            id: self.id,
+            inner: self.inner.clone(),
+            refs: self.refs.as_ref().map(Arc::downgrade),
+            marker: PhantomData,
+        }
+    }
+
+    /// Reinterprets the handle as a handle to `U` with the same id, sharing the reference
+    /// count, so it keeps assets at this id alive in both `Assets<T>` and `Assets<U>`.
+    #[inline]
+    pub fn cast<U: 'static>(self) -> Handle<U> {
+        Handle {
+            id: self.id,
+            inner: self.inner,
+            refs: self.refs,
+            marker: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn unwrap_id(self) -> u64 {
+        match self.inner {
+            InnerHandle::Id(id) => id,
+            _ => panic!("tried to unwrap non id handle"),
+        }
+    }
+
+    #[inline]
+    pub fn unwrap_path(self) -> PathBuf {
+        match self.inner {
+            InnerHandle::Path(path) => path,
+            _ => panic!("tried to unwrap non path handle"),
+        }
+    }
+
+    #[inline]
+    pub fn unwrap_uuid(self) -> Uuid {
+        match self.inner {
+            InnerHandle::Uuid(uuid) => uuid,
+            _ => panic!("tried to unwrap non uuid handle"),
+        }
+    }
+}
+
+impl<T> AssetHandle<T> for Handle<T> {
+    #[inline]
+    fn id(&self) -> HandleId {
+        self.id
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id,
+            inner: self.inner.clone(),
+            refs: self.refs.clone(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> PartialEq for Handle<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl<T> PartialOrd for Handle<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Handle<T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.id.cmp(&other.id)
+    }
+}
+
+impl<T> std::fmt::Debug for Handle<T> {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Handle({:?})", self.inner)
+    }
+}
+
+impl<T> serde::Serialize for Handle<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.inner.serialize(serializer)
+    }
+}
+
+impl<'de, T> serde::Deserialize<'de> for Handle<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let handle = Handle::from_inner(InnerHandle::deserialize(deserializer)?);
+
+        // handles in scenes load their asset, so they're tracked like loaded handles
+        let loaded = scene::with_resources(|resources| {
+            let server = resources.get::<AssetServer>()?;
+
+            if let Some(path) = server.asset_path(handle.inner()) {
+                match split_label(&path) {
+                    Some((file, _)) => scene::add_dependency(&file),
+                    None => scene::add_dependency(&path),
+                }
+            }
+
+            Some(server.load_handle(&handle))
+        });
+
+        Ok(loaded.flatten().unwrap_or(handle))
+    }
+}
+
+impl<T> From<&str> for Handle<T> {
+    #[inline]
+    fn from(path: &str) -> Self {
+        Self::from_inner(InnerHandle::Path(path.into()))
+    }
+}
+
+impl<T> From<PathBuf> for Handle<T> {
+    #[inline]
+    fn from(path: PathBuf) -> Self {
+        Self::from_inner(InnerHandle::Path(path))
+    }
+}
+
+impl<T> From<&Path> for Handle<T> {
+    #[inline]
+    fn from(path: &Path) -> Self {
+        Self::from_inner(InnerHandle::Path(path.into()))
+    }
+}
+
+impl<T> From<Uuid> for Handle<T> {
+    #[inline]
+    fn from(uuid: Uuid) -> Self {
+        Self::from_inner(InnerHandle::Uuid(uuid))
+    }
+}
+
+impl<T> From<u64> for Handle<T> {
+    #[inline]
+    fn from(id: u64) -> Self {
+        Self::from_u64(id)
+    }
+}
+
+/// Reference to an asset that doesn't keep it alive, for caches and events.
+pub struct WeakHandle<T: 'static> {
+    id: HandleId,
+    inner: InnerHandle,
+    refs: Option<Weak<()>>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> WeakHandle<T> {
+    #[inline]
+    pub(crate) fn new(inner: InnerHandle, refs: Option<Weak<()>>) -> Self {
+        Self {
+            id: HandleId::from(&inner),
+            inner,
+            refs,
+            marker: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn id(&self) -> HandleId {
+        self.id
+    }
+
+    #[inline]
+    pub fn inner(&self) -> &InnerHandle {
+        &self.inner
+    }
+
+    #[inline]
+    pub fn strong_count(&self) -> usize {
+        self.refs.as_ref().map_or(0, Weak::strong_count)
+    }
+
+    /// Returns a strong handle if the asset is still alive, weak handles to untracked
+    /// handles always upgrade to an untracked handle.
+    #[inline]
+    pub fn upgrade(&self) -> Option<Handle<T>> {
+        let refs = match &self.refs {
+            Some(refs) => Some(refs.upgrade()?),
+            None => None,
+        };
+
+        Some(Handle {
+            id: self.id,
+            inner: self.inner.clone(),
+            refs,
+            marker: PhantomData,
+        })
+    }
+}
+
+impl<T> AssetHandle<T> for WeakHandle<T> {
+    #[inline]
+    fn id(&self) -> HandleId {
+        self.id
+    }
+}
+
+impl<T> Clone for WeakHandle<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id,
+            inner: self.inner.clone(),
+            refs: self.refs.clone(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> PartialEq for WeakHandle<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T> Eq for WeakHandle<T> {}
+
+impl<T> PartialEq<Handle<T>> for WeakHandle<T> {
+    #[inline]
+    fn eq(&self, other: &Handle<T>) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T> Hash for WeakHandle<T> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl<T> std::fmt::Debug for WeakHandle<T> {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "WeakHandle({:?})", self.inner)
+    }
+}
@@ -0,0 +1,316 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use nova_inspect::{
+    context::with_resources,
+    drag::dropped_asset,
+    egui::{Area, Color32, Frame, Id, Key, Order, Response, ScrollArea, TextEdit, Ui},
+    Inspectable,
+};
+
+use crate::{
+    server::{AssetServer, LoadState},
+    Assets, Handle, InnerHandle, Uuid,
+};
+
+const PICKER_WIDTH: f32 = 240.0;
+const PICKER_HEIGHT: f32 = 240.0;
+
+/// Name shown for the asset `inner` refers to, uuids are shown as the path of their asset.
+#[inline]
+fn asset_name(inner: &InnerHandle, server: Option<&AssetServer>) -> String {
+    match inner {
+        InnerHandle::Id(id) => format!("#{}", id),
+        InnerHandle::Path(path) => path.display().to_string(),
+        InnerHandle::Uuid(uuid) => server
+            .and_then(|server| server.asset_path(inner))
+            .map_or_else(|| uuid.to_string(), |path| path.display().to_string()),
+    }
+}
+
+/// Icon and hover text explaining why `handle` has no asset, `None` if it has one.
+#[inline]
+fn missing_asset<T: Send + Sync + 'static>(
+    handle: &Handle<T>,
+    assets: &Assets<T>,
+    server: Option<&AssetServer>,
+) -> Option<(&'static str, String)> {
+    if assets.contains(handle) {
+        return None;
+    }
+
+    let state = server.map_or(LoadState::NotLoaded, |server| server.load_state(handle));
+
+    Some(match state {
+        LoadState::Loading => ("⏳", String::from("loading")),
+        LoadState::Failed(err) => ("⚠", format!("failed to load: {}", err)),
+        _ => ("⚠", String::from("the handle points to no asset")),
+    })
+}
+
+/// Handle of the asset dropped on `response`, by uuid if it has a meta file.
+#[inline]
+fn dropped_handle(ui: &Ui, response: &Response) -> Option<InnerHandle> {
+    let asset = dropped_asset(ui, response)?;
+
+    Some(
+        match asset.uuid.and_then(|uuid| Uuid::parse_str(&uuid).ok()) {
+            Some(uuid) => InnerHandle::Uuid(uuid),
+            None => InnerHandle::Path(asset.path),
+        },
+    )
+}
+
+/// Loads the file at `path`, by uuid if it has a meta file.
+#[inline]
+fn load_file<T: Send + Sync + 'static>(server: &AssetServer, path: &Path) -> Handle<T> {
+    match server.read_meta(path) {
+        Ok(Some(meta)) => server.load_uuid(meta.uuid),
+        _ => server.load(path),
+    }
+}
+
+/// Lists the loaded assets of `T` and the files `T` can be loaded from, returns the one
+/// clicked. The files are scanned once when the picker opens, not every frame.
+#[inline]
+fn picker_list_ui<T: Send + Sync + 'static>(
+    ui: &mut Ui,
+    id: Id,
+    current: Option<&Handle<T>>,
+    assets: &Assets<T>,
+    server: Option<&AssetServer>,
+) -> Option<Handle<T>> {
+    let search_id = id.with("search");
+    let mut search = ui
+        .memory()
+        .id_data_temp
+        .get_or_default::<String>(search_id)
+        .clone();
+
+    ui.add(TextEdit::singleline(&mut search).hint_text("Search"));
+
+    let filter = search.trim().to_lowercase();
+    let matches = |name: &str| name.to_lowercase().contains(&filter);
+    ui.memory().id_data_temp.insert(search_id, search);
+
+    let current_name = current.map(|current| asset_name(current.inner(), server));
+    let mut picked = None;
+
+    ScrollArea::from_max_height(PICKER_HEIGHT).show(ui, |ui| {
+        ui.label("Loaded");
+
+        let mut loaded = assets
+            .iter_handles()
+            .map(|(weak, _)| (asset_name(weak.inner(), server), weak))
+            .filter(|(name, _)| matches(name))
+            .collect::<Vec<_>>();
+        loaded.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        for (name, weak) in loaded {
+            if ui
+                .selectable_label(current.map(Handle::id) == Some(weak.id()), name)
+                .clicked()
+            {
+                picked = weak.upgrade();
+            }
+        }
+
+        let server = match server {
+            Some(server) => server,
+            None => return,
+        };
+
+        ui.separator();
+        ui.label("On disk");
+
+        let paths = ui
+            .memory()
+            .id_data_temp
+            .get_or_insert_with(id.with("paths"), || -> Arc<Vec<PathBuf>> {
+                let paths = server.asset_paths().into_iter();
+                Arc::new(paths.filter(|path| server.can_load::<T>(path)).collect())
+            })
+            .clone();
+
+        for path in paths.iter() {
+            let name = path.display().to_string();
+
+            if !matches(&name) {
+                continue;
+            }
+
+            if ui
+                .selectable_label(current_name.as_ref() == Some(&name), name)
+                .clicked()
+            {
+                picked = Some(load_file(server, path));
+            }
+        }
+    });
+
+    picked
+}
+
+/// Shows the name of the asset with a warning if it's missing, and a button opening the
+/// picker. Returns the picked handle, `None` handles are shown as "None".
+#[inline]
+fn handle_ui<T: Send + Sync + 'static>(
+    handle: Option<&Handle<T>>,
+    ui: &mut Ui,
+    assets: &Assets<T>,
+    server: Option<&AssetServer>,
+) -> (Response, Option<Handle<T>>) {
+    let mut picked = None;
+
+    let mut response = ui
+        .horizontal(|ui| {
+            let missing = handle.and_then(|handle| missing_asset(handle, assets, server));
+
+            if let Some((icon, reason)) = missing {
+                ui.colored_label(Color32::YELLOW, icon)
+                    .on_hover_text(reason);
+            }
+
+            let name = handle.map_or_else(
+                || String::from("None"),
+                |handle| asset_name(handle.inner(), server),
+            );
+            let button = ui.button(name);
+            let popup_id = button.id.with("asset_picker");
+
+            if button.clicked() {
+                ui.memory().toggle_popup(popup_id);
+            }
+
+            if ui.memory().is_popup_open(popup_id) {
+                // not a combo box, those close when the search field is clicked
+                let area = Area::new(popup_id)
+                    .order(Order::Foreground)
+                    .fixed_pos(button.rect.left_bottom())
+                    .show(ui.ctx(), |ui| {
+                        Frame::popup(ui.style()).show(ui, |ui| {
+                            ui.set_width(PICKER_WIDTH);
+                            picked = picker_list_ui(ui, popup_id, handle, assets, server);
+                        });
+                    });
+
+                let input = ui.input();
+                let clicked_outside = input.pointer.any_click()
+                    && input.pointer.interact_pos().map_or(false, |pos| {
+                        !area.rect.contains(pos) && !button.rect.contains(pos)
+                    });
+                let close = picked.is_some() || clicked_outside || input.key_pressed(Key::Escape);
+
+                if close {
+                    ui.memory().close_popup();
+                }
+            } else {
+                // scanned again the next time the picker opens
+                ui.memory().id_data_temp.remove(&popup_id.with("paths"));
+            }
+
+            button
+        })
+        .inner;
+
+    if let Some(inner) = dropped_handle(ui, &response) {
+        picked = Some(match server {
+            Some(server) => server.load_handle(&Handle::from_inner(inner)),
+            None => Handle::from_inner(inner),
+        });
+    }
+
+    if picked.is_some() {
+        response.mark_changed();
+    }
+
+    (response, picked)
+}
+
+impl<T: Send + Sync + 'static> Inspectable for Handle<T> {
+    #[inline]
+    fn name(&self) -> &'static str {
+        "Handle"
+    }
+
+    /// Shows the asset the handle points to and a picker of the loaded assets of `T` and
+    /// the files `T` can be loaded from. Assets dragged from the editor asset browser can
+    /// be dropped on the handle, assets with a meta file are assigned by uuid.
+    ///
+    /// Outside of [`inspect_with`](nova_inspect::context::inspect_with) the raw handle is
+    /// edited instead.
+    #[inline]
+    fn inspect(&mut self, ui: &mut Ui) -> Option<Response> {
+        let response = with_resources(|resources| {
+            let assets = resources.get::<Assets<T>>()?;
+            let server = resources.get::<AssetServer>();
+
+            let (response, picked) = handle_ui(Some(&*self), ui, &assets, server.as_deref());
+
+            if let Some(picked) = picked {
+                *self = picked;
+            }
+
+            Some(response)
+        })
+        .flatten();
+
+        if response.is_some() {
+            return response;
+        }
+
+        let mut inner = self.inner().clone();
+        let mut response = inner.inspect(ui)?;
+
+        if let Some(dropped) = dropped_handle(ui, &response) {
+            inner = dropped;
+            response.mark_changed();
+        }
+
+        if response.changed() {
+            *self = Self::from_inner(inner);
+        }
+
+        Some(response)
+    }
+}
+
+/// Inspects an optional handle like a [`Handle`], with a button clearing it. `Option<Handle<T>>`
+/// doesn't implement [`Inspectable`] since handles have no default to start from.
+#[inline]
+pub fn inspect_optional<T: Send + Sync + 'static>(
+    handle: &mut Option<Handle<T>>,
+    ui: &mut Ui,
+) -> Option<Response> {
+    ui.horizontal(|ui| {
+        let response = with_resources(|resources| {
+            let assets = resources.get::<Assets<T>>()?;
+            let server = resources.get::<AssetServer>();
+
+            let (response, picked) = handle_ui(handle.as_ref(), ui, &assets, server.as_deref());
+
+            if picked.is_some() {
+                *handle = picked;
+            }
+
+            Some(response)
+        })
+        .flatten();
+
+        let mut response = match (response, handle.as_mut()) {
+            (Some(response), _) => response,
+            (None, Some(handle)) => handle.inspect(ui)?,
+            (None, None) => ui.label("None"),
+        };
+
+        if handle.is_some() && ui.small_button("✖").on_hover_text("Clear").clicked() {
+            *handle = None;
+            response.mark_changed();
+        }
+
+        Some(response)
+    })
+    .inner
+}
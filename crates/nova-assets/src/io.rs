@@ -0,0 +1,100 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::meta::visit_files;
+
+/// Where the [`AssetServer`](crate::server::AssetServer) reads asset files from, paths are
+/// relative to the asset roots.
+pub trait AssetIo: Send + Sync + 'static {
+    /// Reads the file at `path`, fails with [`io::ErrorKind::NotFound`] if there's none.
+    fn read(&self, path: &Path) -> Result<Vec<u8>, io::Error>;
+
+    fn exists(&self, path: &Path) -> bool;
+
+    /// Last modification of the file at `path`, `None` if it can't change, which disables
+    /// hot reloading it.
+    fn modified(&self, path: &Path) -> Option<SystemTime>;
+
+    /// Every file, meta files included, sorted.
+    fn files(&self) -> Vec<PathBuf>;
+
+    /// Path of the file on disk, for tools that work on the files directly.
+    #[inline]
+    fn local_path(&self, _path: &Path) -> Option<PathBuf> {
+        None
+    }
+}
+
+/// Reads loose files from the asset directories, paths are relative to the first root that
+/// contains them, or the working directory if there are no roots.
+#[derive(Clone, Debug, Default)]
+pub struct FileAssetIo {
+    roots: Vec<PathBuf>,
+}
+
+impl FileAssetIo {
+    #[inline]
+    pub fn new(roots: Vec<PathBuf>) -> Self {
+        Self { roots }
+    }
+
+    #[inline]
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// Resolves `path` against the roots.
+    #[inline]
+    pub fn resolve(&self, path: &Path) -> PathBuf {
+        self.roots
+            .iter()
+            .map(|root| root.join(path))
+            .find(|path| path.exists())
+            .or_else(|| self.roots.first().map(|root| root.join(path)))
+            .unwrap_or_else(|| path.to_path_buf())
+    }
+}
+
+impl AssetIo for FileAssetIo {
+    #[inline]
+    fn read(&self, path: &Path) -> Result<Vec<u8>, io::Error> {
+        std::fs::read(self.resolve(path))
+    }
+
+    #[inline]
+    fn exists(&self, path: &Path) -> bool {
+        self.resolve(path).exists()
+    }
+
+    #[inline]
+    fn modified(&self, path: &Path) -> Option<SystemTime> {
+        std::fs::metadata(self.resolve(path)).ok()?.modified().ok()
+    }
+
+    #[inline]
+    fn files(&self) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+
+        for root in self.roots.iter().filter(|root| root.is_dir()) {
+            let result = visit_files(root, &mut |path| {
+                files.push(path.strip_prefix(root).unwrap_or(path).to_path_buf());
+            });
+
+            if let Err(err) = result {
+                log::warn!("failed to scan '{}': {}", root.display(), err);
+            }
+        }
+
+        files.sort();
+        files.dedup();
+        files
+    }
+
+    #[inline]
+    fn local_path(&self, path: &Path) -> Option<PathBuf> {
+        Some(self.resolve(path))
+    }
+}
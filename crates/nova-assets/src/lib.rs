@@ -1,188 +1,51 @@
+pub mod event;
+pub mod handle;
+mod inspect;
+pub mod io;
+pub mod loader;
+pub mod meta;
+pub mod pack;
+pub mod pool;
+pub mod server;
+
 use std::{
     any::type_name,
     collections::HashMap,
-    hash::Hash,
-    marker::PhantomData,
-    path::{Path, PathBuf},
-    sync::Arc,
+    sync::{Arc, Weak},
 };
 
 use nova_core::{app::AppBuilder, stage, systems::Runnable, SystemBuilder};
-use nova_inspect::Inspectable;
-use serde::{Deserialize, Serialize};
-
-#[derive(
-    Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, Inspectable,
-)]
-pub enum InnerHandle {
-    Id(u64),
-    Path(PathBuf),
-}
-
-pub struct Handle<T: 'static> {
-    inner: InnerHandle,
-    marker: Option<Arc<PhantomData<&'static T>>>,
-}
-
-impl<T> Handle<T> {
-    #[inline]
-    pub const fn from_u64(id: u64) -> Self {
-        Self {
-            inner: InnerHandle::Id(id),
-            marker: None,
-        }
-    }
-
-    #[inline]
-    pub fn from_inner(inner: InnerHandle) -> Self {
-        Self {
-            inner,
-            marker: Some(Arc::new(PhantomData)),
-        }
-    }
-
-    #[inline]
-    pub fn cast<U: 'static>(self) -> Handle<U> {
-        Handle {
-            inner: self.inner,
-            marker: Some(Arc::new(PhantomData)),
-        }
-    }
-
-    #[inline]
-    pub fn unwrap_id(self) -> u64 {
-        match self.inner {
-            InnerHandle::Id(id) => id,
-            _ => panic!("tried to unwrap path handle"),
-        }
-    }
-
-    #[inline]
-    pub fn unwrap_path(self) -> PathBuf {
-        match self.inner {
-            InnerHandle::Path(path) => path,
-            _ => panic!("tried to unwrap id handle"),
-        }
-    }
-}
-
-impl<T> Clone for Handle<T> {
-    #[inline]
-    fn clone(&self) -> Self {
-        Self {
-            inner: self.inner.clone(),
-            marker: self.marker.clone(),
-        }
-    }
-}
 
-impl<T> PartialEq for Handle<T> {
-    #[inline]
-    fn eq(&self, other: &Self) -> bool {
-        self.inner == other.inner
-    }
-}
-
-impl<T> Eq for Handle<T> {}
-
-impl<T> Hash for Handle<T> {
-    #[inline]
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.inner.hash(state);
-    }
-}
-
-impl<T> PartialOrd for Handle<T> {
-    #[inline]
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.inner.partial_cmp(&other.inner)
-    }
-}
-
-impl<T> Ord for Handle<T> {
-    #[inline]
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.inner.cmp(&other.inner)
-    }
-}
-
-impl<T> serde::Serialize for Handle<T> {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        self.inner.serialize(serializer)
-    }
-}
-
-impl<'de, T> serde::Deserialize<'de> for Handle<T> {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        Ok(Handle::from_inner(InnerHandle::deserialize(deserializer)?))
-    }
-}
+pub use handle::{AssetHandle, Handle, HandleId, InnerHandle, WeakHandle};
+pub use inspect::inspect_optional;
+pub use uuid::Uuid;
 
-impl<T> Inspectable for Handle<T> {
-    #[inline]
-    fn name(&self) -> &'static str {
-        "Handle"
-    }
-
-    #[inline]
-    fn inspect(&mut self, ui: &mut nova_inspect::egui::Ui) -> Option<nova_inspect::egui::Response> {
-        self.inner.inspect(ui)
-    }
-}
-
-impl<T> From<&str> for Handle<T> {
-    #[inline]
-    fn from(path: &str) -> Self {
-        Self {
-            inner: InnerHandle::Path(path.into()),
-            marker: Some(Arc::new(PhantomData)),
-        }
-    }
-}
+use event::{AssetEvent, AssetEvents};
+use loader::AssetLoader;
+use server::{
+    asset_server_system, load_progress_system, update_server_system, AssetServer, LoadProgress,
+};
 
-impl<T> From<PathBuf> for Handle<T> {
-    #[inline]
-    fn from(path: PathBuf) -> Self {
-        Self {
-            inner: InnerHandle::Path(path),
-            marker: Some(Arc::new(PhantomData)),
-        }
-    }
-}
+/// Stage before [`stage::PRE_UPDATE`] where finished loads are inserted into [`Assets`].
+pub const LOAD_ASSETS_STAGE: &str = "load_assets";
 
-impl<T> From<&Path> for Handle<T> {
-    #[inline]
-    fn from(path: &Path) -> Self {
-        Self {
-            inner: InnerHandle::Path(path.into()),
-            marker: Some(Arc::new(PhantomData)),
-        }
-    }
+/// Entries only hold weak references, so an asset is kept alive by handles alone.
+pub struct AssetEntry<T: 'static> {
+    asset: T,
+    inner: InnerHandle,
+    refs: Option<Weak<()>>,
 }
 
-impl<T> From<u64> for Handle<T> {
+impl<T> AssetEntry<T> {
     #[inline]
-    fn from(id: u64) -> Self {
-        Self {
-            inner: InnerHandle::Id(id),
-            marker: Some(Arc::new(PhantomData)),
-        }
+    fn weak_handle(&self) -> WeakHandle<T> {
+        WeakHandle::new(self.inner.clone(), self.refs.clone())
     }
 }
 
-pub struct AssetEntry<T: 'static> {
-    asset: T,
-    handle: Option<Arc<PhantomData<&'static T>>>,
-}
-
 pub struct Assets<T: 'static> {
-    assets: HashMap<InnerHandle, AssetEntry<T>>,
+    assets: HashMap<HandleId, AssetEntry<T>>,
+    events: AssetEvents<T>,
     next_id: u64,
 }
 
@@ -191,6 +54,7 @@ impl<T> Default for Assets<T> {
     fn default() -> Self {
         Self {
             assets: Default::default(),
+            events: Default::default(),
             next_id: 0,
         }
     }
@@ -201,70 +65,126 @@ impl<T: 'static> Assets<T> {
     pub fn new() -> Self {
         Self {
             assets: Default::default(),
+            events: Default::default(),
             next_id: 0,
         }
     }
 
+    /// Returns a strong handle to an unused id.
     #[inline]
     pub fn make_handle(&mut self) -> Handle<T> {
-        while self.assets.contains_key(&InnerHandle::Id(self.next_id)) {
+        while self.assets.contains_key(&HandleId::Id(self.next_id)) {
             self.next_id += 1;
         }
 
         let id = self.next_id;
         self.next_id += 1;
 
-        Handle {
-            inner: InnerHandle::Id(id),
-            marker: Some(Arc::new(PhantomData)),
+        Handle::tracked(InnerHandle::Id(id), Arc::new(()))
+    }
+
+    /// Inserts `entry`, sending [`AssetEvent::Created`] or [`AssetEvent::Modified`] if
+    /// there already was an asset at `id`.
+    #[inline]
+    fn insert_entry(&mut self, id: HandleId, entry: AssetEntry<T>) {
+        let handle = entry.weak_handle();
+
+        if self.assets.insert(id, entry).is_some() {
+            self.events.send(AssetEvent::Modified(handle));
+        } else {
+            self.events.send(AssetEvent::Created(handle));
         }
     }
 
     #[inline]
     pub fn add(&mut self, asset: T) -> Handle<T> {
         let handle = self.make_handle();
+        self.insert(&handle, asset);
+
+        handle
+    }
 
-        self.assets.insert(
-            handle.inner.clone(),
+    #[inline]
+    pub fn contains(&self, handle: &impl AssetHandle<T>) -> bool {
+        self.assets.contains_key(&handle.id())
+    }
+
+    /// Inserts `asset` at `handle`, it's kept as long as `handle` or clones of it exist,
+    /// or forever if `handle` is untracked.
+    #[inline]
+    pub fn insert(&mut self, handle: &Handle<T>, asset: T) {
+        self.insert_entry(
+            handle.id(),
             AssetEntry {
                 asset,
-                handle: handle.marker.clone(),
+                inner: handle.inner().clone(),
+                refs: handle.refs().map(Arc::downgrade),
             },
         );
-
-        handle
     }
 
+    /// Replaces the asset at `handle` if it exists, keeping it tracked and sending
+    /// [`AssetEvent::Modified`].
     #[inline]
-    pub fn contains(&self, handle: &Handle<T>) -> bool {
-        self.assets.contains_key(&handle.inner)
+    pub fn replace(&mut self, handle: &impl AssetHandle<T>, asset: T) -> Option<T> {
+        let entry = self.assets.get_mut(&handle.id())?;
+        let old = std::mem::replace(&mut entry.asset, asset);
+
+        self.events.send(AssetEvent::Modified(entry.weak_handle()));
+
+        Some(old)
     }
 
+    /// Inserts `asset` at `handle` without keeping track of it, it's never removed by
+    /// [`Assets::clean`].
     #[inline]
     pub fn insert_untracked(&mut self, handle: impl Into<Handle<T>>, asset: T) -> Handle<T> {
         let handle = handle.into();
 
-        self.assets.insert(
-            handle.inner.clone(),
+        self.insert_entry(
+            handle.id(),
             AssetEntry {
                 asset,
-                handle: None,
+                inner: handle.inner().clone(),
+                refs: None,
             },
         );
 
         handle
     }
 
+    /// Removes the asset at `handle` even if it's still referenced.
     #[inline]
-    pub fn get(&self, handle: &Handle<T>) -> Option<&T> {
-        self.assets.get(&handle.inner).map(|entry| &entry.asset)
+    pub fn remove(&mut self, handle: &impl AssetHandle<T>) -> Option<T> {
+        let entry = self.assets.remove(&handle.id())?;
+
+        self.events.send(AssetEvent::Removed(entry.weak_handle()));
+
+        Some(entry.asset)
     }
 
     #[inline]
-    pub fn get_mut(&mut self, handle: &Handle<T>) -> Option<&mut T> {
-        self.assets
-            .get_mut(&handle.inner)
-            .map(|entry| &mut entry.asset)
+    pub fn get(&self, handle: &impl AssetHandle<T>) -> Option<&T> {
+        self.assets.get(&handle.id()).map(|entry| &entry.asset)
+    }
+
+    /// Sends [`AssetEvent::Modified`] if the asset exists, use [`Assets::get`] for reading.
+    #[inline]
+    pub fn get_mut(&mut self, handle: &impl AssetHandle<T>) -> Option<&mut T> {
+        let entry = self.assets.get_mut(&handle.id())?;
+
+        self.events.send(AssetEvent::Modified(entry.weak_handle()));
+
+        Some(&mut entry.asset)
+    }
+
+    /// Number of strong handles to the asset at `handle`, `None` if the asset doesn't exist
+    /// or is untracked.
+    #[inline]
+    pub fn ref_count(&self, handle: &impl AssetHandle<T>) -> Option<usize> {
+        let refs = self.assets.get(&handle.id())?.refs.as_ref()?;
+
+        Some(refs.strong_count())
     }
 
     #[inline]
@@ -272,13 +192,40 @@ impl<T: 'static> Assets<T> {
         self.assets.values().map(|entry| &entry.asset)
     }
 
+    /// Iterates over weak handles to every asset along with the asset.
+    #[inline]
+    pub fn iter_handles(&self) -> impl Iterator<Item = (WeakHandle<T>, &T)> {
+        self.assets
+            .values()
+            .map(|entry| (entry.weak_handle(), &entry.asset))
+    }
+
+    #[inline]
+    pub fn events(&self) -> &AssetEvents<T> {
+        &self.events
+    }
+
+    #[inline]
+    pub fn update_events(&mut self) {
+        self.events.update();
+    }
+
+    /// Removes assets that are no longer referenced, sending [`AssetEvent::Removed`].
     #[inline]
     pub fn clean(&mut self) {
+        let events = &mut self.events;
+
         self.assets.retain(|_id, entry| {
-            entry
-                .handle
-                .as_mut()
-                .map_or(true, |h| Arc::get_mut(h).is_none())
+            let keep = entry
+                .refs
+                .as_ref()
+                .map_or(true, |refs| refs.strong_count() > 0);
+
+            if !keep {
+                events.send(AssetEvent::Removed(entry.weak_handle()));
+            }
+
+            keep
         })
     }
 }
@@ -291,15 +238,48 @@ pub fn asset_system<T: Send + Sync + 'static>() -> impl Runnable {
 
 pub trait AssetsAppExt {
     fn register_asset<T: Send + Sync + 'static>(&mut self) -> &mut Self;
+
+    fn add_asset_loader<L: AssetLoader>(&mut self, loader: L) -> &mut Self;
 }
 
 impl AssetsAppExt for AppBuilder {
     fn register_asset<T: Send + Sync + 'static>(&mut self) -> &mut Self {
         self.register_resource::<Assets<T>>();
+        self.register_resource::<AssetServer>();
+
+        if !self.stages.contains_key(LOAD_ASSETS_STAGE) {
+            self.register_resource::<LoadProgress>();
+            self.add_stage_before(LOAD_ASSETS_STAGE, stage::PRE_UPDATE);
+            self.add_system_to_stage(LOAD_ASSETS_STAGE, update_server_system());
+            self.add_system_to_stage(stage::PRE_UPDATE, load_progress_system());
+
+            #[cfg(feature = "editor")]
+            {
+                self.asset_files = Some(server::asset_files);
+                self.add_editor_stage_before(LOAD_ASSETS_STAGE, stage::PRE_UPDATE);
+                self.add_editor_system_to_stage(LOAD_ASSETS_STAGE, update_server_system());
+                self.add_editor_system_to_stage(stage::PRE_UPDATE, load_progress_system());
+            }
+        }
+
+        self.add_system_to_stage(LOAD_ASSETS_STAGE, asset_server_system::<T>());
         self.add_system_to_stage(stage::POST_UPDATE, asset_system::<T>());
 
         #[cfg(feature = "editor")]
-        self.add_editor_system_to_stage(stage::POST_UPDATE, asset_system::<T>());
+        {
+            self.add_editor_system_to_stage(LOAD_ASSETS_STAGE, asset_server_system::<T>());
+            self.add_editor_system_to_stage(stage::POST_UPDATE, asset_system::<T>());
+        }
+
+        self
+    }
+
+    fn add_asset_loader<L: AssetLoader>(&mut self, loader: L) -> &mut Self {
+        self.register_resource::<AssetServer>();
+        self.resources
+            .get_mut::<AssetServer>()
+            .unwrap()
+            .add_loader(loader);
 
         self
     }
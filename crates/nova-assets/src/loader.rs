@@ -0,0 +1,253 @@
+use std::{
+    any::{Any, TypeId},
+    io,
+    path::{Component, Path, PathBuf},
+    sync::Mutex,
+};
+
+use crate::{
+    io::AssetIo,
+    meta::ImportSettings,
+    server::{Key, Marker, ServerState},
+    Handle, InnerHandle,
+};
+
+pub type AssetError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Path of the asset labeled `label` in the file at `path`, e.g. `level.gltf#Mesh0`.
+#[inline]
+pub fn label_path(path: &Path, label: &str) -> PathBuf {
+    PathBuf::from(format!("{}#{}", path.display(), label))
+}
+
+/// Splits a path made by [`label_path`] into the path of the file and the label.
+///
+/// Only a `#` right after the extension of the file starts the label, so file names can
+/// contain `#`, e.g. `level#2.gltf#Mesh0` is the label `Mesh0` of `level#2.gltf`.
+#[inline]
+pub fn split_label(path: &Path) -> Option<(PathBuf, &str)> {
+    // labels can contain '/', so the whole path is split
+    let path = path.to_str()?;
+
+    path.match_indices('#').find_map(|(i, _)| {
+        let file = Path::new(&path[..i]);
+
+        if file.extension().is_some() {
+            Some((file.to_path_buf(), &path[i + 1..]))
+        } else {
+            None
+        }
+    })
+}
+
+/// Asset added with [`LoadContext::add_labeled`].
+pub(crate) struct LabeledAsset {
+    pub(crate) key: Key,
+    pub(crate) marker: Marker,
+    pub(crate) asset: Box<dyn Any + Send + Sync>,
+}
+
+/// Passed to [`AssetLoader::load`] with information about the asset being loaded.
+pub struct LoadContext<'a> {
+    path: PathBuf,
+    settings: ImportSettings,
+    io: &'a dyn AssetIo,
+    state: &'a Mutex<ServerState>,
+    dependencies: Vec<Key>,
+    /// Files read with [`LoadContext::read`].
+    files: Vec<PathBuf>,
+    labeled: Vec<LabeledAsset>,
+}
+
+impl<'a> LoadContext<'a> {
+    #[inline]
+    pub(crate) fn new(
+        path: PathBuf,
+        settings: ImportSettings,
+        io: &'a dyn AssetIo,
+        state: &'a Mutex<ServerState>,
+    ) -> Self {
+        Self {
+            path,
+            settings,
+            io,
+            state,
+            dependencies: Vec::new(),
+            files: Vec::new(),
+            labeled: Vec::new(),
+        }
+    }
+
+    /// Path of the asset being loaded relative to the asset roots.
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Import settings from the [`AssetMeta`](crate::meta::AssetMeta) of the asset, or the
+    /// defaults if it has none.
+    #[inline]
+    pub fn settings(&self) -> &ImportSettings {
+        &self.settings
+    }
+
+    /// Resolves `path` relative to the directory of the asset being loaded.
+    #[inline]
+    pub fn relative(&self, path: impl AsRef<Path>) -> PathBuf {
+        let dir = self.path.parent().unwrap_or_else(|| Path::new(""));
+        let mut resolved = PathBuf::new();
+
+        for component in dir.join(path).components() {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    resolved.pop();
+                }
+                component => resolved.push(component),
+            }
+        }
+
+        resolved
+    }
+
+    /// Loads a dependency of the asset, `path` is relative to the asset roots like paths
+    /// passed to [`AssetServer::load`](crate::server::AssetServer::load), see
+    /// [`LoadContext::relative`].
+    ///
+    /// The load is started once the loader returns, and the dependency is included in
+    /// [`AssetServer::recursive_load_state`](crate::server::AssetServer::recursive_load_state).
+    /// Assets in a dependency cycle keep each other alive if they store the handles.
+    #[inline]
+    pub fn load<T: Send + Sync + 'static>(&mut self, path: impl Into<PathBuf>) -> Handle<T> {
+        self.request(InnerHandle::Path(path.into()))
+    }
+
+    /// Loads the asset `handle` refers to as a dependency, e.g. a handle deserialized from
+    /// the file, like [`AssetServer::load_handle`](crate::server::AssetServer::load_handle).
+    /// Id handles aren't loaded from disk and are returned as is.
+    #[inline]
+    pub fn load_handle<T: Send + Sync + 'static>(&mut self, handle: &Handle<T>) -> Handle<T> {
+        match handle.inner() {
+            InnerHandle::Id(_) => handle.clone(),
+            inner => self.request(inner.clone()),
+        }
+    }
+
+    #[inline]
+    fn request<T: Send + Sync + 'static>(&mut self, inner: InnerHandle) -> Handle<T> {
+        let (key, handle) = self.state.lock().unwrap().request::<T>(inner, self.io);
+
+        self.dependencies.push(key);
+
+        handle
+    }
+
+    /// Reads another file, e.g. the buffers of a scene. `path` is relative to the asset
+    /// roots, see [`LoadContext::relative`].
+    ///
+    /// The file is recorded as part of the asset, see
+    /// [`AssetServer::dependency_files`](crate::server::AssetServer::dependency_files).
+    #[inline]
+    pub fn read(&mut self, path: impl AsRef<Path>) -> Result<Vec<u8>, io::Error> {
+        let path = path.as_ref();
+
+        if !self.files.iter().any(|file| file == path) {
+            self.files.push(path.to_path_buf());
+        }
+
+        self.io.read(path)
+    }
+
+    /// Adds an asset found in the file besides the one loaded, e.g. a mesh of a scene. It's
+    /// stored at [`label_path`] of the file and delivered with the loaded asset, loading
+    /// the labeled path later loads the whole file again.
+    #[inline]
+    pub fn add_labeled<T: Send + Sync + 'static>(&mut self, label: &str, asset: T) -> Handle<T> {
+        let key = (
+            TypeId::of::<T>(),
+            InnerHandle::Path(label_path(&self.path, label)),
+        );
+        let (refs, marker) = self.state.lock().unwrap().labeled_handle(&key);
+
+        self.labeled.push(LabeledAsset {
+            key: key.clone(),
+            marker,
+            asset: Box::new(asset),
+        });
+
+        Handle::tracked(key.1, refs)
+    }
+
+    #[inline]
+    pub(crate) fn into_parts(self) -> (Vec<Key>, Vec<PathBuf>, Vec<LabeledAsset>) {
+        (self.dependencies, self.files, self.labeled)
+    }
+}
+
+/// Loads assets of type [`AssetLoader::Asset`] from files with one of
+/// [`AssetLoader::extensions`].
+pub trait AssetLoader: Send + Sync + 'static {
+    type Asset: Send + Sync + 'static;
+
+    /// Extensions handled by the loader, without the leading '.'.
+    fn extensions(&self) -> &[&str];
+
+    fn load(&self, bytes: &[u8], ctx: &mut LoadContext<'_>) -> Result<Self::Asset, AssetError>;
+}
+
+pub(crate) trait ErasedLoader: Send + Sync {
+    fn asset_type(&self) -> TypeId;
+
+    fn extensions(&self) -> &[&str];
+
+    fn load(
+        &self,
+        bytes: &[u8],
+        ctx: &mut LoadContext<'_>,
+    ) -> Result<Box<dyn Any + Send + Sync>, AssetError>;
+}
+
+impl<L: AssetLoader> ErasedLoader for L {
+    #[inline]
+    fn asset_type(&self) -> TypeId {
+        TypeId::of::<L::Asset>()
+    }
+
+    #[inline]
+    fn extensions(&self) -> &[&str] {
+        AssetLoader::extensions(self)
+    }
+
+    #[inline]
+    fn load(
+        &self,
+        bytes: &[u8],
+        ctx: &mut LoadContext<'_>,
+    ) -> Result<Box<dyn Any + Send + Sync>, AssetError> {
+        Ok(Box::new(AssetLoader::load(self, bytes, ctx)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_label_after_extension() {
+        let path = label_path(Path::new("assets/level.gltf"), "Mesh0/Primitive1");
+
+        assert_eq!(
+            split_label(&path),
+            Some((PathBuf::from("assets/level.gltf"), "Mesh0/Primitive1"))
+        );
+    }
+
+    #[test]
+    fn split_label_ignores_hash_in_file_name() {
+        assert_eq!(split_label(Path::new("assets/level#2.obj")), None);
+        assert_eq!(
+            split_label(Path::new("assets/level#2.gltf#Mesh0")),
+            Some((PathBuf::from("assets/level#2.gltf"), "Mesh0"))
+        );
+    }
+}
@@ -0,0 +1,295 @@
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{io::AssetIo, loader::AssetError};
+
+/// Extension of meta files, which are stored next to their asset as `<file>.meta`.
+pub const META_EXTENSION: &str = "meta";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TextureSettings {
+    /// Whether the texture holds colors in sRGB, false for data like normal maps.
+    pub srgb: bool,
+    pub mipmaps: bool,
+}
+
+impl Default for TextureSettings {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            srgb: true,
+            mipmaps: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MeshSettings {
+    pub scale: f32,
+    /// Generate normals, replacing the ones in the file if any.
+    pub generate_normals: bool,
+    /// Generated normals are averaged over the faces sharing a vertex instead of each face
+    /// having its own.
+    pub smooth_normals: bool,
+}
+
+impl Default for MeshSettings {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            generate_normals: false,
+            smooth_normals: true,
+        }
+    }
+}
+
+/// Settings passed to loaders through [`LoadContext::settings`](crate::loader::LoadContext::settings),
+/// loaders use the ones relevant to them.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImportSettings {
+    pub texture: TextureSettings,
+    pub mesh: MeshSettings,
+}
+
+/// Contents of the `.meta` file next to an asset.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AssetMeta {
+    pub uuid: Uuid,
+    #[serde(default)]
+    pub settings: ImportSettings,
+}
+
+/// Path of the meta file of the asset at `path`.
+#[inline]
+pub fn meta_path(path: &Path) -> PathBuf {
+    let mut meta = OsString::from(path.as_os_str());
+    meta.push(".");
+    meta.push(META_EXTENSION);
+
+    PathBuf::from(meta)
+}
+
+#[inline]
+pub fn is_meta(path: &Path) -> bool {
+    path.extension().map_or(false, |ext| ext == META_EXTENSION)
+}
+
+impl AssetMeta {
+    /// Creates meta with a new random uuid.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            uuid: Uuid::new_v4(),
+            settings: ImportSettings::default(),
+        }
+    }
+
+    /// Reads the meta of the asset at `path`, `None` if it has none.
+    #[inline]
+    pub fn read(path: &Path) -> Result<Option<Self>, AssetError> {
+        Self::parse(std::fs::read(meta_path(path)))
+    }
+
+    /// Reads the meta of the asset at `path` from `io`, `None` if it has none.
+    #[inline]
+    pub fn read_io(io: &dyn AssetIo, path: &Path) -> Result<Option<Self>, AssetError> {
+        Self::parse(io.read(&meta_path(path)))
+    }
+
+    #[inline]
+    fn parse(source: Result<Vec<u8>, std::io::Error>) -> Result<Option<Self>, AssetError> {
+        let source = match source {
+            Ok(source) => source,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(Some(ron::de::from_bytes(&source)?))
+    }
+
+    /// Writes the meta of the asset at `path`.
+    #[inline]
+    pub fn write(&self, path: &Path) -> Result<(), AssetError> {
+        let source = ron::ser::to_string_pretty(self, Default::default())?;
+        std::fs::write(meta_path(path), source)?;
+
+        Ok(())
+    }
+
+    /// Reads the meta of the asset at `path`, creating it if it has none.
+    #[inline]
+    pub fn read_or_create(path: &Path) -> Result<Self, AssetError> {
+        match Self::read(path)? {
+            Some(meta) => Ok(meta),
+            None => {
+                let meta = Self::new();
+                meta.write(path)?;
+
+                Ok(meta)
+            }
+        }
+    }
+}
+
+impl Default for AssetMeta {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Calls `f` with every file in `dir` and its subdirectories.
+#[inline]
+pub(crate) fn visit_files(dir: &Path, f: &mut impl FnMut(&Path)) -> Result<(), std::io::Error> {
+    for entry in dir.read_dir()? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            visit_files(&path, f)?;
+        } else {
+            f(&path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Finds the uuids of every asset in `io`, mapped to their paths. Assets with unreadable
+/// meta files are skipped, a uuid shared by several assets maps to the first path in order.
+#[inline]
+pub fn scan_uuids(io: &dyn AssetIo) -> HashMap<Uuid, PathBuf> {
+    let mut uuids = HashMap::<Uuid, PathBuf>::new();
+
+    for path in io.files().into_iter().filter(|path| !is_meta(path)) {
+        match AssetMeta::read_io(io, &path) {
+            Ok(Some(meta)) => match uuids.entry(meta.uuid) {
+                Entry::Occupied(entry) => log::warn!(
+                    "'{}' has the same uuid as '{}', was it copied with its meta file?",
+                    path.display(),
+                    entry.get().display()
+                ),
+                Entry::Vacant(entry) => {
+                    entry.insert(path);
+                }
+            },
+            Ok(None) => {}
+            Err(err) => log::warn!("invalid meta for '{}': {}", path.display(), err),
+        }
+    }
+
+    uuids
+}
+
+/// Creates meta files for every asset in `dir` that doesn't have one, returns how many
+/// were created.
+///
+/// Assets copied together with their meta file share a uuid with the original, the copies
+/// are given new uuids. The asset with the oldest meta file is taken as the original.
+#[inline]
+pub fn create_missing(dir: &Path) -> Result<usize, std::io::Error> {
+    let mut created = 0;
+    let mut existing = Vec::new();
+
+    visit_files(dir, &mut |path| {
+        if is_meta(path) {
+            return;
+        }
+
+        if meta_path(path).exists() {
+            existing.push(path.to_path_buf());
+            return;
+        }
+
+        match AssetMeta::new().write(path) {
+            Ok(()) => created += 1,
+            Err(err) => log::warn!("failed to create meta for '{}': {}", path.display(), err),
+        }
+    })?;
+
+    let mut metas = existing
+        .into_iter()
+        .filter_map(|path| match AssetMeta::read(&path) {
+            Ok(meta) => {
+                let modified = std::fs::metadata(meta_path(&path))
+                    .and_then(|metadata| metadata.modified())
+                    .ok();
+
+                meta.map(|meta| (modified, path, meta))
+            }
+            Err(err) => {
+                log::warn!("invalid meta for '{}': {}", path.display(), err);
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // originals come before their copies
+    metas.sort_by(|(a_modified, a_path, _), (b_modified, b_path, _)| {
+        a_modified.cmp(b_modified).then_with(|| a_path.cmp(b_path))
+    });
+
+    let mut uuids = HashMap::<Uuid, PathBuf>::new();
+
+    for (_, path, mut meta) in metas {
+        let original = match uuids.entry(meta.uuid) {
+            Entry::Occupied(entry) => entry.get().clone(),
+            Entry::Vacant(entry) => {
+                entry.insert(path);
+                continue;
+            }
+        };
+
+        meta.uuid = Uuid::new_v4();
+
+        match meta.write(&path) {
+            Ok(()) => log::warn!(
+                "'{}' had the same uuid as '{}', gave it a new one",
+                path.display(),
+                original.display()
+            ),
+            Err(err) => log::warn!("failed to write meta for '{}': {}", path.display(), err),
+        }
+    }
+
+    Ok(created)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copies_get_new_uuids() {
+        let dir = std::env::temp_dir().join(format!("nova-meta-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let original = dir.join("a.txt");
+        let copy = dir.join("b.txt");
+
+        std::fs::write(&original, "a").unwrap();
+        assert_eq!(create_missing(&dir).unwrap(), 1);
+        let uuid = AssetMeta::read(&original).unwrap().unwrap().uuid;
+
+        std::fs::copy(&original, &copy).unwrap();
+        std::fs::copy(meta_path(&original), meta_path(&copy)).unwrap();
+        assert_eq!(create_missing(&dir).unwrap(), 0);
+
+        let original_uuid = AssetMeta::read(&original).unwrap().unwrap().uuid;
+        let copy_uuid = AssetMeta::read(&copy).unwrap().unwrap().uuid;
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(original_uuid, uuid);
+        assert_ne!(copy_uuid, uuid);
+    }
+}
@@ -0,0 +1,272 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Component, Path, PathBuf},
+    sync::Mutex,
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{io::AssetIo, loader::AssetError};
+
+/// Extension of asset archives.
+pub const PACK_EXTENSION: &str = "pack";
+
+// archives start with the magic, the version and the length of the index as little endian
+// u32 and u64, followed by the ron index and the blobs of every file
+const MAGIC: &[u8; 8] = b"NOVAPACK";
+const VERSION: u32 = 1;
+const COMPRESSION_LEVEL: u8 = 6;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct PackEntry {
+    /// Offset of the blob from the end of the index.
+    offset: u64,
+    /// Length of the blob.
+    stored: u64,
+    /// Length of the file, the blob is deflated if it's not the same.
+    size: u64,
+}
+
+/// Path with `/` separators so archives are the same on every platform.
+#[inline]
+fn pack_path(path: &Path) -> String {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[inline]
+fn invalid_data(err: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err.to_string())
+}
+
+/// Builds an archive read by [`PackedAssetIo`], files are deflated unless that doesn't
+/// make them smaller.
+#[derive(Default)]
+pub struct PackWriter {
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+impl PackWriter {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the file at `path` relative to the asset roots, replacing any file there.
+    #[inline]
+    pub fn add(&mut self, path: &Path, data: Vec<u8>) {
+        self.files.insert(pack_path(path), data);
+    }
+
+    /// Adds every file in `io`.
+    #[inline]
+    pub fn add_io(&mut self, io: &dyn AssetIo) -> Result<(), io::Error> {
+        for path in io.files() {
+            let data = io.read(&path)?;
+            self.add(&path, data);
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    #[inline]
+    pub fn write(&self, writer: &mut impl Write) -> Result<(), io::Error> {
+        let mut index = BTreeMap::new();
+        let mut blobs = Vec::new();
+
+        for (path, data) in &self.files {
+            let compressed = miniz_oxide::deflate::compress_to_vec(data, COMPRESSION_LEVEL);
+            let blob = if compressed.len() < data.len() {
+                &compressed
+            } else {
+                data
+            };
+
+            index.insert(
+                path.clone(),
+                PackEntry {
+                    offset: blobs.len() as u64,
+                    stored: blob.len() as u64,
+                    size: data.len() as u64,
+                },
+            );
+
+            blobs.extend_from_slice(blob);
+        }
+
+        let index = ron::to_string(&index).map_err(invalid_data)?;
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&(index.len() as u64).to_le_bytes())?;
+        writer.write_all(index.as_bytes())?;
+        writer.write_all(&blobs)?;
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn write_file(&self, path: &Path) -> Result<(), io::Error> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()?;
+
+        Ok(())
+    }
+}
+
+/// Reads assets from an archive made by [`PackWriter`], the index is kept in memory and
+/// files are read from the archive on demand.
+pub struct PackedAssetIo {
+    file: Mutex<BufReader<File>>,
+    /// Start of the blobs in the archive.
+    data_start: u64,
+    /// Length of the archive, entries must end before it.
+    file_len: u64,
+    entries: HashMap<String, PackEntry>,
+}
+
+impl PackedAssetIo {
+    #[inline]
+    pub fn open(path: &Path) -> Result<Self, AssetError> {
+        let file = File::open(path)?;
+        let file_len = file.metadata()?.len();
+        let mut file = BufReader::new(file);
+
+        let mut magic = [0; 8];
+        file.read_exact(&mut magic)?;
+
+        if magic != *MAGIC {
+            return Err(format!("'{}' is not an asset archive", path.display()).into());
+        }
+
+        let mut version = [0; 4];
+        file.read_exact(&mut version)?;
+        let version = u32::from_le_bytes(version);
+
+        if version != VERSION {
+            return Err(format!("unsupported archive version {}", version).into());
+        }
+
+        let mut index_len = [0; 8];
+        file.read_exact(&mut index_len)?;
+        let index_len = u64::from_le_bytes(index_len);
+
+        // the length isn't trusted, a truncated or tampered archive could allocate anything
+        let header_len = (MAGIC.len() + 4 + 8) as u64;
+        if index_len > file_len.saturating_sub(header_len) {
+            return Err(format!("'{}' has a truncated index", path.display()).into());
+        }
+
+        let mut index = vec![0; index_len as usize];
+        file.read_exact(&mut index)?;
+
+        Ok(Self {
+            file: Mutex::new(file),
+            data_start: header_len + index_len,
+            file_len,
+            entries: ron::de::from_bytes(&index)?,
+        })
+    }
+}
+
+impl AssetIo for PackedAssetIo {
+    #[inline]
+    fn read(&self, path: &Path) -> Result<Vec<u8>, io::Error> {
+        let entry = match self.entries.get(&pack_path(path)) {
+            Some(entry) => entry,
+            None => {
+                let err = format!("'{}' is not in the archive", path.display());
+                return Err(io::Error::new(io::ErrorKind::NotFound, err));
+            }
+        };
+
+        let end = self
+            .data_start
+            .checked_add(entry.offset)
+            .and_then(|start| start.checked_add(entry.stored));
+        if end.map_or(true, |end| end > self.file_len) {
+            return Err(invalid_data("blob extends past the end of the archive"));
+        }
+
+        let mut blob = vec![0; entry.stored as usize];
+
+        {
+            let mut file = self.file.lock().unwrap();
+            file.seek(SeekFrom::Start(self.data_start + entry.offset))?;
+            file.read_exact(&mut blob)?;
+        }
+
+        if entry.stored == entry.size {
+            return Ok(blob);
+        }
+
+        let data = miniz_oxide::inflate::decompress_to_vec(&blob)
+            .map_err(|err| invalid_data(format!("failed to inflate: {:?}", err)))?;
+
+        if data.len() as u64 != entry.size {
+            return Err(invalid_data("inflated size doesn't match the index"));
+        }
+
+        Ok(data)
+    }
+
+    #[inline]
+    fn exists(&self, path: &Path) -> bool {
+        self.entries.contains_key(&pack_path(path))
+    }
+
+    #[inline]
+    fn modified(&self, _path: &Path) -> Option<SystemTime> {
+        None
+    }
+
+    #[inline]
+    fn files(&self) -> Vec<PathBuf> {
+        let mut files = self.entries.keys().map(PathBuf::from).collect::<Vec<_>>();
+        files.sort();
+        files
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reject_index_longer_than_archive() {
+        let mut writer = PackWriter::new();
+        writer.add(Path::new("text.txt"), b"hello".to_vec());
+
+        let mut archive = Vec::new();
+        writer.write(&mut archive).unwrap();
+
+        let path = std::env::temp_dir().join(format!("nova-pack-{}.pack", std::process::id()));
+
+        archive[12..20].copy_from_slice(&u64::MAX.to_le_bytes());
+        std::fs::write(&path, &archive).unwrap();
+        let res = PackedAssetIo::open(&path);
+
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(res.is_err());
+    }
+}
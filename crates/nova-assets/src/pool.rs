@@ -0,0 +1,106 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+};
+
+type Job = Box<dyn FnOnce() + Send>;
+
+struct Workers {
+    sender: Sender<Job>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+/// Threads running asset loads, spawned on the first job.
+///
+/// Dropping the pool skips queued jobs and waits for running ones, so no loader code is
+/// running once it's dropped, which matters when the game lib is unloaded.
+pub struct TaskPool {
+    num_threads: usize,
+    closed: Arc<AtomicBool>,
+    workers: Mutex<Option<Workers>>,
+}
+
+impl Default for TaskPool {
+    #[inline]
+    fn default() -> Self {
+        let num_threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+
+        Self::new(num_threads)
+    }
+}
+
+impl TaskPool {
+    #[inline]
+    pub fn new(num_threads: usize) -> Self {
+        Self {
+            num_threads: num_threads.max(1),
+            closed: Arc::new(AtomicBool::new(false)),
+            workers: Mutex::new(None),
+        }
+    }
+
+    #[inline]
+    fn spawn_workers(&self) -> Workers {
+        let (sender, receiver) = channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let threads = (0..self.num_threads)
+            .map(|i| {
+                let receiver = receiver.clone();
+                let closed = self.closed.clone();
+
+                std::thread::Builder::new()
+                    .name(format!("asset worker {}", i))
+                    .spawn(move || worker(&receiver, &closed))
+                    .expect("failed to spawn asset worker")
+            })
+            .collect();
+
+        Workers { sender, threads }
+    }
+
+    #[inline]
+    pub fn spawn(&self, job: impl FnOnce() + Send + 'static) {
+        let mut workers = self.workers.lock().unwrap();
+
+        workers
+            .get_or_insert_with(|| self.spawn_workers())
+            .sender
+            .send(Box::new(job))
+            .unwrap();
+    }
+}
+
+fn worker(receiver: &Mutex<Receiver<Job>>, closed: &AtomicBool) {
+    loop {
+        let job = match receiver.lock().unwrap().recv() {
+            Ok(job) => job,
+            Err(_) => return,
+        };
+
+        if closed.load(Ordering::Acquire) {
+            continue;
+        }
+
+        job();
+    }
+}
+
+impl Drop for TaskPool {
+    #[inline]
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::Release);
+
+        if let Some(workers) = self.workers.get_mut().unwrap().take() {
+            drop(workers.sender);
+
+            for thread in workers.threads {
+                let _ = thread.join();
+            }
+        }
+    }
+}
@@ -0,0 +1,1245 @@
+use std::{
+    any::{type_name, Any, TypeId},
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, Weak},
+    time::{Duration, Instant, SystemTime},
+};
+
+use nova_core::{systems::Runnable, SystemBuilder};
+
+use crate::{
+    io::{AssetIo, FileAssetIo},
+    loader::{split_label, AssetError, AssetLoader, ErasedLoader, LabeledAsset, LoadContext},
+    meta::{is_meta, meta_path, scan_uuids, AssetMeta},
+    pool::TaskPool,
+    Assets, Handle, HandleId, InnerHandle, Uuid,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LoadState {
+    NotLoaded,
+    Loading,
+    Loaded,
+    Failed(String),
+}
+
+/// Progress of the loads started since the server was last idle, for loading screens.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LoadProgress {
+    pub total: usize,
+    pub loaded: usize,
+    pub failed: usize,
+}
+
+impl LoadProgress {
+    #[inline]
+    pub fn finished(&self) -> usize {
+        self.loaded + self.failed
+    }
+
+    #[inline]
+    pub fn is_done(&self) -> bool {
+        self.finished() >= self.total
+    }
+
+    /// Fraction of loads finished, 1.0 if nothing is loading.
+    #[inline]
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.finished() as f32 / self.total as f32
+        }
+    }
+}
+
+pub(crate) type Key = (TypeId, InnerHandle);
+pub(crate) type Marker = Weak<()>;
+
+/// How often [`AssetServer::watch_for_changes`] checks files by default.
+pub const DEFAULT_WATCH_INTERVAL: Duration = Duration::from_millis(500);
+/// How often looking up the path of an unknown uuid may rescan the io, loads always
+/// rescan.
+const UUID_RESCAN_INTERVAL: Duration = Duration::from_secs(1);
+
+struct FinishedLoad {
+    key: Key,
+    marker: Marker,
+    result: Result<Box<dyn Any + Send + Sync>, AssetError>,
+    dependencies: Vec<Key>,
+    /// Files the loader read besides the asset's own.
+    files: Vec<PathBuf>,
+    modified: Option<SystemTime>,
+    reload: bool,
+}
+
+/// Load requested by a loader, started on the main thread where the loaders are.
+struct RequestedLoad {
+    key: Key,
+    marker: Marker,
+    type_name: &'static str,
+}
+
+#[derive(Default)]
+pub(crate) struct ServerState {
+    handles: HashMap<Key, Marker>,
+    states: HashMap<Key, LoadState>,
+    finished: HashMap<TypeId, Vec<FinishedLoad>>,
+    requested: Vec<RequestedLoad>,
+    dependencies: HashMap<Key, Vec<Key>>,
+    /// Files read by the loaders of assets besides their own.
+    files: HashMap<Key, Vec<PathBuf>>,
+    /// Paths of assets by the uuid in their meta.
+    uuids: HashMap<Uuid, PathBuf>,
+    last_uuid_scan: Option<Instant>,
+    /// Modification times of loaded files, for hot reloading.
+    modified: HashMap<Key, SystemTime>,
+    last_check: Option<Instant>,
+    progress: LoadProgress,
+    /// Files of labeled assets loaded by their path, kept alive with them.
+    parents: HashMap<Key, Arc<()>>,
+    /// Labeled assets waiting for their file to load, by the key of the file.
+    pending_labels: HashMap<Key, Vec<Key>>,
+}
+
+#[inline]
+fn format_cycle(cycle: &[Key]) -> String {
+    cycle
+        .iter()
+        .map(|(_, handle)| handle.to_string())
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
+
+impl ServerState {
+    /// Key the asset of `handle` is stored at, uuid handles loaded by
+    /// [`AssetServer::load_uuid`] are stored at the path of their file.
+    #[inline]
+    fn key<T: 'static>(&self, handle: &Handle<T>) -> Key {
+        if let InnerHandle::Uuid(uuid) = handle.inner() {
+            let path = self.uuids.get(uuid);
+
+            if let Some(path) = path.filter(|path| HandleId::path(path) == handle.id()) {
+                return (TypeId::of::<T>(), InnerHandle::Path(path.clone()));
+            }
+        }
+
+        (TypeId::of::<T>(), handle.inner().clone())
+    }
+
+    /// Returns the reference count of `key`, and a weak reference to it if it's new and
+    /// has to be loaded.
+    #[inline]
+    fn handle(&mut self, key: &Key) -> (Arc<()>, Option<Marker>) {
+        if let Some(refs) = self.handles.get(key).and_then(Weak::upgrade) {
+            return (refs, None);
+        }
+
+        let refs = Arc::new(());
+        let weak = Arc::downgrade(&refs);
+
+        if self.progress.is_done() {
+            self.progress = LoadProgress::default();
+        }
+
+        self.progress.total += 1;
+        self.handles.insert(key.clone(), weak.clone());
+
+        (refs, Some(weak))
+    }
+
+    /// Returns the reference count of the labeled asset at `key`, labeled assets are
+    /// delivered with their file so they don't count towards the progress.
+    #[inline]
+    pub(crate) fn labeled_handle(&mut self, key: &Key) -> (Arc<()>, Marker) {
+        if let Some(marker) = self.handles.get(key) {
+            if let Some(refs) = marker.upgrade() {
+                return (refs, marker.clone());
+            }
+        }
+
+        let refs = Arc::new(());
+        let marker = Arc::downgrade(&refs);
+        self.handles.insert(key.clone(), marker.clone());
+
+        (refs, marker)
+    }
+
+    /// Queues a finished load with the labeled assets the loader added, and finishes the
+    /// labeled assets that were requested before the file was loaded.
+    #[inline]
+    fn finish(&mut self, load: FinishedLoad, labeled: Vec<LabeledAsset>) {
+        for key in self.pending_labels.remove(&load.key).unwrap_or_default() {
+            if load.result.is_ok() && labeled.iter().any(|asset| asset.key == key) {
+                self.progress.loaded += 1;
+                continue;
+            }
+
+            let err = match &load.result {
+                Ok(_) => format!("no such asset in '{}'", load.key.1),
+                Err(err) => format!("'{}': {}", load.key.1, err),
+            };
+
+            log::error!("failed to load '{}': {}", key.1, err);
+
+            self.progress.failed += 1;
+            self.states.insert(key, LoadState::Failed(err));
+        }
+
+        if load.result.is_ok() {
+            for asset in labeled {
+                self.finished
+                    .entry(asset.key.0)
+                    .or_default()
+                    .push(FinishedLoad {
+                        key: asset.key,
+                        marker: asset.marker,
+                        result: Ok(asset.asset),
+                        dependencies: Vec::new(),
+                        files: Vec::new(),
+                        // the file is watched instead
+                        modified: None,
+                        // counted in the progress with the file
+                        reload: true,
+                    });
+            }
+        }
+
+        self.finished.entry(load.key.0).or_default().push(load);
+    }
+
+    /// Like [`AssetServer::load_handle`] but the load is started on the main thread, for
+    /// loaders. Returns the key the asset is stored at with the handle.
+    #[inline]
+    pub(crate) fn request<T: Send + Sync + 'static>(
+        &mut self,
+        inner: InnerHandle,
+        io: &dyn AssetIo,
+    ) -> (Key, Handle<T>) {
+        // uuids are stored at the path of their file, like in `AssetServer::load_inner`
+        let path = match &inner {
+            InnerHandle::Uuid(uuid) => {
+                if !self.uuids.get(uuid).map_or(false, |path| io.exists(path)) {
+                    self.uuids = scan_uuids(io);
+                    self.last_uuid_scan = Some(Instant::now());
+                }
+
+                self.uuids.get(uuid).cloned()
+            }
+            _ => None,
+        };
+
+        let key = (
+            TypeId::of::<T>(),
+            path.map_or_else(|| inner.clone(), InnerHandle::Path),
+        );
+        let (refs, new) = self.handle(&key);
+
+        if let Some(marker) = new {
+            self.states.insert(key.clone(), LoadState::Loading);
+            self.requested.push(RequestedLoad {
+                key: key.clone(),
+                marker,
+                type_name: type_name::<T>(),
+            });
+        }
+
+        let id = HandleId::from(&key.1);
+
+        (key, Handle::tracked_at(inner, id, refs))
+    }
+
+    /// Returns the keys of a dependency cycle going through `start`.
+    #[inline]
+    fn find_cycle(&self, start: &Key) -> Option<Vec<Key>> {
+        let mut stack = vec![(start.clone(), 0)];
+        let mut visited = HashSet::new();
+
+        while let Some((key, index)) = stack.last().cloned() {
+            let dependencies = self.dependencies.get(&key).map_or(&[][..], |deps| deps);
+
+            match dependencies.get(index) {
+                Some(dependency) => {
+                    stack.last_mut().unwrap().1 += 1;
+
+                    if dependency == start {
+                        let mut cycle: Vec<_> = stack.into_iter().map(|(key, _)| key).collect();
+                        cycle.push(start.clone());
+
+                        return Some(cycle);
+                    }
+
+                    if visited.insert(dependency.clone()) {
+                        stack.push((dependency.clone(), 0));
+                    }
+                }
+                None => {
+                    stack.pop();
+                }
+            }
+        }
+
+        None
+    }
+
+    /// State of `key` and everything it depends on, `path` is the chain of dependencies
+    /// leading to `key`.
+    fn recursive_load_state(
+        &self,
+        key: &Key,
+        path: &mut Vec<Key>,
+        visited: &mut HashSet<Key>,
+    ) -> LoadState {
+        if let Some(i) = path.iter().position(|k| k == key) {
+            let mut cycle = path[i..].to_vec();
+            cycle.push(key.clone());
+
+            return LoadState::Failed(format!("dependency cycle: {}", format_cycle(&cycle)));
+        }
+
+        if !visited.insert(key.clone()) {
+            return LoadState::Loaded;
+        }
+
+        let state = if self.is_alive(key) {
+            self.states
+                .get(key)
+                .cloned()
+                .unwrap_or(LoadState::NotLoaded)
+        } else {
+            LoadState::NotLoaded
+        };
+
+        match state {
+            LoadState::Loaded => {}
+            LoadState::Failed(err) if !path.is_empty() => {
+                return LoadState::Failed(format!("'{}': {}", key.1, err));
+            }
+            state => return state,
+        }
+
+        let mut state = LoadState::Loaded;
+        path.push(key.clone());
+
+        for dependency in self.dependencies.get(key).into_iter().flatten() {
+            match self.recursive_load_state(dependency, path, visited) {
+                LoadState::Loaded => {}
+                LoadState::Failed(err) => {
+                    state = LoadState::Failed(err);
+                    break;
+                }
+                _ => state = LoadState::Loading,
+            }
+        }
+
+        path.pop();
+        state
+    }
+
+    /// Forgets a load whose handles were all dropped before it was delivered.
+    #[inline]
+    fn cancel(&mut self, key: &Key, reload: bool) {
+        self.states.remove(key);
+        self.modified.remove(key);
+
+        if !reload {
+            self.progress.total -= 1;
+        }
+    }
+
+    #[inline]
+    fn is_alive(&self, key: &Key) -> bool {
+        self.handles
+            .get(key)
+            .map_or(false, |marker| marker.strong_count() > 0)
+    }
+}
+
+/// Loads assets from its [`AssetIo`] with the [`AssetLoader`] registered for the file
+/// extension.
+///
+/// Loads run on a [`TaskPool`] and are delivered to [`Assets<T>`] in [`LOAD_ASSETS_STAGE`],
+/// a load is cancelled if every handle to it is dropped before that.
+///
+/// By default files are read from disk with a [`FileAssetIo`], so paths are relative to
+/// the first root that contains them, or the working directory if there are no roots.
+///
+/// [`LOAD_ASSETS_STAGE`]: crate::LOAD_ASSETS_STAGE
+pub struct AssetServer {
+    io: Arc<dyn AssetIo>,
+    loaders: Vec<Arc<dyn ErasedLoader>>,
+    state: Arc<Mutex<ServerState>>,
+    pool: TaskPool,
+    watch: Option<Duration>,
+}
+
+/// Last modification of the file at `path` or its meta, so changing import settings
+/// reloads the asset.
+#[inline]
+fn modified(io: &dyn AssetIo, path: &Path) -> Option<SystemTime> {
+    let modified = io.modified(path)?;
+
+    Some(
+        io.modified(&meta_path(path))
+            .map_or(modified, |meta| meta.max(modified)),
+    )
+}
+
+type LoadResult = (
+    Result<Box<dyn Any + Send + Sync>, AssetError>,
+    Vec<Key>,
+    Vec<PathBuf>,
+    Vec<LabeledAsset>,
+);
+
+/// Loads the file at `path`, also returns the dependencies the loader requested, the other
+/// files it read and the labeled assets it added.
+#[inline]
+fn load_file(
+    loader: &dyn ErasedLoader,
+    io: &dyn AssetIo,
+    path: &Path,
+    state: &Mutex<ServerState>,
+) -> LoadResult {
+    let settings = match AssetMeta::read_io(io, path) {
+        Ok(meta) => meta.map(|meta| meta.settings).unwrap_or_default(),
+        Err(err) => {
+            let err = format!("invalid meta: {}", err).into();
+            return (Err(err), Vec::new(), Vec::new(), Vec::new());
+        }
+    };
+
+    let bytes = match io.read(path) {
+        Ok(bytes) => bytes,
+        Err(err) => return (Err(err.into()), Vec::new(), Vec::new(), Vec::new()),
+    };
+
+    let mut ctx = LoadContext::new(path.to_path_buf(), settings, io, state);
+    let result = loader.load(&bytes, &mut ctx);
+    let (dependencies, files, labeled) = ctx.into_parts();
+
+    (result, dependencies, files, labeled)
+}
+
+impl Default for AssetServer {
+    #[inline]
+    fn default() -> Self {
+        Self::with_io(FileAssetIo::default())
+    }
+}
+
+impl AssetServer {
+    /// Creates a server reading loose files from `roots`.
+    #[inline]
+    pub fn new(roots: Vec<PathBuf>) -> Self {
+        Self::with_io(FileAssetIo::new(roots))
+    }
+
+    #[inline]
+    pub fn with_io(io: impl AssetIo) -> Self {
+        Self {
+            io: Arc::new(io),
+            loaders: Vec::new(),
+            state: Default::default(),
+            pool: Default::default(),
+            watch: None,
+        }
+    }
+
+    #[inline]
+    pub fn io(&self) -> &dyn AssetIo {
+        &*self.io
+    }
+
+    /// Pool the loads run on, for tools decoding files in the background.
+    #[inline]
+    pub fn pool(&self) -> &TaskPool {
+        &self.pool
+    }
+
+    /// Replaces the io, already loaded assets are kept.
+    #[inline]
+    pub fn set_io(&mut self, io: impl AssetIo) {
+        self.io = Arc::new(io);
+        self.state.lock().unwrap().last_uuid_scan = None;
+    }
+
+    /// Reads loose files from `roots`.
+    #[inline]
+    pub fn set_roots(&mut self, roots: Vec<PathBuf>) {
+        self.set_io(FileAssetIo::new(roots));
+    }
+
+    /// Reloads assets when their files are modified, files are checked every `interval`.
+    #[inline]
+    pub fn watch_for_changes(&mut self, interval: Duration) {
+        self.watch = Some(interval);
+    }
+
+    /// Adds `loader`, loaders added later take priority for the same extension and asset type.
+    #[inline]
+    pub fn add_loader<L: AssetLoader>(&mut self, loader: L) {
+        self.loaders.push(Arc::new(loader));
+    }
+
+    /// Path of the file at `path` on disk, `path` itself if the io doesn't read from disk.
+    #[inline]
+    pub fn resolve(&self, path: &Path) -> PathBuf {
+        self.io
+            .local_path(path)
+            .unwrap_or_else(|| path.to_path_buf())
+    }
+
+    /// Reads the meta of the asset at `path`, `None` if it has none.
+    #[inline]
+    pub fn read_meta(&self, path: &Path) -> Result<Option<AssetMeta>, AssetError> {
+        AssetMeta::read_io(&*self.io, path)
+    }
+
+    /// Every asset file in the io, without meta files.
+    #[inline]
+    pub fn asset_paths(&self) -> Vec<PathBuf> {
+        self.io
+            .files()
+            .into_iter()
+            .filter(|path| !is_meta(path))
+            .collect()
+    }
+
+    #[inline]
+    fn get_loader(&self, asset_type: TypeId, path: &Path) -> Option<&Arc<dyn ErasedLoader>> {
+        let ext = path.extension()?.to_str()?;
+
+        self.loaders.iter().rev().find(|loader| {
+            loader.asset_type() == asset_type && loader.extensions().contains(&ext)
+        })
+    }
+
+    /// Loader for the file at `path` whatever asset it loads, for labeled assets.
+    #[inline]
+    fn get_file_loader(&self, path: &Path) -> Option<&Arc<dyn ErasedLoader>> {
+        let ext = path.extension()?.to_str()?;
+
+        self.loaders
+            .iter()
+            .rev()
+            .find(|loader| loader.extensions().contains(&ext))
+    }
+
+    #[inline]
+    pub fn progress(&self) -> LoadProgress {
+        self.state.lock().unwrap().progress
+    }
+
+    /// Starts loading the asset at `path`, the returned handle is filled in [`Assets<T>`]
+    /// once the load finishes. Loading a path that's already loaded returns the same asset.
+    ///
+    /// Paths made by [`label_path`](crate::loader::label_path) load the file the labeled
+    /// asset is in.
+    #[inline]
+    pub fn load<T: Send + Sync + 'static>(&self, path: impl AsRef<Path>) -> Handle<T> {
+        self.load_inner(InnerHandle::Path(path.as_ref().to_path_buf()))
+    }
+
+    /// Starts loading the asset with `uuid` in its meta, like [`AssetServer::load`].
+    ///
+    /// The asset is stored at the path the uuid points to when it's loaded, so it's the
+    /// same asset as the one loaded by path, while the handle still serializes as the uuid.
+    #[inline]
+    pub fn load_uuid<T: Send + Sync + 'static>(&self, uuid: Uuid) -> Handle<T> {
+        self.load_inner(InnerHandle::Uuid(uuid))
+    }
+
+    /// Loads the asset `handle` refers to, e.g. a handle deserialized from a scene. Id
+    /// handles aren't loaded from disk and are returned as is.
+    #[inline]
+    pub fn load_handle<T: 'static>(&self, handle: &Handle<T>) -> Handle<T> {
+        match handle.inner() {
+            InnerHandle::Id(_) => handle.clone(),
+            inner => self.load_inner(inner.clone()),
+        }
+    }
+
+    #[inline]
+    fn load_inner<T: 'static>(&self, inner: InnerHandle) -> Handle<T> {
+        let mut state = self.state.lock().unwrap();
+
+        // uuids are stored at the path of their file, so a file is only loaded once
+        let key = match &inner {
+            InnerHandle::Uuid(_) => match self.find_path(&mut state, &inner, true) {
+                Some(path) => (TypeId::of::<T>(), InnerHandle::Path(path)),
+                None => (TypeId::of::<T>(), inner.clone()),
+            },
+            _ => (TypeId::of::<T>(), inner.clone()),
+        };
+
+        let (refs, new) = state.handle(&key);
+
+        if let Some(marker) = new {
+            self.start_load(&mut state, key.clone(), marker, type_name::<T>());
+        }
+
+        Handle::tracked_at(inner, HandleId::from(&key.1), refs)
+    }
+
+    /// Rescans the roots for meta files, done automatically when a uuid isn't found.
+    #[inline]
+    pub fn scan_uuids(&self) {
+        self.scan_uuids_into(&mut self.state.lock().unwrap());
+    }
+
+    #[inline]
+    fn scan_uuids_into(&self, state: &mut ServerState) {
+        state.uuids = scan_uuids(&*self.io);
+        state.last_uuid_scan = Some(Instant::now());
+    }
+
+    /// Path of the asset `handle` refers to, relative to the roots.
+    #[inline]
+    pub fn asset_path(&self, handle: &InnerHandle) -> Option<PathBuf> {
+        let mut state = self.state.lock().unwrap();
+
+        // called every frame by the inspector, so unknown uuids don't rescan every time
+        let rescan = state
+            .last_uuid_scan
+            .map_or(true, |last| last.elapsed() >= UUID_RESCAN_INTERVAL);
+
+        self.find_path(&mut state, handle, rescan)
+    }
+
+    /// Whether a loader for `T` can load the file at `path`.
+    #[inline]
+    pub fn can_load<T: 'static>(&self, path: &Path) -> bool {
+        self.get_loader(TypeId::of::<T>(), path).is_some()
+    }
+
+    #[inline]
+    fn find_path(
+        &self,
+        state: &mut ServerState,
+        handle: &InnerHandle,
+        rescan: bool,
+    ) -> Option<PathBuf> {
+        match handle {
+            InnerHandle::Id(_) => None,
+            InnerHandle::Path(path) => Some(path.clone()),
+            InnerHandle::Uuid(uuid) => {
+                // the file is missing or was moved since the last scan
+                let found = state
+                    .uuids
+                    .get(uuid)
+                    .map_or(false, |path| self.io.exists(path));
+
+                if !found && rescan {
+                    self.scan_uuids_into(state);
+                }
+
+                state.uuids.get(uuid).cloned()
+            }
+        }
+    }
+
+    #[inline]
+    fn start_load(
+        &self,
+        state: &mut ServerState,
+        key: Key,
+        marker: Marker,
+        type_name: &'static str,
+    ) {
+        if let InnerHandle::Path(path) = &key.1 {
+            if let Some((file, _)) = split_label(path) {
+                self.start_labeled_load(state, key, file, type_name);
+                return;
+            }
+        }
+
+        let path = self.find_path(state, &key.1, true);
+        let loader = path.as_ref().and_then(|path| self.get_loader(key.0, path));
+
+        match (path, loader) {
+            (Some(path), Some(loader)) => {
+                state.states.insert(key.clone(), LoadState::Loading);
+
+                self.spawn_load(loader.clone(), key, path, marker, false);
+            }
+            (path, _) => {
+                let err = match path {
+                    Some(path) => format!("no loader for '{}' as {}", path.display(), type_name),
+                    None => String::from("no asset found"),
+                };
+
+                log::error!("failed to load '{}': {}", key.1, err);
+
+                state.progress.failed += 1;
+                state.states.insert(key, LoadState::Failed(err));
+            }
+        }
+    }
+
+    /// Loads the file of the labeled asset at `key`, the asset is delivered with it.
+    #[inline]
+    fn start_labeled_load(
+        &self,
+        state: &mut ServerState,
+        key: Key,
+        file: PathBuf,
+        type_name: &'static str,
+    ) {
+        let loader = match self.get_file_loader(&file) {
+            Some(loader) => loader.clone(),
+            None => {
+                let err = format!("no loader for '{}'", file.display());
+                log::error!("failed to load '{}': {}", key.1, err);
+
+                state.progress.failed += 1;
+                state.states.insert(key, LoadState::Failed(err));
+                return;
+            }
+        };
+
+        let file_key = (loader.asset_type(), InnerHandle::Path(file.clone()));
+        let (refs, new) = state.handle(&file_key);
+
+        state.states.insert(key.clone(), LoadState::Loading);
+        state.parents.insert(key.clone(), refs);
+        state
+            .pending_labels
+            .entry(file_key.clone())
+            .or_default()
+            .push(key);
+
+        match new {
+            Some(marker) => self.start_load(state, file_key, marker, type_name),
+            // the file was loaded without the asset being kept, so it's loaded again
+            None if state.states.get(&file_key) != Some(&LoadState::Loading) => {
+                let marker = state.handles[&file_key].clone();
+                self.spawn_load(loader, file_key, file, marker, true);
+            }
+            None => {}
+        }
+    }
+
+    /// Drops the files of labeled assets that were dropped.
+    #[inline]
+    pub fn release_parents(&self) {
+        let mut state = self.state.lock().unwrap();
+
+        let state = &mut *state;
+        let handles = &state.handles;
+        state.parents.retain(|key, _| {
+            handles
+                .get(key)
+                .map_or(false, |marker| marker.strong_count() > 0)
+        });
+    }
+
+    /// Starts the loads requested by loaders.
+    #[inline]
+    pub fn start_requested(&self) {
+        let mut state = self.state.lock().unwrap();
+
+        for request in std::mem::take(&mut state.requested) {
+            self.start_load(&mut state, request.key, request.marker, request.type_name);
+        }
+    }
+
+    #[inline]
+    fn spawn_load(
+        &self,
+        loader: Arc<dyn ErasedLoader>,
+        key: Key,
+        path: PathBuf,
+        marker: Marker,
+        reload: bool,
+    ) {
+        let io = self.io.clone();
+        let shared = self.state.clone();
+
+        self.pool.spawn(move || {
+            if marker.strong_count() == 0 {
+                shared.lock().unwrap().cancel(&key, reload);
+                return;
+            }
+
+            let modified = modified(&*io, &path);
+            let (result, dependencies, files, labeled) = load_file(&*loader, &*io, &path, &shared);
+
+            let load = FinishedLoad {
+                key,
+                marker,
+                result,
+                dependencies,
+                files,
+                modified,
+                reload,
+            };
+
+            shared.lock().unwrap().finish(load, labeled);
+        });
+    }
+
+    /// Starts reloading assets whose files were modified, if watching for changes.
+    #[inline]
+    pub fn check_for_changes(&self) {
+        let interval = match self.watch {
+            Some(interval) => interval,
+            None => return,
+        };
+
+        let mut state = self.state.lock().unwrap();
+
+        if state
+            .last_check
+            .map_or(false, |last| last.elapsed() < interval)
+        {
+            return;
+        }
+
+        state.last_check = Some(Instant::now());
+
+        let state = &mut *state;
+        let handles = &state.handles;
+        state.modified.retain(|key, _| {
+            handles
+                .get(key)
+                .map_or(false, |marker| marker.strong_count() > 0)
+        });
+
+        let mut changed = Vec::new();
+
+        for (key, time) in &mut state.modified {
+            let path = match &key.1 {
+                InnerHandle::Path(path) => path,
+                InnerHandle::Uuid(uuid) => match state.uuids.get(uuid) {
+                    Some(path) => path,
+                    None => continue,
+                },
+                InnerHandle::Id(_) => continue,
+            };
+
+            if let Some(modified) = modified(&*self.io, path) {
+                if modified != *time {
+                    *time = modified;
+                    changed.push((key.clone(), path.clone()));
+                }
+            }
+        }
+
+        for (key, path) in changed {
+            let loader = match self.get_loader(key.0, &path) {
+                Some(loader) => loader.clone(),
+                None => continue,
+            };
+
+            log::info!("reloading '{}'", path.display());
+
+            let marker = state.handles[&key].clone();
+            self.spawn_load(loader, key, path, marker, true);
+        }
+    }
+
+    #[inline]
+    pub fn load_state<T: 'static>(&self, handle: &Handle<T>) -> LoadState {
+        let state = self.state.lock().unwrap();
+        let key = state.key(handle);
+
+        // the asset was dropped
+        if !state.is_alive(&key) {
+            return LoadState::NotLoaded;
+        }
+
+        state
+            .states
+            .get(&key)
+            .cloned()
+            .unwrap_or(LoadState::NotLoaded)
+    }
+
+    /// State of the asset at `handle` combined with the states of everything it depends on,
+    /// it's only [`LoadState::Loaded`] once all of them are.
+    #[inline]
+    pub fn recursive_load_state<T: 'static>(&self, handle: &Handle<T>) -> LoadState {
+        let state = self.state.lock().unwrap();
+        let key = state.key(handle);
+
+        state.recursive_load_state(&key, &mut Vec::new(), &mut HashSet::new())
+    }
+
+    /// Whether no loads are running or waiting to be delivered.
+    #[inline]
+    pub fn is_idle(&self) -> bool {
+        let state = self.state.lock().unwrap();
+
+        state.progress.is_done()
+            && state.requested.is_empty()
+            && state.finished.values().all(Vec::is_empty)
+    }
+
+    /// Files the assets loaded from the file at `path` were made from, relative to the
+    /// roots: the file itself, the files their loaders read and the files of everything
+    /// they depend on. Only complete once the assets and their dependencies are loaded.
+    ///
+    /// Used to find every file a build needs, not only the ones handles point to.
+    #[inline]
+    pub fn dependency_files(&self, path: &Path) -> Vec<PathBuf> {
+        let state = self.state.lock().unwrap();
+
+        let file_of = |key: &Key| -> Option<PathBuf> {
+            let path = match &key.1 {
+                InnerHandle::Path(path) => path.clone(),
+                InnerHandle::Uuid(uuid) => state.uuids.get(uuid)?.clone(),
+                InnerHandle::Id(_) => return None,
+            };
+
+            Some(split_label(&path).map_or(path.clone(), |(file, _)| file))
+        };
+
+        let mut stack = vec![path.to_path_buf()];
+        let mut visited = stack.iter().cloned().collect::<HashSet<_>>();
+        let mut files = Vec::new();
+
+        while let Some(file) = stack.pop() {
+            // every asset loaded from the file, labeled ones included
+            let keys = state
+                .states
+                .keys()
+                .filter(|key| file_of(key).as_ref() == Some(&file));
+
+            for key in keys {
+                files.extend(state.files.get(key).into_iter().flatten().cloned());
+
+                for dependency in state.dependencies.get(key).into_iter().flatten() {
+                    if let Some(dependency) = file_of(dependency) {
+                        if visited.insert(dependency.clone()) {
+                            stack.push(dependency);
+                        }
+                    }
+                }
+            }
+
+            files.push(file);
+        }
+
+        files.sort();
+        files.dedup();
+        files
+    }
+
+    /// Inserts finished loads of `T` into `assets`.
+    #[inline]
+    pub fn update<T: Send + Sync + 'static>(&self, assets: &mut Assets<T>) {
+        let mut state = self.state.lock().unwrap();
+
+        let finished = match state.finished.remove(&TypeId::of::<T>()) {
+            Some(finished) => finished,
+            None => return,
+        };
+
+        for load in finished {
+            let key = load.key;
+
+            let refs = match load.marker.upgrade() {
+                Some(refs) => refs,
+                None => {
+                    state.cancel(&key, load.reload);
+                    continue;
+                }
+            };
+
+            if let Some(modified) = load.modified {
+                state.modified.insert(key.clone(), modified);
+            }
+
+            let handle = Handle::tracked(key.1.clone(), refs);
+
+            let load_state = match load.result {
+                Ok(asset) => {
+                    let asset = *asset.downcast::<T>().unwrap();
+
+                    state.dependencies.insert(key.clone(), load.dependencies);
+                    state.files.insert(key.clone(), load.files);
+
+                    if let Some(cycle) = state.find_cycle(&key) {
+                        log::error!("dependency cycle: {}", format_cycle(&cycle));
+                    }
+
+                    if !load.reload {
+                        state.progress.loaded += 1;
+                    }
+
+                    // reloads replace the asset in place so existing handles see the change
+                    if assets.contains(&handle) {
+                        assets.replace(&handle, asset);
+                    } else {
+                        assets.insert(&handle, asset);
+                    }
+
+                    LoadState::Loaded
+                }
+                Err(err) => {
+                    log::error!("failed to load '{}': {}", key.1, err);
+
+                    if !load.reload {
+                        state.progress.failed += 1;
+                    }
+
+                    // a failed reload keeps the last working asset
+                    if assets.contains(&handle) {
+                        continue;
+                    }
+
+                    LoadState::Failed(err.to_string())
+                }
+            };
+
+            state.states.insert(key, load_state);
+        }
+    }
+}
+
+pub fn asset_server_system<T: Send + Sync + 'static>() -> impl Runnable {
+    SystemBuilder::new(format!("asset_server_system<{}>", type_name::<T>()))
+        .read_resource::<AssetServer>()
+        .write_resource::<Assets<T>>()
+        .build(|_commands, _world, (server, assets), _queries| {
+            assets.update_events();
+            server.update(assets);
+        })
+}
+
+pub fn update_server_system() -> impl Runnable {
+    SystemBuilder::new("update_server_system")
+        .read_resource::<AssetServer>()
+        .build(|_commands, _world, server, _queries| {
+            server.start_requested();
+            server.release_parents();
+            server.check_for_changes();
+        })
+}
+
+/// [`AssetFilesFn`](nova_core::app::AssetFilesFn) of apps with an [`AssetServer`], see
+/// [`AssetServer::dependency_files`].
+#[cfg(feature = "editor")]
+pub fn asset_files(resources: &nova_core::Resources, path: &Path) -> Option<Vec<PathBuf>> {
+    let server = match resources.get::<AssetServer>() {
+        Some(server) => server,
+        None => return Some(vec![path.to_path_buf()]),
+    };
+
+    if !server.is_idle() {
+        return None;
+    }
+
+    Some(server.dependency_files(path))
+}
+
+pub fn load_progress_system() -> impl Runnable {
+    SystemBuilder::new("load_progress_system")
+        .read_resource::<AssetServer>()
+        .write_resource::<LoadProgress>()
+        .build(|_commands, _world, (server, progress), _queries| **progress = server.progress())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, io};
+
+    use nova_core::{scene, Resources};
+
+    use super::*;
+
+    /// Files in memory, none of them change.
+    struct MemoryIo(BTreeMap<PathBuf, Vec<u8>>);
+
+    impl AssetIo for MemoryIo {
+        fn read(&self, path: &Path) -> Result<Vec<u8>, io::Error> {
+            self.0
+                .get(path)
+                .cloned()
+                .ok_or_else(|| io::ErrorKind::NotFound.into())
+        }
+
+        fn exists(&self, path: &Path) -> bool {
+            self.0.contains_key(path)
+        }
+
+        fn modified(&self, _path: &Path) -> Option<SystemTime> {
+            None
+        }
+
+        fn files(&self) -> Vec<PathBuf> {
+            self.0.keys().cloned().collect()
+        }
+    }
+
+    struct TextLoader;
+
+    impl AssetLoader for TextLoader {
+        type Asset = String;
+
+        fn extensions(&self) -> &[&str] {
+            &["txt"]
+        }
+
+        fn load(&self, bytes: &[u8], _ctx: &mut LoadContext<'_>) -> Result<String, AssetError> {
+            Ok(String::from_utf8(bytes.to_vec())?)
+        }
+    }
+
+    /// Server with `level.txt` whose meta has `uuid`.
+    fn server(uuid: Uuid) -> AssetServer {
+        let meta = AssetMeta {
+            uuid,
+            settings: Default::default(),
+        };
+
+        let mut files = BTreeMap::new();
+        files.insert(PathBuf::from("level.txt"), b"hello".to_vec());
+        files.insert(
+            meta_path(Path::new("level.txt")),
+            ron::ser::to_string(&meta).unwrap().into_bytes(),
+        );
+
+        let mut server = AssetServer::with_io(MemoryIo(files));
+        server.add_loader(TextLoader);
+        server
+    }
+
+    fn wait_for(server: &AssetServer, assets: &mut Assets<String>, handle: &Handle<String>) {
+        let start = Instant::now();
+
+        while server.load_state(handle) == LoadState::Loading {
+            assert!(start.elapsed() < Duration::from_secs(5), "load timed out");
+
+            std::thread::sleep(Duration::from_millis(1));
+            server.update(assets);
+        }
+    }
+
+    #[test]
+    fn uuid_loads_the_path_asset() {
+        let uuid = Uuid::new_v4();
+        let server = server(uuid);
+        let mut assets = Assets::new();
+
+        let by_path = server.load::<String>("level.txt");
+        let by_uuid = server.load_uuid::<String>(uuid);
+
+        assert_eq!(by_path, by_uuid);
+        assert_eq!(by_uuid.inner(), &InnerHandle::Uuid(uuid));
+        assert_eq!(server.progress().total, 1);
+
+        wait_for(&server, &mut assets, &by_uuid);
+
+        assert_eq!(server.load_state(&by_uuid), LoadState::Loaded);
+        assert_eq!(assets.get(&by_uuid).map(String::as_str), Some("hello"));
+    }
+
+    #[test]
+    fn scene_handles_are_loaded() {
+        let uuid = Uuid::new_v4();
+        let mut resources = Resources::default();
+        resources.insert(server(uuid));
+
+        let source = format!("(Uuid(\"{}\"), Path(\"level.txt\"), Id(3))", uuid);
+        let (handles, dependencies) =
+            scene::deserialize_with(&resources, || ron::de::from_str(&source).unwrap());
+        let (by_uuid, by_path, by_id): (Handle<String>, Handle<String>, Handle<String>) = handles;
+
+        assert_eq!(dependencies, [PathBuf::from("level.txt")]);
+
+        assert!(by_uuid.is_tracked());
+        assert!(by_path.is_tracked());
+        assert!(!by_id.is_tracked());
+        assert_eq!(by_uuid, by_path);
+
+        let server = resources.get::<AssetServer>().unwrap();
+        let mut assets = Assets::new();
+        wait_for(&server, &mut assets, &by_uuid);
+
+        assert_eq!(assets.get(&by_uuid).map(String::as_str), Some("hello"));
+
+        // the handle is still saved as the uuid
+        let saved = ron::ser::to_string(&by_uuid).unwrap();
+        assert_eq!(saved, format!("Uuid(\"{}\")", uuid));
+
+        // outside of scenes handles stay untracked
+        let handle: Handle<String> = ron::de::from_str(&saved).unwrap();
+        assert!(!handle.is_tracked());
+    }
+
+    /// Lines of `read <path>` and `load <path>`, reading or loading files next to the list.
+    struct List(Vec<Handle<List>>);
+
+    struct ListLoader;
+
+    impl AssetLoader for ListLoader {
+        type Asset = List;
+
+        fn extensions(&self) -> &[&str] {
+            &["list"]
+        }
+
+        fn load(&self, bytes: &[u8], ctx: &mut LoadContext<'_>) -> Result<List, AssetError> {
+            let source = String::from_utf8(bytes.to_vec())?;
+            let mut loaded = Vec::new();
+
+            for line in source.lines() {
+                match line.split_once(' ') {
+                    Some(("read", path)) => {
+                        let path = ctx.relative(path);
+                        ctx.read(path)?;
+                    }
+                    Some(("load", path)) => {
+                        let path = ctx.relative(path);
+                        loaded.push(ctx.load(path));
+                    }
+                    _ => return Err(format!("invalid line '{}'", line).into()),
+                }
+            }
+
+            Ok(List(loaded))
+        }
+    }
+
+    #[test]
+    fn dependency_files_include_read_files() {
+        let mut files = BTreeMap::new();
+        files.insert(
+            PathBuf::from("levels/a.list"),
+            b"read a.bin\nload ../shared/b.list".to_vec(),
+        );
+        files.insert(PathBuf::from("levels/a.bin"), Vec::new());
+        files.insert(PathBuf::from("shared/b.list"), b"read b.bin".to_vec());
+        files.insert(PathBuf::from("shared/b.bin"), Vec::new());
+        files.insert(PathBuf::from("unused.bin"), Vec::new());
+
+        let mut server = AssetServer::with_io(MemoryIo(files));
+        server.add_loader(ListLoader);
+
+        let mut assets = Assets::<List>::new();
+        let handle = server.load::<List>("levels/a.list");
+
+        let start = Instant::now();
+
+        while server.recursive_load_state(&handle) != LoadState::Loaded {
+            assert!(start.elapsed() < Duration::from_secs(5), "load timed out");
+
+            std::thread::sleep(Duration::from_millis(1));
+            server.start_requested();
+            server.update(&mut assets);
+        }
+
+        assert_eq!(
+            server.dependency_files(Path::new("levels/a.list")),
+            [
+                PathBuf::from("levels/a.bin"),
+                PathBuf::from("levels/a.list"),
+                PathBuf::from("shared/b.bin"),
+                PathBuf::from("shared/b.list"),
+            ]
+        );
+    }
+}
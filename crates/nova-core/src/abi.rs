@@ -0,0 +1,187 @@
+use std::{
+    any::type_name,
+    fmt::{Display, Formatter},
+    mem::{align_of, size_of},
+};
+
+use legion::{Registry, Resources, Schedule, World};
+
+use crate::{App, AppBuilder};
+
+/// Version of the [`AbiDescriptor`] layout itself, bump when changing the struct.
+pub const ABI_VERSION: u32 = 1;
+
+pub const FEATURE_EDITOR: u64 = 1 << 0;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct AbiStr {
+    ptr: *const u8,
+    len: usize,
+}
+
+impl AbiStr {
+    #[inline]
+    pub const fn new(s: &'static str) -> Self {
+        Self {
+            ptr: s.as_ptr(),
+            len: s.len(),
+        }
+    }
+
+    /// # Safety
+    /// The library the string was created in must still be loaded.
+    #[inline]
+    pub unsafe fn as_str(&self) -> &str {
+        unsafe { std::str::from_utf8_unchecked(std::slice::from_raw_parts(self.ptr, self.len)) }
+    }
+}
+
+/// Describes what a game lib was compiled against, exported by `export_app!` and checked
+/// before anything else is called in the lib.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct AbiDescriptor {
+    pub abi_version: u32,
+    pub nova_version: AbiStr,
+    pub rustc_version: AbiStr,
+    pub features: u64,
+    pub layout_hash: u64,
+}
+
+// fnv-1a, std's hasher isn't guaranteed to be stable between builds
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+#[inline]
+fn hash_bytes(mut hash: u64, bytes: &[u8]) -> u64 {
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+
+    hash
+}
+
+impl AbiDescriptor {
+    #[inline]
+    pub fn current() -> Self {
+        let mut features = 0;
+
+        if cfg!(feature = "editor") {
+            features |= FEATURE_EDITOR;
+        }
+
+        Self {
+            abi_version: ABI_VERSION,
+            nova_version: AbiStr::new(env!("CARGO_PKG_VERSION")),
+            rustc_version: AbiStr::new(env!("NOVA_RUSTC_VERSION")),
+            features,
+            layout_hash: FNV_OFFSET,
+        }
+        .with_layout::<App>()
+        .with_layout::<AppBuilder>()
+        .with_layout::<World>()
+        .with_layout::<Resources>()
+        .with_layout::<Registry<String>>()
+        .with_layout::<Schedule>()
+    }
+
+    /// Includes the name, size and alignment of `T` in the layout hash.
+    #[inline]
+    pub fn with_layout<T>(mut self) -> Self {
+        let mut hash = hash_bytes(self.layout_hash, type_name::<T>().as_bytes());
+        hash = hash_bytes(hash, &(size_of::<T>() as u64).to_le_bytes());
+        hash = hash_bytes(hash, &(align_of::<T>() as u64).to_le_bytes());
+
+        self.layout_hash = hash;
+        self
+    }
+
+    /// Checks that a descriptor loaded from a game lib matches `expected`.
+    ///
+    /// # Safety
+    /// The library `self` was loaded from must still be loaded.
+    #[inline]
+    pub unsafe fn check(&self, expected: &Self) -> Result<(), AbiMismatch> {
+        // the rest of the layout can't be trusted if this doesn't match
+        if self.abi_version != expected.abi_version {
+            return Err(AbiMismatch::AbiVersion {
+                found: self.abi_version,
+                expected: expected.abi_version,
+            });
+        }
+
+        let (found_rustc, expected_rustc) =
+            unsafe { (self.rustc_version.as_str(), expected.rustc_version.as_str()) };
+
+        if found_rustc != expected_rustc {
+            return Err(AbiMismatch::RustcVersion {
+                found: String::from(found_rustc),
+                expected: String::from(expected_rustc),
+            });
+        }
+
+        let (found_nova, expected_nova) =
+            unsafe { (self.nova_version.as_str(), expected.nova_version.as_str()) };
+
+        if found_nova != expected_nova {
+            return Err(AbiMismatch::NovaVersion {
+                found: String::from(found_nova),
+                expected: String::from(expected_nova),
+            });
+        }
+
+        if self.features != expected.features {
+            return Err(AbiMismatch::Features {
+                found: self.features,
+                expected: expected.features,
+            });
+        }
+
+        if self.layout_hash != expected.layout_hash {
+            return Err(AbiMismatch::Layout);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AbiMismatch {
+    AbiVersion { found: u32, expected: u32 },
+    RustcVersion { found: String, expected: String },
+    NovaVersion { found: String, expected: String },
+    Features { found: u64, expected: u64 },
+    Layout,
+}
+
+impl Display for AbiMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AbiVersion { found, expected } => write!(
+                f,
+                "game lib uses abi descriptor version {}, expected {}",
+                found, expected
+            ),
+            Self::RustcVersion { found, expected } => write!(
+                f,
+                "game lib was compiled with '{}', expected '{}'",
+                found, expected
+            ),
+            Self::NovaVersion { found, expected } => write!(
+                f,
+                "game lib was compiled against nova {}, expected nova {}",
+                found, expected
+            ),
+            Self::Features { found, expected } => write!(
+                f,
+                "game lib was compiled with nova features {:#x}, expected {:#x}",
+                found, expected
+            ),
+            Self::Layout => write!(f, "game lib type layouts don't match"),
+        }
+    }
+}
+
+impl std::error::Error for AbiMismatch {}
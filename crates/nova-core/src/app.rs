@@ -1,4 +1,4 @@
-use std::{any::type_name, collections::HashMap};
+use std::{any::type_name, collections::HashMap, path::PathBuf};
 
 use erased_serde::{Deserializer, Serializer};
 use legion::{
@@ -13,7 +13,7 @@ use serde::{
     Serialize,
 };
 
-use crate::{system::RunnableContainer, Plugin};
+use crate::{scene, system::RunnableContainer, Plugin};
 
 pub mod stage {
     pub const START: &'static str = "start";
@@ -41,21 +41,53 @@ pub struct App {
     ) -> Result<(), Box<dyn std::error::Error>>,
     pub deserialize: fn(
         &mut World,
+        &Resources,
         &Registry<String>,
         &mut dyn Deserializer,
-    ) -> Result<(), Box<dyn std::error::Error>>,
+    ) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>>,
     pub unload: fn(Self),
     #[cfg(feature = "editor")]
     pub inspect_world: fn(&World, &mut Option<legion::Entity>, &mut egui::Ui),
     #[cfg(feature = "editor")]
-    pub inspect_entity:
-        fn(&mut World, &crate::inspectables::Inspectables, &legion::Entity, &mut egui::Ui),
+    pub inspect_entity: fn(
+        &mut World,
+        &Resources,
+        &crate::inspectables::Inspectables,
+        &legion::Entity,
+        &mut egui::Ui,
+    ),
+    #[cfg(feature = "editor")]
+    pub spawn_asset: fn(
+        &mut World,
+        &mut Resources,
+        &crate::spawners::AssetSpawners,
+        &std::path::Path,
+    ) -> Option<legion::Entity>,
+    #[cfg(feature = "editor")]
+    pub preview_asset: fn(
+        &mut World,
+        &mut Resources,
+        &crate::spawners::AssetSpawners,
+        &std::path::Path,
+    ) -> Option<crate::spawners::Preview>,
+    #[cfg(feature = "editor")]
+    pub edit_asset: fn(
+        &mut World,
+        &mut Resources,
+        &crate::spawners::AssetSpawners,
+        &std::path::Path,
+        &mut egui::Ui,
+    ) -> Option<bool>,
+    #[cfg(feature = "editor")]
+    pub asset_files: AssetFilesFn,
     pub startup_schedule: Schedule,
     pub schedule: Schedule,
     #[cfg(feature = "editor")]
     pub editor_schedule: Schedule,
     #[cfg(feature = "editor")]
     pub inspectables: crate::inspectables::Inspectables,
+    #[cfg(feature = "editor")]
+    pub spawners: crate::spawners::AssetSpawners,
 }
 
 pub fn update(schedule: &mut Schedule, world: &mut World, resources: &mut Resources) {
@@ -76,18 +108,23 @@ pub fn serialize(
     Ok(())
 }
 
+/// Deserializes a scene into `world`, components can read `resources` meanwhile. Returns
+/// the files the scene depends on, see [`scene::deserialize_with`].
 pub fn deserialize(
     world: &mut World,
+    resources: &Resources,
     registry: &Registry<String>,
     deserializer: &mut dyn Deserializer,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
     let canon = Canon::default();
 
     let deserialize = registry.as_deserialize_into_world(world, &canon);
 
-    deserialize.deserialize(deserializer)?;
+    let (result, dependencies) =
+        scene::deserialize_with(resources, || deserialize.deserialize(deserializer));
+    result?;
 
-    Ok(())
+    Ok(dependencies)
 }
 
 pub fn unload(app: App) {
@@ -112,11 +149,84 @@ pub fn inspect_world(world: &World, selected: &mut Option<legion::Entity>, ui: &
 #[cfg(feature = "editor")]
 pub fn inspect_entity(
     world: &mut World,
+    resources: &Resources,
     inspectables: &crate::inspectables::Inspectables,
     entity: &legion::Entity,
     ui: &mut egui::Ui,
 ) {
-} 
+    let mut entry = match world.entry(*entity) {
+        Some(entry) => entry,
+        None => return,
+    };
+
+    let component_types: Vec<_> = entry
+        .archetype()
+        .layout()
+        .component_types()
+        .iter()
+        .map(|ty| ty.type_id())
+        .collect();
+
+    nova_inspect::context::inspect_with(resources, || {
+        for type_id in component_types {
+            if let Some((name, inspect)) = inspectables.get(&type_id) {
+                ui.collapsing(name, |ui| inspect(&mut entry, ui));
+            }
+        }
+    });
+}
+
+/// Files the assets loaded from `path` are made from, `None` while they're loading, see
+/// [`AppBuilder::asset_files`].
+#[cfg(feature = "editor")]
+pub type AssetFilesFn = fn(&Resources, &std::path::Path) -> Option<Vec<PathBuf>>;
+
+/// [`AssetFilesFn`] of apps without an asset server, assets are only made from their file.
+#[cfg(feature = "editor")]
+pub fn asset_files(_resources: &Resources, path: &std::path::Path) -> Option<Vec<PathBuf>> {
+    Some(vec![path.to_path_buf()])
+}
+
+#[cfg(feature = "editor")]
+pub fn spawn_asset(
+    world: &mut World,
+    resources: &mut Resources,
+    spawners: &crate::spawners::AssetSpawners,
+    path: &std::path::Path,
+) -> Option<legion::Entity> {
+    let spawn = spawners.get(path)?;
+
+    Some(spawn(world, resources, path))
+}
+
+/// Stages the asset spawned at `path` for its thumbnail, `None` if the game has no preview
+/// for it.
+#[cfg(feature = "editor")]
+pub fn preview_asset(
+    world: &mut World,
+    resources: &mut Resources,
+    spawners: &crate::spawners::AssetSpawners,
+    path: &std::path::Path,
+) -> Option<crate::spawners::Preview> {
+    let preview = spawners.preview(path)?;
+
+    Some(preview(world, resources))
+}
+
+/// Shows the asset at `path` in the inspector, `None` if the game has no editor for it.
+/// Returns if the asset was changed.
+#[cfg(feature = "editor")]
+pub fn edit_asset(
+    world: &mut World,
+    resources: &mut Resources,
+    spawners: &crate::spawners::AssetSpawners,
+    path: &std::path::Path,
+    ui: &mut egui::Ui,
+) -> Option<bool> {
+    let edit = spawners.editor(path)?;
+
+    Some(edit(world, resources, path, ui))
+}
 
 #[derive(Default)]
 pub struct AppBuilder {
@@ -132,6 +242,11 @@ pub struct AppBuilder {
     pub editor_stages: HashMap<&'static str, Stage>,
     #[cfg(feature = "editor")]
     pub inspectables: crate::inspectables::Inspectables,
+    #[cfg(feature = "editor")]
+    pub spawners: crate::spawners::AssetSpawners,
+    /// Set by the asset server, so exports can find the files scenes need.
+    #[cfg(feature = "editor")]
+    pub asset_files: Option<AssetFilesFn>,
 }
 
 impl AppBuilder {
@@ -150,6 +265,10 @@ impl AppBuilder {
             editor_stages: HashMap::new(),
             #[cfg(feature = "editor")]
             inspectables: Default::default(),
+            #[cfg(feature = "editor")]
+            spawners: Default::default(),
+            #[cfg(feature = "editor")]
+            asset_files: None,
         };
 
         app_builder
@@ -185,6 +304,48 @@ impl AppBuilder {
         self
     }
 
+    /// Spawns entities with `spawner` when a file with one of `extensions` is dropped into
+    /// the editor viewport.
+    #[inline]
+    #[cfg(feature = "editor")]
+    pub fn register_asset_spawner(
+        &mut self,
+        extensions: &[&str],
+        spawner: crate::spawners::SpawnFn,
+    ) -> &mut Self {
+        self.spawners.register(extensions, spawner);
+
+        self
+    }
+
+    /// Stages the entities spawned for files with one of `extensions` with `preview` to
+    /// render their thumbnails in the editor's asset browser.
+    #[inline]
+    #[cfg(feature = "editor")]
+    pub fn register_asset_preview(
+        &mut self,
+        extensions: &[&str],
+        preview: crate::spawners::PreviewFn,
+    ) -> &mut Self {
+        self.spawners.register_preview(extensions, preview);
+
+        self
+    }
+
+    /// Edits files with one of `extensions` with `edit` when they're opened from the editor's
+    /// asset browser.
+    #[inline]
+    #[cfg(feature = "editor")]
+    pub fn register_asset_editor(
+        &mut self,
+        extensions: &[&str],
+        edit: crate::spawners::EditFn,
+    ) -> &mut Self {
+        self.spawners.register_editor(extensions, edit);
+
+        self
+    }
+
     #[inline]
     pub fn insert_resource<T: Resource>(&mut self, resource: T) -> &mut Self {
         self.resources.insert(resource);
@@ -399,12 +560,22 @@ impl AppBuilder {
             inspect_world,
             #[cfg(feature = "editor")]
             inspect_entity,
+            #[cfg(feature = "editor")]
+            spawn_asset,
+            #[cfg(feature = "editor")]
+            preview_asset,
+            #[cfg(feature = "editor")]
+            edit_asset,
+            #[cfg(feature = "editor")]
+            asset_files: self.asset_files.unwrap_or(asset_files),
             startup_schedule: startup_schedule.build(),
             schedule: schedule.build(),
             #[cfg(feature = "editor")]
             editor_schedule: editor_schedule.build(),
             #[cfg(feature = "editor")]
             inspectables: self.inspectables,
+            #[cfg(feature = "editor")]
+            spawners: self.spawners,
         }
     }
 }
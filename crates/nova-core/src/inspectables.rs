@@ -4,9 +4,11 @@ use egui::{Response, Ui};
 use legion::{storage::Component, world::Entry};
 use nova_inspect::Inspectable;
 
+pub type InspectFn = fn(&mut Entry, &mut Ui) -> Option<Response>;
+
 #[derive(Default)]
 pub struct Inspectables {
-    inspectables: HashMap<TypeId, fn(&mut Entry, &mut Ui) -> Option<Response>>,
+    inspectables: HashMap<TypeId, (&'static str, InspectFn)>,
 }
 
 impl Inspectables {
@@ -16,6 +18,16 @@ impl Inspectables {
             entry.get_component_mut::<T>().ok()?.inspect(ui)
         }
 
-        self.inspectables.insert(TypeId::of::<T>(), inspect::<T>);
+        let name = std::any::type_name::<T>();
+        let name = name.rsplit("::").next().unwrap_or(name);
+
+        self.inspectables
+            .insert(TypeId::of::<T>(), (name, inspect::<T>));
+    }
+
+    /// Name and inspect function of the component with `type_id`, if registered.
+    #[inline]
+    pub fn get(&self, type_id: &TypeId) -> Option<(&'static str, InspectFn)> {
+        self.inspectables.get(type_id).copied()
     }
 }
@@ -1,7 +1,11 @@
+pub mod abi;
 pub mod app;
 #[cfg(feature = "editor")]
 pub mod inspectables;
 pub mod plugin;
+pub mod scene;
+#[cfg(feature = "editor")]
+pub mod spawners;
 pub mod system;
 
 pub use app::{stage, App, AppBuilder};
@@ -0,0 +1,70 @@
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    ptr,
+};
+
+use legion::Resources;
+
+struct Context {
+    resources: *const Resources,
+    dependencies: Vec<PathBuf>,
+}
+
+thread_local! {
+    static CONTEXT: RefCell<Context> = RefCell::new(Context {
+        resources: ptr::null(),
+        dependencies: Vec::new(),
+    });
+}
+
+/// Makes `resources` available to components through [`with_resources`] while `f`
+/// deserializes a scene, e.g. so handles can load the assets they point to. Also returns
+/// the files added with [`add_dependency`] meanwhile.
+#[inline]
+pub fn deserialize_with<R>(resources: &Resources, f: impl FnOnce() -> R) -> (R, Vec<PathBuf>) {
+    struct Restore(Context);
+
+    impl Drop for Restore {
+        #[inline]
+        fn drop(&mut self) {
+            CONTEXT.with(|context| std::mem::swap(&mut *context.borrow_mut(), &mut self.0));
+        }
+    }
+
+    let current = Context {
+        resources,
+        dependencies: Vec::new(),
+    };
+    let _restore = Restore(CONTEXT.with(|context| context.replace(current)));
+
+    let result = f();
+    let dependencies =
+        CONTEXT.with(|context| std::mem::take(&mut context.borrow_mut().dependencies));
+
+    (result, dependencies)
+}
+
+/// Calls `f` with the resources of the app a scene is deserialized into, `None` if not
+/// called within [`deserialize_with`].
+#[inline]
+pub fn with_resources<R>(f: impl FnOnce(&Resources) -> R) -> Option<R> {
+    let resources = CONTEXT.with(|context| context.borrow().resources);
+
+    // SAFETY: the pointer is only set while `deserialize_with` borrows the resources
+    unsafe { resources.as_ref() }.map(f)
+}
+
+/// Records that the scene being deserialized needs the file at `path`, e.g. the asset a
+/// handle points to, relative to the asset roots. Does nothing outside of
+/// [`deserialize_with`].
+#[inline]
+pub fn add_dependency(path: &Path) {
+    CONTEXT.with(|context| {
+        let mut context = context.borrow_mut();
+
+        if !context.resources.is_null() && !context.dependencies.iter().any(|dep| dep == path) {
+            context.dependencies.push(path.to_path_buf());
+        }
+    });
+}
@@ -0,0 +1,76 @@
+use std::{collections::HashMap, path::Path};
+
+use legion::{Entity, Resources, World};
+
+pub type SpawnFn = fn(&mut World, &mut Resources, &Path) -> Entity;
+
+/// Called every frame after an asset is spawned alone into a world for its thumbnail, until
+/// it's ready to be rendered.
+pub type PreviewFn = fn(&mut World, &mut Resources) -> Preview;
+
+/// Shows the asset at `path` in the editor's inspector, returns if it was changed.
+pub type EditFn = fn(&mut World, &mut Resources, &Path, &mut egui::Ui) -> bool;
+
+/// State of a thumbnail returned by a [`PreviewFn`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Preview {
+    /// Waiting for the assets to load, or for the entities to settle.
+    Loading,
+    /// The world has a camera showing the asset.
+    Ready,
+    Failed(String),
+}
+
+/// Functions spawning an entity for an asset file, used when an asset is dropped into the
+/// editor viewport, the functions staging those entities for thumbnails, and the functions
+/// editing asset files in the inspector.
+#[derive(Default)]
+pub struct AssetSpawners {
+    spawners: HashMap<String, SpawnFn>,
+    previews: HashMap<String, PreviewFn>,
+    editors: HashMap<String, EditFn>,
+}
+
+impl AssetSpawners {
+    #[inline]
+    pub fn register(&mut self, extensions: &[&str], spawner: SpawnFn) {
+        for ext in extensions {
+            self.spawners.insert(String::from(*ext), spawner);
+        }
+    }
+
+    #[inline]
+    pub fn get(&self, path: &Path) -> Option<SpawnFn> {
+        let ext = path.extension()?.to_str()?;
+
+        self.spawners.get(ext).copied()
+    }
+
+    #[inline]
+    pub fn register_preview(&mut self, extensions: &[&str], preview: PreviewFn) {
+        for ext in extensions {
+            self.previews.insert(String::from(*ext), preview);
+        }
+    }
+
+    #[inline]
+    pub fn preview(&self, path: &Path) -> Option<PreviewFn> {
+        let ext = path.extension()?.to_str()?;
+
+        self.previews.get(ext).copied()
+    }
+
+    #[inline]
+    pub fn register_editor(&mut self, extensions: &[&str], edit: EditFn) {
+        for ext in extensions {
+            self.editors.insert(String::from(*ext), edit);
+        }
+    }
+
+    #[inline]
+    pub fn editor(&self, path: &Path) -> Option<EditFn> {
+        let ext = path.extension()?.to_str()?;
+
+        self.editors.get(ext).copied()
+    }
+}
@@ -0,0 +1,528 @@
+use std::{
+    collections::{HashMap, HashSet},
+    mem::ManuallyDrop,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
+};
+
+use egui::*;
+use nova_assets::{server::AssetServer, Assets, Handle};
+use nova_core::spawners::Preview;
+use nova_engine::RunSettings;
+use nova_inspect::drag::{dragged_asset, set_dragged_asset, DraggedAsset};
+use nova_render::{render_node::Target, render_texture::RenderTexture};
+use nova_wgpu::*;
+
+use crate::{load::Game, scenes::Scenes};
+
+/// How often the asset dirs are rescanned.
+const SCAN_INTERVAL: Duration = Duration::from_secs(2);
+const THUMBNAIL_SIZE: f32 = 64.0;
+const TILE_SIZE: Vec2 = Vec2::new(THUMBNAIL_SIZE + 8.0, THUMBNAIL_SIZE + 24.0);
+/// Textures decoded at once on the asset pool, so loads aren't queued behind a folder of
+/// large images.
+const MAX_DECODING: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AssetKind {
+    Scene,
+    Mesh,
+    Material,
+    Texture,
+    Shader,
+    Other,
+}
+
+impl AssetKind {
+    pub const ALL: [Self; 6] = [
+        Self::Scene,
+        Self::Mesh,
+        Self::Material,
+        Self::Texture,
+        Self::Shader,
+        Self::Other,
+    ];
+
+    #[inline]
+    pub fn from_path(path: &Path) -> Self {
+        let ext = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+
+        match ext.as_str() {
+            "scn" => Self::Scene,
+            "obj" | "gltf" | "glb" => Self::Mesh,
+            "mat" => Self::Material,
+            "png" | "jpg" | "jpeg" | "bmp" | "tga" => Self::Texture,
+            "wgsl" | "spv" | "glsl" | "vert" | "frag" => Self::Shader,
+            _ => Self::Other,
+        }
+    }
+
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Scene => "Scenes",
+            Self::Mesh => "Meshes",
+            Self::Material => "Materials",
+            Self::Texture => "Textures",
+            Self::Shader => "Shaders",
+            Self::Other => "Other",
+        }
+    }
+
+    #[inline]
+    pub fn icon(&self) -> &'static str {
+        match self {
+            Self::Scene => "🎬",
+            Self::Mesh => "📦",
+            Self::Material => "🎨",
+            Self::Texture => "🖼",
+            Self::Shader => "✨",
+            Self::Other => "📄",
+        }
+    }
+}
+
+pub struct AssetEntry {
+    /// Path relative to the asset dirs.
+    pub path: PathBuf,
+    pub kind: AssetKind,
+    pub uuid: Option<String>,
+    pub modified: Option<SystemTime>,
+}
+
+struct Thumbnail {
+    modified: Option<SystemTime>,
+    texture: Option<Handle<TextureView<'static>>>,
+}
+
+/// Texture thumbnail decoded on the asset pool, waiting to be uploaded.
+struct DecodedThumbnail {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    image: Result<(u32, u32, Vec<u8>), String>,
+}
+
+#[derive(Default)]
+pub struct AssetBrowser {
+    pub filter: Option<AssetKind>,
+    pub search: String,
+    pub entries: Vec<AssetEntry>,
+    last_scan: Option<Instant>,
+    thumbnails: HashMap<PathBuf, Thumbnail>,
+    /// Mesh spawned into [`Scenes::thumbnails`] and the time it was modified, rendered once
+    /// its assets are loaded.
+    previewing: Option<(PathBuf, Option<SystemTime>)>,
+    /// Textures being decoded on the asset pool.
+    decoding: HashSet<PathBuf>,
+    decoded: Arc<Mutex<Vec<DecodedThumbnail>>>,
+}
+
+/// Decodes the image at `path` into an rgba thumbnail.
+#[inline]
+fn decode_thumbnail(path: &Path) -> Result<(u32, u32, Vec<u8>), image::ImageError> {
+    let size = THUMBNAIL_SIZE as u32;
+    let image = image::open(path)?.thumbnail(size, size).to_rgba8();
+
+    Ok((image.width(), image.height(), image.into_raw()))
+}
+
+impl AssetBrowser {
+    /// Rescans the asset dirs of `server` if it's been a while or `force` is set.
+    #[inline]
+    pub fn scan(&mut self, server: &AssetServer, force: bool) {
+        if !force
+            && self
+                .last_scan
+                .map_or(false, |last| last.elapsed() < SCAN_INTERVAL)
+        {
+            return;
+        }
+
+        self.last_scan = Some(Instant::now());
+
+        self.entries = server
+            .asset_paths()
+            .into_iter()
+            .map(|path| {
+                let uuid = server
+                    .read_meta(&path)
+                    .ok()
+                    .flatten()
+                    .map(|meta| meta.uuid.to_string());
+                let modified = server.io().modified(&path);
+
+                AssetEntry {
+                    kind: AssetKind::from_path(&path),
+                    path,
+                    uuid,
+                    modified,
+                }
+            })
+            .collect();
+
+        let entries = &self.entries;
+        self.thumbnails
+            .retain(|path, _| entries.iter().any(|entry| entry.path == *path));
+    }
+
+    #[inline]
+    fn is_shown(&self, entry: &AssetEntry) -> bool {
+        if self.filter.map_or(false, |filter| filter != entry.kind) {
+            return false;
+        }
+
+        let search = self.search.trim().to_lowercase();
+
+        search.is_empty()
+            || entry
+                .path
+                .to_string_lossy()
+                .to_lowercase()
+                .contains(&search)
+    }
+
+    #[inline]
+    fn has_thumbnail(&self, entry: &AssetEntry) -> bool {
+        self.thumbnails
+            .get(&entry.path)
+            .map_or(false, |thumbnail| thumbnail.modified == entry.modified)
+    }
+
+    /// Creates missing or outdated thumbnails for textures, see
+    /// [`update_mesh_thumbnails`](Self::update_mesh_thumbnails) for meshes, other kinds show
+    /// their icon. Textures are decoded on the pool of `server` and uploaded once decoded.
+    #[inline]
+    pub fn update_thumbnails(
+        &mut self,
+        server: &AssetServer,
+        instance: &Instance,
+        textures: &mut Assets<TextureView<'static>>,
+    ) {
+        let decoded = std::mem::take(&mut *self.decoded.lock().unwrap());
+
+        for decoded in decoded {
+            self.decoding.remove(&decoded.path);
+
+            // removed while it was decoded
+            if !self.entries.iter().any(|entry| entry.path == decoded.path) {
+                continue;
+            }
+
+            let texture = match decoded.image {
+                Ok((width, height, data)) => {
+                    let desc = TextureDescriptor {
+                        label: Some("thumbnail"),
+                        size: Extent3d {
+                            width,
+                            height,
+                            depth_or_array_layers: 1,
+                        },
+                        mip_level_count: 1,
+                        sample_count: 1,
+                        dimension: TextureDimension::D2,
+                        format: TextureFormat::Rgba8UnormSrgb,
+                        usage: TextureUsage::SAMPLED | TextureUsage::COPY_DST,
+                    };
+
+                    let texture = instance.create_texture_with_data(&desc, &data);
+
+                    Some(textures.add(texture.view()))
+                }
+                Err(err) => {
+                    log::warn!("no thumbnail for '{}': {}", decoded.path.display(), err);
+                    None
+                }
+            };
+
+            let thumbnail = Thumbnail {
+                modified: decoded.modified,
+                texture,
+            };
+
+            self.thumbnails.insert(decoded.path, thumbnail);
+        }
+
+        for entry in &self.entries {
+            if self.decoding.len() >= MAX_DECODING {
+                break;
+            }
+
+            if entry.kind != AssetKind::Texture
+                || self.has_thumbnail(entry)
+                || self.decoding.contains(&entry.path)
+            {
+                continue;
+            }
+
+            self.decoding.insert(entry.path.clone());
+
+            let path = entry.path.clone();
+            let modified = entry.modified;
+            let file = server.resolve(&entry.path);
+            let decoded = self.decoded.clone();
+
+            server.pool().spawn(move || {
+                let image = decode_thumbnail(&file).map_err(|err| err.to_string());
+
+                decoded.lock().unwrap().push(DecodedThumbnail {
+                    path,
+                    modified,
+                    image,
+                });
+            });
+        }
+    }
+
+    /// Renders missing or outdated thumbnails of meshes with the game's pipelines, one at a
+    /// time since their assets load in the background. Each mesh is spawned alone into
+    /// [`Scenes::thumbnails`], which the game frames for it once loaded, see
+    /// [`AppBuilder::register_asset_preview`](nova_core::AppBuilder::register_asset_preview).
+    /// Meshes keep their icon while the game isn't loaded.
+    #[inline]
+    pub fn update_mesh_thumbnails(
+        &mut self,
+        game: &Game,
+        scenes: &mut Scenes,
+        instance: &Instance,
+        textures: &mut Assets<TextureView<'static>>,
+        settings: &RunSettings,
+    ) {
+        let loaded = match &game.loaded {
+            Some(loaded) => loaded,
+            None => return,
+        };
+
+        // the app was unloaded with the scenes
+        if scenes.thumbnails.is_none() {
+            self.previewing = None;
+        }
+
+        let (path, modified) = match &self.previewing {
+            Some(previewing) => previewing.clone(),
+            None => {
+                let entry = match self
+                    .entries
+                    .iter()
+                    .find(|entry| entry.kind == AssetKind::Mesh && !self.has_thumbnail(entry))
+                {
+                    Some(entry) => entry,
+                    None => return,
+                };
+
+                if scenes.thumbnails.is_none() {
+                    let size = THUMBNAIL_SIZE as u32;
+
+                    match unsafe { game.load_thumbnails(instance, settings, size) } {
+                        Ok(thumbnails) => scenes.thumbnails = Some(ManuallyDrop::new(thumbnails)),
+                        Err(err) => log::error!("no mesh thumbnails: {}", err),
+                    }
+                }
+
+                let spawned = scenes.thumbnails.as_mut().map_or(false, |thumbnails| {
+                    let app = &mut thumbnails.app;
+                    app.world.clear();
+
+                    (app.spawn_asset)(
+                        &mut app.world,
+                        &mut app.resources,
+                        &app.spawners,
+                        &entry.path,
+                    )
+                    .is_some()
+                });
+
+                if spawned {
+                    self.previewing = Some((entry.path.clone(), entry.modified));
+                } else {
+                    let thumbnail = Thumbnail {
+                        modified: entry.modified,
+                        texture: None,
+                    };
+
+                    self.thumbnails.insert(entry.path.clone(), thumbnail);
+                }
+
+                return;
+            }
+        };
+
+        let app = &mut scenes.thumbnails.as_mut().unwrap().app;
+
+        (app.update)(&mut app.editor_schedule, &mut app.world, &mut app.resources);
+
+        let preview = (app.preview_asset)(&mut app.world, &mut app.resources, &app.spawners, &path);
+
+        let texture = match preview {
+            Some(Preview::Loading) => return,
+            Some(Preview::Ready) => {
+                let size = THUMBNAIL_SIZE as u32;
+                let texture =
+                    RenderTexture::new(instance, TextureFormat::Rgba8UnormSrgb, (size, size), 1);
+
+                let target = Target {
+                    view: &texture.view,
+                    depth: None,
+                    format: texture.desc.format,
+                    size: texture.size(),
+                };
+
+                match unsafe { loaded.render_view(&mut app.world, &mut app.resources, &target) } {
+                    Ok(()) => Some(textures.add(texture.texture.view())),
+                    Err(err) => {
+                        log::warn!("no thumbnail for '{}': {}", path.display(), err);
+                        None
+                    }
+                }
+            }
+            Some(Preview::Failed(err)) => {
+                log::warn!("no thumbnail for '{}': {}", path.display(), err);
+                None
+            }
+            None => None,
+        };
+
+        app.world.clear();
+
+        self.previewing = None;
+        self.thumbnails
+            .insert(path, Thumbnail { modified, texture });
+    }
+
+    #[inline]
+    fn tile_ui(&self, ui: &mut Ui, entry: &AssetEntry) -> Response {
+        let (rect, response) = ui.allocate_exact_size(TILE_SIZE, Sense::click_and_drag());
+
+        if response.hovered() || response.dragged() {
+            let fill = ui.visuals().widgets.hovered.bg_fill;
+            ui.painter().rect_filled(rect, 2.0, fill);
+        }
+
+        let image_rect = Rect::from_min_size(
+            rect.min + Vec2::splat((TILE_SIZE.x - THUMBNAIL_SIZE) / 2.0),
+            Vec2::splat(THUMBNAIL_SIZE),
+        );
+
+        let texture = self
+            .thumbnails
+            .get(&entry.path)
+            .and_then(|thumbnail| thumbnail.texture.as_ref());
+
+        match texture {
+            Some(texture) => {
+                let id = TextureId::User(texture.clone().unwrap_id());
+                ui.put(image_rect, Image::new(id, image_rect.size()));
+            }
+            None => {
+                let icon = Label::new(entry.kind.icon()).text_style(TextStyle::Heading);
+                ui.put(image_rect, icon);
+            }
+        }
+
+        let name = entry
+            .path
+            .file_name()
+            .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+        let name_rect = Rect::from_min_max(pos2(rect.min.x, image_rect.max.y), rect.max);
+        ui.put(name_rect, Label::new(name).small());
+
+        response.on_hover_text(entry.path.display().to_string())
+    }
+
+    /// Shows the asset tiles, returns the path of an asset that was double clicked, relative
+    /// to the asset dirs.
+    #[inline]
+    pub fn ui(&mut self, ui: &mut Ui, server: &AssetServer) -> Option<PathBuf> {
+        let mut opened = None;
+
+        ui.horizontal(|ui| {
+            ui.label("Assets");
+
+            let selected = self.filter.map_or("All", |filter| filter.name());
+
+            ComboBox::from_id_source("asset_filter")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.filter, None, "All");
+
+                    for kind in AssetKind::ALL.iter() {
+                        ui.selectable_value(&mut self.filter, Some(*kind), kind.name());
+                    }
+                });
+
+            ui.add(TextEdit::singleline(&mut self.search).hint_text("Search"));
+
+            if ui.button("Refresh").clicked() {
+                self.scan(server, true);
+            }
+        });
+
+        ui.separator();
+
+        ScrollArea::auto_sized().show(ui, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                for entry in self.entries.iter().filter(|entry| self.is_shown(entry)) {
+                    let response = self.tile_ui(ui, entry);
+
+                    if response.drag_started() {
+                        let asset = DraggedAsset {
+                            path: entry.path.clone(),
+                            uuid: entry.uuid.clone(),
+                        };
+
+                        set_dragged_asset(ui.ctx(), Some(asset));
+                    }
+
+                    if response.double_clicked() {
+                        opened = Some(entry.path.clone());
+                    }
+                }
+            });
+        });
+
+        opened
+    }
+}
+
+/// Shows the dragged asset at the pointer, and stops the drag once it's released, call
+/// after every drop target had a chance to accept the asset.
+#[inline]
+pub fn dragged_asset_ui(ctx: &CtxRef) {
+    let asset = match dragged_asset(ctx) {
+        Some(asset) => asset,
+        None => return,
+    };
+
+    let pointer = &ctx.input().pointer;
+
+    if !pointer.any_down() {
+        set_dragged_asset(ctx, None);
+        return;
+    }
+
+    let pos = match pointer.hover_pos() {
+        Some(pos) => pos,
+        None => return,
+    };
+
+    let kind = AssetKind::from_path(&asset.path);
+    let name = asset
+        .path
+        .file_name()
+        .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+
+    // not interactable so drop targets under the pointer still see it
+    Area::new("dragged_asset")
+        .order(Order::Tooltip)
+        .interactable(false)
+        .fixed_pos(pos + vec2(12.0, 12.0))
+        .show(ctx, |ui| {
+            Frame::popup(ui.style()).show(ui, |ui| {
+                ui.label(format!("{} {}", kind.icon(), name));
+            });
+        });
+}
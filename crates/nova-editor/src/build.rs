@@ -1,6 +1,6 @@
 use crate::{
     load::Game,
-    project::{Project, ProjectPath},
+    project::{BuildSettings, Project, ProjectPath},
     scenes::{SceneInstance, Scenes},
     view::{View, PRIMARY_VIEW},
 };
@@ -13,7 +13,7 @@ use nova_wgpu::{Instance, PrimitiveState};
 use std::{
     io,
     mem::ManuallyDrop,
-    path::Path,
+    path::{Path, PathBuf},
     process::{Child, Command, Stdio},
 };
 
@@ -21,13 +21,87 @@ fn verify_crate_type(manifest: &Manifest) -> Result<(), ()> {
     let lib = manifest.lib.as_ref().ok_or(())?;
     let crate_type = lib.crate_type.as_ref().ok_or(())?;
 
-    if crate_type.iter().find(|ty| *ty == "rlib").is_some() {
+    if crate_type.iter().any(|ty| ty == "cdylib") {
         Ok(())
     } else {
         Err(())
     }
 }
 
+/// Loads the manifest at `manifest_path` and checks that it describes a game lib.
+pub fn load_manifest(manifest_path: &Path) -> Result<Manifest, String> {
+    // `Manifest::from_path` replaces `[lib]` with one inferred from 'src/lib.rs', which
+    // is always an rlib
+    let manifest = match std::fs::read(manifest_path) {
+        Ok(manifest) => Manifest::from_slice(&manifest),
+        Err(e) => return Err(format!("failed to load Cargo.toml: {}", e)),
+    };
+
+    let manifest = match manifest {
+        Ok(manifest) => manifest,
+        Err(e) => return Err(format!("failed to load Cargo.toml: {}", e)),
+    };
+
+    if verify_crate_type(&manifest).is_err() {
+        return Err(format!(
+            "crate type must be \"cdylib\" {}",
+            manifest_path.display()
+        ));
+    }
+
+    Ok(manifest)
+}
+
+#[inline]
+pub fn build_command(
+    manifest_path: &Path,
+    target_dir: &Path,
+    settings: &BuildSettings,
+    release: bool,
+) -> Command {
+    let mut command = Command::new("cargo");
+    command
+        .arg("build")
+        .arg("--lib")
+        .arg("--manifest-path")
+        .arg(manifest_path)
+        .arg("--target-dir")
+        .arg(target_dir)
+        .arg("--profile")
+        .arg(settings.profile(release));
+
+    if !settings.features.is_empty() {
+        command.arg("--features").arg(settings.features.join(","));
+    }
+
+    log::debug!("running build command: {:?}", command);
+
+    command
+}
+
+/// Path of the game lib built from `manifest`.
+#[inline]
+pub fn lib_path(
+    manifest: &Manifest,
+    target_dir: &Path,
+    settings: &BuildSettings,
+    release: bool,
+) -> PathBuf {
+    let project_name = &manifest.package.as_ref().unwrap().name;
+    let lib_name = project_name.replace('-', "_");
+
+    // cargo puts the builtin profiles in the directory of the profile they inherit from
+    let profile_dir = match settings.profile(release) {
+        "dev" | "test" => "debug",
+        "bench" => "release",
+        profile => profile,
+    };
+
+    target_dir
+        .join(profile_dir)
+        .join(library_filename(lib_name))
+}
+
 #[derive(Default)]
 pub struct Builder {
     process: Option<Child>,
@@ -36,34 +110,18 @@ pub struct Builder {
 
 impl Builder {
     #[inline]
-    pub fn build(&mut self, manifest_path: &Path, target_dir: &Path) -> Result<(), io::Error> {
-        let manifest = match Manifest::from_path(manifest_path) {
-            Ok(manifest) => manifest,
-            Err(e) => {
-                log::error!("failed to load Cargo.toml: {}", e);
-                return Ok(());
-            }
-        };
-
-        if verify_crate_type(&manifest).is_err() {
-            log::error!("crate type must be \"cdylib\" {}", manifest_path.display());
+    pub fn build(
+        &mut self,
+        manifest_path: &Path,
+        target_dir: &Path,
+        settings: &BuildSettings,
+    ) -> Result<(), io::Error> {
+        if let Err(e) = load_manifest(manifest_path) {
+            log::error!("{}", e);
             return Ok(());
         }
 
-        let mut command = Command::new("cargo");
-        command
-            .arg("build")
-            .arg("--lib")
-            .arg("--manifest-path")
-            .arg(manifest_path)
-            .arg("--target-dir")
-            .arg(target_dir);
-
-        log::debug!("running build command: {:?}", command);
-
-        if self.release {
-            command.arg("--release");
-        }
+        let mut command = build_command(manifest_path, target_dir, settings, self.release);
 
         command
             .stdin(Stdio::null())
@@ -116,21 +174,14 @@ pub fn build_system(_world: &mut World, resources: &mut Resources) {
                     }
                 };
 
-            let project_name = &manifest.package.as_ref().unwrap().name;
-            let lib_name = project_name.replace('-', "_");
-
             log::info!("loading game lib");
 
-            let target = if builder.release {
-                project_path
-                    .dir()
-                    .join(project.target_dir())
-                    .join("release")
-            } else {
-                project_path.dir().join(project.target_dir()).join("debug")
-            };
-
-            let lib_path = target.join(library_filename(lib_name));
+            let lib_path = lib_path(
+                &manifest,
+                &project_path.dir().join(project.target_dir()),
+                &project.build,
+                builder.release,
+            );
 
             let res = unsafe { game.load(&lib_path) };
 
@@ -157,10 +208,12 @@ pub fn build_system(_world: &mut World, resources: &mut Resources) {
                 };
 
                 let res = unsafe {
-                    game.loaded
-                        .as_ref()
-                        .unwrap()
-                        .init(app, instance.clone(), target)
+                    game.loaded.as_ref().unwrap().init(
+                        app,
+                        instance.clone(),
+                        target,
+                        &project.run_settings(project_path.dir()),
+                    )
                 };
 
                 let app = match res {
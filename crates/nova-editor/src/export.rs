@@ -0,0 +1,421 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use clap::Clap;
+use erased_serde::Serializer;
+use libloading::library_filename;
+use nova_assets::{
+    io::FileAssetIo,
+    meta::meta_path,
+    pack::{PackWriter, PACK_EXTENSION},
+};
+use nova_core::App;
+use nova_engine::RunSettings;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    build::{build_command, lib_path, load_manifest},
+    load::LoadedGame,
+    project::{ExportSettings, Project, ProjectPath, RenderSettings, WindowSettings},
+    scenes::SceneInstance,
+};
+
+/// Name of the manifest written to the root of an exported game.
+pub const EXPORT_MANIFEST: &str = "export.toml";
+/// Name of the asset archive, without extension.
+pub const ASSET_PACK: &str = "assets";
+/// How long the assets of a scene may take to load while compiling it.
+const LOAD_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Clap)]
+pub struct ExportOpts {
+    /// Path to the root of the project or 'Nova.toml'.
+    pub path: PathBuf,
+    /// Directory to export to, overrides 'export.out-dir'.
+    #[clap(long)]
+    pub out_dir: Option<PathBuf>,
+}
+
+#[derive(Clap)]
+pub struct PackOpts {
+    /// Path to the root of the project or 'Nova.toml'.
+    pub path: PathBuf,
+    /// Archive to write, defaults to 'assets.pack' in the project.
+    #[clap(long)]
+    pub out: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ExportedFile {
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ExportManifest {
+    pub name: String,
+    pub main_scene: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bindings: Option<String>,
+    #[serde(default)]
+    pub asset_dirs: Vec<String>,
+    pub lib: ExportedFile,
+    pub scenes: Vec<ExportedFile>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assets: Vec<ExportedFile>,
+    /// Archive holding the assets of the asset dirs, if they're packed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asset_pack: Option<ExportedFile>,
+    #[serde(default)]
+    pub window: WindowSettings,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub render: Option<RenderSettings>,
+}
+
+impl ExportManifest {
+    #[inline]
+    pub fn load(dir: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let manifest = fs::read_to_string(dir.join(EXPORT_MANIFEST))?;
+        Ok(toml::from_str(&manifest)?)
+    }
+
+    /// Settings to run the exported game with, paths are relative to `dir`.
+    #[inline]
+    pub fn run_settings(&self, dir: &Path) -> RunSettings {
+        RunSettings {
+            render: self.render.as_ref().map(Into::into),
+            bindings: self.bindings.as_ref().map(|path| dir.join(path)),
+            asset_dirs: self.asset_dirs.iter().map(|path| dir.join(path)).collect(),
+            asset_pack: self.asset_pack.as_ref().map(|pack| dir.join(&pack.path)),
+            ..self.window.run_settings(&self.name)
+        }
+    }
+}
+
+#[inline]
+pub fn hash(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+#[inline]
+fn path_string(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+fn write_file(out_dir: &Path, path: &Path, data: &[u8]) -> Result<ExportedFile, std::io::Error> {
+    let out_path = out_dir.join(path);
+
+    if let Some(parent) = out_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(out_path, data)?;
+
+    Ok(ExportedFile {
+        path: path_string(path),
+        size: data.len() as u64,
+        sha256: hash(data),
+    })
+}
+
+/// Compiles the scene at `path` to the format shipped in exports by loading it into `app`
+/// and saving it again without whitespace, so it fails if the game can't load it. Also
+/// returns the asset files the scene depends on, including the ones their loaders read or
+/// load themselves, like the buffers and textures of a glTF file.
+pub fn compile_scene(
+    app: &mut App,
+    path: &Path,
+) -> Result<(String, Vec<PathBuf>), Box<dyn std::error::Error>> {
+    app.world.clear();
+
+    let dependencies = SceneInstance::deserialize(app, path)?;
+
+    // the files loaders read are only known once everything is loaded
+    let start = Instant::now();
+
+    let dependencies = loop {
+        (app.update)(&mut app.editor_schedule, &mut app.world, &mut app.resources);
+
+        let files = dependencies
+            .iter()
+            .map(|dependency| (app.asset_files)(&app.resources, dependency))
+            .collect::<Option<Vec<_>>>();
+
+        if let Some(files) = files {
+            break files.concat();
+        }
+
+        if start.elapsed() > LOAD_TIMEOUT {
+            return Err("timed out loading the assets of the scene".into());
+        }
+
+        std::thread::sleep(Duration::from_millis(1));
+    };
+
+    let mut compiled = Vec::new();
+    let mut serializer = ron::ser::Serializer::new(&mut compiled, None, false)?;
+    let mut serializer = <dyn Serializer>::erase(&mut serializer);
+
+    (app.serialize)(&app.world, &app.registry, &mut serializer)?;
+
+    Ok((String::from_utf8(compiled)?, dependencies))
+}
+
+/// Collects every file in `path`, recursing into directories.
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<(), std::io::Error> {
+    if path.is_dir() {
+        for entry in path.read_dir()? {
+            collect_files(&entry?.path(), files)?;
+        }
+    } else {
+        files.push(path.to_path_buf());
+    }
+
+    Ok(())
+}
+
+/// Exports the project at `opts.path` into a self-contained directory.
+pub fn export(opts: ExportOpts) -> Result<(), Box<dyn std::error::Error>> {
+    let project_path = ProjectPath::resolve(&opts.path)?;
+    let project_dir = project_path.dir();
+
+    let project = match Project::load(&project_path.0)? {
+        Some(project) => project,
+        None => return Err(format!("no project at '{}'", project_path.0.display()).into()),
+    };
+
+    let default_settings = ExportSettings::default();
+    let settings = project.export.as_ref().unwrap_or(&default_settings);
+
+    let main_scene = match project.main_scene_path() {
+        Some(main_scene) => main_scene,
+        None => return Err("project has no 'game.main-scene' to export".into()),
+    };
+
+    let out_dir = match opts.out_dir {
+        Some(out_dir) => out_dir,
+        None => project_dir.join(&settings.out_dir),
+    };
+
+    // the out dir is replaced, so it must be a previous export and not a typo
+    if out_dir.exists()
+        && fs::read_dir(&out_dir)?.next().is_some()
+        && !out_dir.join(EXPORT_MANIFEST).is_file()
+    {
+        return Err(format!(
+            "'{}' is not empty and not a previous export, refusing to replace it",
+            out_dir.display()
+        )
+        .into());
+    }
+
+    let manifest_path = project_dir.join(project.manifest_path());
+    let target_dir = project_dir.join(project.target_dir());
+
+    let manifest = load_manifest(&manifest_path)?;
+
+    log::info!("building game lib in release");
+
+    let status = build_command(&manifest_path, &target_dir, &project.build, true).status()?;
+
+    if !status.success() {
+        return Err("failed to build game".into());
+    }
+
+    if out_dir.exists() {
+        fs::remove_dir_all(&out_dir)?;
+    }
+
+    fs::create_dir_all(&out_dir)?;
+
+    let lib_name = PathBuf::from(library_filename(
+        manifest.package.as_ref().unwrap().name.replace('-', "_"),
+    ));
+    let lib_path = lib_path(&manifest, &target_dir, &project.build, true);
+    let lib_data = fs::read(&lib_path)?;
+    let lib = write_file(&out_dir, &lib_name, &lib_data)?;
+
+    let mut scene_paths = vec![main_scene.clone()];
+    scene_paths.extend(settings.scenes.iter().map(PathBuf::from));
+
+    let asset_dirs = project.asset_dirs();
+
+    let mut scenes = Vec::new();
+    let mut asset_paths = Vec::new();
+
+    // scenes are compiled by the game, which knows their components
+    let game = unsafe { LoadedGame::load(&lib_path)? };
+    let app = unsafe { game.init_headless(&project.run_settings(project_dir))? };
+    let mut scene = SceneInstance { app };
+
+    let compiled = scene_paths
+        .iter()
+        .map(|scene_path| {
+            log::info!("compiling scene: {}", scene_path.display());
+
+            compile_scene(&mut scene.app, &project_dir.join(scene_path))
+                .map_err(|e| format!("failed to compile '{}': {}", scene_path.display(), e))
+        })
+        .collect::<Result<Vec<_>, _>>();
+
+    // the app is dropped in the game lib before it's unloaded
+    scene.unload();
+    drop(game);
+
+    for (scene_path, (compiled, dependencies)) in scene_paths.iter().zip(compiled?) {
+        scenes.push(write_file(&out_dir, scene_path, compiled.as_bytes())?);
+
+        // dependencies are relative to the asset dirs
+        for path in dependencies {
+            let dir = asset_dirs
+                .iter()
+                .find(|dir| project_dir.join(dir).join(&path).exists())
+                .or_else(|| asset_dirs.first());
+
+            match dir {
+                Some(dir) => asset_paths.push(dir.join(path)),
+                None => asset_paths.push(path),
+            }
+        }
+    }
+
+    let bindings = project.bindings_path();
+    asset_paths.extend(bindings.clone());
+
+    for asset in &settings.assets {
+        let mut files = Vec::new();
+        collect_files(&project_dir.join(asset), &mut files)?;
+
+        for file in files {
+            asset_paths.push(file.strip_prefix(project_dir)?.to_path_buf());
+        }
+    }
+
+    // meta files carry the uuids and import settings of assets
+    let metas: Vec<_> = asset_paths
+        .iter()
+        .map(|path| meta_path(path))
+        .filter(|meta| project_dir.join(meta).exists())
+        .collect();
+    asset_paths.extend(metas);
+
+    asset_paths.sort();
+    asset_paths.dedup();
+
+    let mut assets = Vec::new();
+    let mut pack = PackWriter::new();
+
+    for asset_path in asset_paths {
+        if asset_path.is_absolute() {
+            log::warn!("skipping absolute asset path: {}", asset_path.display());
+            continue;
+        }
+
+        let data = match fs::read(project_dir.join(&asset_path)) {
+            Ok(data) => data,
+            Err(e) => {
+                log::warn!("skipping asset '{}': {}", asset_path.display(), e);
+                continue;
+            }
+        };
+
+        // the server only loads from the pack, so files outside the asset dirs stay loose
+        let packed = asset_dirs
+            .iter()
+            .find_map(|dir| asset_path.strip_prefix(dir).ok())
+            .filter(|_| settings.pack_assets);
+
+        match packed {
+            Some(path) => pack.add(path, data),
+            None => assets.push(write_file(&out_dir, &asset_path, &data)?),
+        }
+    }
+
+    let asset_pack = if pack.is_empty() {
+        None
+    } else {
+        let path = Path::new(ASSET_PACK).with_extension(PACK_EXTENSION);
+        pack.write_file(&out_dir.join(&path))?;
+
+        let data = fs::read(out_dir.join(&path))?;
+
+        Some(ExportedFile {
+            path: path_string(&path),
+            size: data.len() as u64,
+            sha256: hash(&data),
+        })
+    };
+
+    let launcher_name = format!("{}{}", project.package.name, std::env::consts::EXE_SUFFIX);
+    fs::copy(std::env::current_exe()?, out_dir.join(launcher_name))?;
+
+    let export_manifest = ExportManifest {
+        name: project.package.name.clone(),
+        main_scene: path_string(&main_scene),
+        bindings: bindings.as_deref().map(path_string),
+        asset_dirs: asset_dirs.iter().map(|dir| path_string(dir)).collect(),
+        lib,
+        scenes,
+        assets,
+        asset_pack,
+        window: project.window.clone().unwrap_or_default(),
+        render: project.render.clone(),
+    };
+
+    fs::write(
+        out_dir.join(EXPORT_MANIFEST),
+        toml::to_string(&export_manifest)?,
+    )?;
+
+    log::info!(
+        "exported {} scenes and {} assets to {}",
+        export_manifest.scenes.len(),
+        export_manifest.assets.len(),
+        out_dir.display()
+    );
+
+    Ok(())
+}
+
+/// Loose files of the asset dirs of `project`.
+#[inline]
+fn project_asset_io(project_dir: &Path, project: &Project) -> FileAssetIo {
+    FileAssetIo::new(
+        project
+            .asset_dirs()
+            .iter()
+            .map(|dir| project_dir.join(dir))
+            .collect(),
+    )
+}
+
+/// Packs every file in the asset dirs of the project at `opts.path` into an archive.
+pub fn pack(opts: PackOpts) -> Result<(), Box<dyn std::error::Error>> {
+    let project_path = ProjectPath::resolve(&opts.path)?;
+    let project_dir = project_path.dir();
+
+    let project = match Project::load(&project_path.0)? {
+        Some(project) => project,
+        None => return Err(format!("no project at '{}'", project_path.0.display()).into()),
+    };
+
+    let out = match opts.out {
+        Some(out) => out,
+        None => project_dir.join(Path::new(ASSET_PACK).with_extension(PACK_EXTENSION)),
+    };
+
+    let mut pack = PackWriter::new();
+    pack.add_io(&project_asset_io(project_dir, &project))?;
+    pack.write_file(&out)?;
+
+    log::info!("packed {} files into {}", pack.len(), out.display());
+
+    Ok(())
+}
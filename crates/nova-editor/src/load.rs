@@ -1,28 +1,82 @@
 use libloading::{Error, Library, Symbol};
 use nova_assets::Assets;
-use nova_core::{App, AppBuilder, Resources, World};
+use nova_core::{
+    abi::{AbiDescriptor, AbiMismatch},
+    App, AppBuilder, Resources, World,
+};
+use nova_engine::{init_wgpu_headless, RunSettings};
 use nova_render::{
     render_node::Target, render_target::RenderTarget, render_texture::RenderTexture,
 };
-use nova_wgpu::Instance;
+use nova_wgpu::{
+    Extent3d, Instance, TextureDescriptor, TextureDimension, TextureFormat, TextureUsage,
+};
 use serde::__private::de::InPlaceSeed;
-use std::path::Path;
+use std::{
+    fmt::{Display, Formatter},
+    path::Path,
+};
 
 use crate::{
     scenes::SceneInstance,
     view::{View, ViewType, PRIMARY_VIEW},
 };
 
+/// The [`AbiDescriptor`] a game lib must export to be loaded, must match `export_app!`.
+#[inline]
+pub fn expected_abi() -> AbiDescriptor {
+    AbiDescriptor::current()
+        .with_layout::<Instance>()
+        .with_layout::<RenderTarget>()
+        .with_layout::<Target<'static>>()
+        .with_layout::<RunSettings>()
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+    Library(Error),
+    MissingAbi(Error),
+    Abi(AbiMismatch),
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Library(err) => write!(f, "{}", err),
+            Self::MissingAbi(err) => write!(
+                f,
+                "game lib doesn't export an abi descriptor, was it built with export_app!? ({})",
+                err
+            ),
+            Self::Abi(mismatch) => write!(f, "{}, rebuild the game against this editor", mismatch),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
 pub struct LoadedGame {
     library: Library,
 }
 
 impl LoadedGame {
+    /// Loads the game lib at `path` and verifies it was built against the same nova and rustc
+    /// as the editor, nothing else in the lib is called if it wasn't.
     #[inline]
-    pub unsafe fn load(path: &Path) -> Result<Self, Error> {
-        Ok(Self {
-            library: unsafe { Library::new(path)? },
-        })
+    pub unsafe fn load(path: &Path) -> Result<Self, LoadError> {
+        let library = unsafe { Library::new(path).map_err(LoadError::Library)? };
+
+        {
+            let nova_abi: Symbol<extern "C" fn() -> AbiDescriptor> =
+                unsafe { library.get(b"nova_abi").map_err(LoadError::MissingAbi)? };
+
+            let abi = nova_abi();
+
+            // SAFETY: library is still loaded
+            unsafe { abi.check(&expected_abi()) }.map_err(LoadError::Abi)?;
+        }
+
+        Ok(Self { library })
     }
 
     #[inline]
@@ -31,10 +85,55 @@ impl LoadedGame {
         app: AppBuilder,
         instance: Instance,
         render_target: RenderTarget,
+        settings: &RunSettings,
     ) -> Result<App, Error> {
-        let export_app: Symbol<unsafe fn(AppBuilder, Instance, RenderTarget) -> App> =
+        let export_app: Symbol<unsafe fn(AppBuilder, Instance, RenderTarget, &RunSettings) -> App> =
             unsafe { self.library.get(b"export_app")? };
-        Ok(unsafe { export_app(app, instance, render_target) })
+        Ok(unsafe { export_app(app, instance, render_target, settings) })
+    }
+
+    /// Builds the app of the game without a window, rendering into a texture nothing reads,
+    /// for working with its scenes from the command line.
+    #[inline]
+    pub unsafe fn init_headless(&self, settings: &RunSettings) -> Result<App, Error> {
+        let instance = pollster::block_on(init_wgpu_headless());
+
+        let desc = TextureDescriptor {
+            label: Some("headless_target"),
+            size: Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Rgba8UnormSrgb,
+            usage: TextureUsage::RENDER_ATTACHMENT,
+        };
+
+        let view = instance.create_texture(&desc).view();
+        let target = RenderTarget::Texture { view, desc };
+
+        unsafe { self.init(AppBuilder::new(), instance, target, settings) }
+    }
+
+    /// Runs `settings` with the game's own engine loop, `load_scene` is called once the app
+    /// is built. Only returns if the lib doesn't export `run_app`.
+    #[inline]
+    pub unsafe fn run(
+        &self,
+        settings: RunSettings,
+        load_scene: &mut dyn FnMut(&mut App) -> Result<(), Box<dyn std::error::Error>>,
+    ) -> Result<(), Error> {
+        let run_app: Symbol<
+            unsafe fn(
+                RunSettings,
+                &mut dyn FnMut(&mut App) -> Result<(), Box<dyn std::error::Error>>,
+            ) -> !,
+        > = unsafe { self.library.get(b"run_app")? };
+
+        unsafe { run_app(settings, load_scene) }
     }
 
     #[inline]
@@ -58,7 +157,7 @@ pub struct Game {
 
 impl Game {
     #[inline]
-    pub unsafe fn load(&mut self, path: &Path) -> Result<(), Error> {
+    pub unsafe fn load(&mut self, path: &Path) -> Result<(), LoadError> {
         self.loaded = Some(unsafe { LoadedGame::load(path)? });
 
         Ok(())
@@ -80,6 +179,7 @@ impl Game {
         instance: &Instance,
         views: &Assets<View>,
         textures: &Assets<RenderTexture>,
+        settings: &RunSettings,
         path: &Path,
     ) -> Result<SceneInstance, String> {
         let app = AppBuilder::new();
@@ -87,6 +187,11 @@ impl Game {
         let view = views.get(&PRIMARY_VIEW).unwrap();
         let texture = textures.get(&view.texture).unwrap();
 
+        let settings = RunSettings {
+            watch_assets: true,
+            ..settings.clone()
+        };
+
         let target = RenderTarget::Texture {
             view: texture.texture.view(),
             desc: texture.desc.clone(),
@@ -96,7 +201,7 @@ impl Game {
             self.loaded
                 .as_ref()
                 .ok_or_else(|| "game lib not loaded")?
-                .init(app, instance.clone(), target)
+                .init(app, instance.clone(), target, &settings)
         };
 
         let app = match res {
@@ -115,6 +220,35 @@ impl Game {
 
         Ok(scene_instance)
     }
+
+    /// Builds an app of the game with an empty world for rendering thumbnails of `size`, see
+    /// [`AssetBrowser::update_mesh_thumbnails`](crate::asset_browser::AssetBrowser).
+    #[inline]
+    pub unsafe fn load_thumbnails(
+        &self,
+        instance: &Instance,
+        settings: &RunSettings,
+        size: u32,
+    ) -> Result<SceneInstance, String> {
+        let texture = RenderTexture::new(instance, TextureFormat::Rgba8UnormSrgb, (size, size), 1);
+
+        let target = RenderTarget::Texture {
+            view: texture.texture.view(),
+            desc: texture.desc.clone(),
+        };
+
+        let res = unsafe {
+            self.loaded
+                .as_ref()
+                .ok_or_else(|| "game lib not loaded")?
+                .init(AppBuilder::new(), instance.clone(), target, settings)
+        };
+
+        match res {
+            Ok(app) => Ok(SceneInstance { app }),
+            Err(err) => Err(format!("failed to init app: {}", err)),
+        }
+    }
 }
 
 impl Drop for Game {
@@ -1,26 +1,34 @@
 #![deny(unsafe_op_in_unsafe_fn)]
 
+mod asset_browser;
 mod build;
 mod egui_system;
+mod export;
 mod load;
 mod main_ui;
+mod new;
 mod project;
+mod runner;
 mod scenes;
 mod view;
 
 use std::path::PathBuf;
 
+use asset_browser::AssetBrowser;
 use build::{build_system, Builder};
 use egui_system::EguiPlugin;
+use export::{ExportManifest, ExportOpts, PackOpts, EXPORT_MANIFEST};
 use load::Game;
-use main_ui::SelectedEntity;
-use nova_assets::AssetsAppExt;
+use main_ui::{SelectedAsset, SelectedEntity};
+use new::{NewOpts, Template};
+use nova_assets::{server::AssetServer, AssetsAppExt};
 use nova_core::stage;
 use nova_engine::run;
 use nova_input::InputPlugin;
 use nova_render::render_texture::RenderTexture;
 use nova_wgpu::TextureView;
 use project::{Project, ProjectPath};
+use runner::RunOpts;
 use scenes::{scenes_system, Scenes};
 use view::{View, ViewPlugin};
 
@@ -30,7 +38,21 @@ use clap::{crate_authors, crate_version, Clap};
 #[clap(author = crate_authors!(), version = crate_version!())]
 struct Opts {
     /// Path to the root of the project or 'Nova.toml'.
-    path: PathBuf,
+    path: Option<PathBuf>,
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Clap)]
+enum Command {
+    /// Create a new project from a template.
+    New(NewOpts),
+    /// Build the project and run its main scene without the editor.
+    Run(RunOpts),
+    /// Build the project in release and export it with its scenes and assets.
+    Export(ExportOpts),
+    /// Pack the assets of the project into a single archive.
+    Pack(PackOpts),
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -43,41 +65,96 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .with_module_level("naga", log::LevelFilter::Error)
         .init()?;
 
-    let mut path = std::fs::canonicalize(opts.path).unwrap();
+    match opts.command {
+        Some(Command::New(opts)) => new::new(opts),
+        Some(Command::Run(opts)) => runner::run(opts),
+        Some(Command::Export(opts)) => export::export(opts),
+        Some(Command::Pack(opts)) => export::pack(opts),
+        None => match opts.path {
+            Some(path) => editor(path),
+            None => launch_export(),
+        },
+    }
+}
+
+/// Runs the game next to the executable when it's the launcher of an exported game.
+fn launch_export() -> Result<(), Box<dyn std::error::Error>> {
+    let exe = std::env::current_exe()?;
+    let dir = exe.parent().unwrap();
 
-    if path.is_dir() {
-        path = path.join("Nova.toml");
+    if !dir.join(EXPORT_MANIFEST).exists() {
+        return Err("expected a project path or a subcommand, see --help".into());
+    }
+
+    let manifest = ExportManifest::load(dir)?;
+    let settings = manifest.run_settings(dir);
+
+    runner::run_exported(dir, manifest, settings)
+}
+
+fn editor(path: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let project_path = ProjectPath::resolve(&path)?;
+    let path = project_path.0.clone();
+
+    if !path.exists() {
+        let dir = project_path.dir();
+        let name = dir.file_name().unwrap().to_string_lossy();
+
+        log::info!("create new project: {}", path.display());
+        new::scaffold(dir, &name, Template::default(), &new::default_nova_path())?;
     }
 
     let project = match Project::load(&path) {
-        Ok(project) => match project {
-            Some(project) => {
-                log::info!("loaded project: {}", path.display());
-                project
-            }
-            None => {
-                log::info!("create new project: {}", path.display());
-                Project::default()
-            }
-        },
+        Ok(Some(project)) => {
+            log::info!("loaded project: {}", path.display());
+            project
+        }
+        Ok(None) => {
+            log::error!("failed to read project: {}", path.display());
+            return Ok(());
+        }
         Err(e) => {
             log::error!("failed to load project: {}", e);
             return Ok(());
         }
     };
 
-    project.write(&path)?;
+    // assets need meta files for their uuids before scenes reference them
+    for dir in project.asset_dirs() {
+        let dir = project_path.dir().join(dir);
+
+        if !dir.is_dir() {
+            continue;
+        }
+
+        match nova_assets::meta::create_missing(&dir) {
+            Ok(0) => {}
+            Ok(created) => log::info!("created {} meta files in {}", created, dir.display()),
+            Err(e) => log::error!("failed to create meta files in {}: {}", dir.display(), e),
+        }
+    }
+
+    let asset_dirs = project
+        .asset_dirs()
+        .iter()
+        .map(|dir| project_path.dir().join(dir))
+        .collect();
 
     run("Nova Editor", |mut app| {
         app.add_thread_local_to_stage(stage::PRE_UPDATE, build_system);
         app.add_thread_local_to_stage(stage::UPDATE, scenes_system);
 
+        // the editor's own server, only used to browse the project's assets
+        app.insert_resource(AssetServer::new(asset_dirs));
+
         app.insert_resource(project);
         app.insert_resource(ProjectPath(path));
         app.register_resource::<Game>();
         app.register_resource::<Scenes>();
         app.register_resource::<Builder>();
         app.register_resource::<SelectedEntity>();
+        app.register_resource::<SelectedAsset>();
+        app.register_resource::<AssetBrowser>();
         app.register_asset::<TextureView>();
         app.register_asset::<RenderTexture>();
         app.register_asset::<View>();
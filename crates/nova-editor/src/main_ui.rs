@@ -6,12 +6,14 @@ use std::{
 use egui::*;
 use erased_serde::Serializer;
 use glam::UVec2;
-use nova_assets::Assets;
+use nova_assets::{server::AssetServer, Assets};
 use nova_core::{AppBuilder, Entity, Resources, World};
+use nova_inspect::drag::dropped_asset;
 use nova_render::{render_target::RenderTarget, render_texture::RenderTexture};
 use nova_wgpu::{Instance, TextureView};
 
 use crate::{
+    asset_browser::{dragged_asset_ui, AssetBrowser, AssetKind},
     build::Builder,
     load::Game,
     project::{Project, ProjectPath},
@@ -22,6 +24,11 @@ use crate::{
 #[derive(Default)]
 pub struct SelectedEntity(pub Option<Entity>);
 
+/// Asset opened from the asset browser, edited in the inspector by the open scene's game
+/// until an entity is selected.
+#[derive(Default)]
+pub struct SelectedAsset(pub Option<PathBuf>);
+
 fn save(_world: &World, resources: &Resources) -> Result<(), Box<dyn std::error::Error>> {
     let builder = resources.get::<Builder>().unwrap();
     let scenes = resources.get::<Scenes>().unwrap();
@@ -53,6 +60,7 @@ pub fn main_ui(ctx: &CtxRef, world: &World, resources: &Resources) {
     bottom_panel_ui(ctx, world, resources);
     scene_panel_ui(ctx, world, resources);
     main_panel_ui(ctx, world, resources);
+    dragged_asset_ui(ctx);
 
     let running = resources.get::<Scenes>().unwrap().running;
     let input = ctx.input();
@@ -91,6 +99,7 @@ pub fn top_panel_ui(ctx: &CtxRef, world: &World, resources: &Resources) {
                 let res = builder.build(
                     &project_path.dir().join(&project.manifest_path()),
                     &project_path.dir().join(&project.target_dir()),
+                    &project.build,
                 );
 
                 if let Err(e) = res {
@@ -131,8 +140,10 @@ pub fn top_panel_ui(ctx: &CtxRef, world: &World, resources: &Resources) {
                     let instance = resources.get::<Instance>().unwrap();
                     let views = resources.get::<Assets<View>>().unwrap();
                     let textures = resources.get::<Assets<RenderTexture>>().unwrap();
+                    let settings = project.run_settings(project_path.dir());
 
-                    match unsafe { game.load_scene(&instance, &views, &textures, &path) } {
+                    match unsafe { game.load_scene(&instance, &views, &textures, &settings, &path) }
+                    {
                         Ok(loaded_scene) => {
                             scenes
                                 .instances
@@ -212,6 +223,54 @@ fn show_dir_ui(
     Ok(clicked)
 }
 
+/// Loads the scene at `path` if it isn't loaded already.
+fn load_scene(
+    resources: &Resources,
+    project: &Project,
+    project_path: &ProjectPath,
+    scenes: &mut Scenes,
+    path: &Path,
+) {
+    if scenes.instances.contains_key(path) {
+        return;
+    }
+
+    let game = resources.get::<Game>().unwrap();
+    let instance = resources.get::<Instance>().unwrap();
+    let views = resources.get::<Assets<View>>().unwrap();
+    let textures = resources.get::<Assets<RenderTexture>>().unwrap();
+    let settings = project.run_settings(project_path.dir());
+
+    match unsafe { game.load_scene(&instance, &views, &textures, &settings, path) } {
+        Ok(loaded_scene) => {
+            scenes
+                .instances
+                .insert(path.to_path_buf(), ManuallyDrop::new(loaded_scene));
+        }
+        Err(err) => {
+            log::error!("{}", err);
+        }
+    };
+}
+
+/// Loads the scene at `path` and switches to it, unless a scene is running.
+fn open_scene(resources: &Resources, path: &Path) {
+    let project_path = resources.get::<ProjectPath>().unwrap();
+    let project = resources.get::<Project>().unwrap();
+    let mut scenes = resources.get_mut::<Scenes>().unwrap();
+
+    if scenes.running {
+        return;
+    }
+
+    load_scene(resources, &project, &project_path, &mut scenes, path);
+
+    if scenes.instances.contains_key(path) {
+        scenes.open = Some(path.to_path_buf());
+        resources.get_mut::<SelectedEntity>().unwrap().0 = None;
+    }
+}
+
 pub fn left_panel_ui(ctx: &CtxRef, world: &World, resources: &Resources) {
     SidePanel::left("left_panel")
         .resizable(true)
@@ -245,25 +304,7 @@ pub fn left_panel_ui(ctx: &CtxRef, world: &World, resources: &Resources) {
 
             match res {
                 Ok(Some(path)) => match path.extension().map(|ext| ext.to_str().unwrap()) {
-                    Some("scn") => {
-                        if !scenes.instances.contains_key(&path) {
-                            let game = resources.get::<Game>().unwrap();
-                            let instance = resources.get::<Instance>().unwrap();
-                            let views = resources.get::<Assets<View>>().unwrap();
-                            let textures = resources.get::<Assets<RenderTexture>>().unwrap();
-
-                            match unsafe { game.load_scene(&instance, &views, &textures, &path) } {
-                                Ok(loaded_scene) => {
-                                    scenes
-                                        .instances
-                                        .insert(path.clone(), ManuallyDrop::new(loaded_scene));
-                                }
-                                Err(err) => {
-                                    log::error!("{}", err);
-                                }
-                            };
-                        }
-                    }
+                    Some("scn") => load_scene(resources, &project, &project_path, scenes, &path),
                     _ => {}
                 },
                 Err(err) => log::error!("error showing files: {}", err),
@@ -279,6 +320,69 @@ pub fn right_panel_ui(ctx: &CtxRef, world: &World, resources: &Resources) {
             ui.label("Inspector");
 
             ui.separator();
+
+            let mut scenes = resources.get_mut::<Scenes>().unwrap();
+            let selected_entity = resources.get::<SelectedEntity>().unwrap();
+            let mut selected_asset = resources.get_mut::<SelectedAsset>().unwrap();
+
+            if selected_entity.0.is_some() {
+                selected_asset.0 = None;
+            }
+
+            if let Some(path) = selected_asset.0.clone() {
+                ui.horizontal(|ui| {
+                    ui.label(path.display().to_string());
+
+                    if ui.small_button("✖").on_hover_text("Close").clicked() {
+                        selected_asset.0 = None;
+                    }
+                });
+
+                ui.separator();
+
+                let open = match &scenes.open {
+                    Some(open) => open.clone(),
+                    None => {
+                        ui.label("Open a scene to edit assets with its game");
+                        return;
+                    }
+                };
+
+                let app = &mut scenes.instances.get_mut(&open).unwrap().app;
+
+                ScrollArea::auto_sized().show(ui, |ui| {
+                    let edited = (app.edit_asset)(
+                        &mut app.world,
+                        &mut app.resources,
+                        &app.spawners,
+                        &path,
+                        ui,
+                    );
+
+                    if edited.is_none() {
+                        ui.label("The game can't edit this asset");
+                    }
+                });
+
+                return;
+            }
+
+            let (open, entity) = match (&scenes.open, selected_entity.0) {
+                (Some(open), Some(entity)) => (open.clone(), entity),
+                _ => return,
+            };
+
+            let app = &mut scenes.instances.get_mut(&open).unwrap().app;
+
+            ScrollArea::auto_sized().show(ui, |ui| {
+                (app.inspect_entity)(
+                    &mut app.world,
+                    &app.resources,
+                    &app.inspectables,
+                    &entity,
+                    ui,
+                );
+            });
         });
 }
 
@@ -286,14 +390,68 @@ pub fn bottom_panel_ui(ctx: &CtxRef, world: &World, resources: &Resources) {
     TopBottomPanel::bottom("bottom_panel")
         .resizable(true)
         .show(ctx, |ui| {
-            ui.label("Assets");
+            let server = resources.get::<AssetServer>().unwrap();
+            let mut browser = resources.get_mut::<AssetBrowser>().unwrap();
 
-            ui.horizontal(|ui| {});
+            browser.scan(&server, false);
 
-            ui.separator();
+            {
+                let instance = resources.get::<Instance>().unwrap();
+                let mut textures = resources.get_mut::<Assets<TextureView>>().unwrap();
+
+                browser.update_thumbnails(&server, &instance, &mut textures);
+
+                let game = resources.get::<Game>().unwrap();
+                let mut scenes = resources.get_mut::<Scenes>().unwrap();
+                let project = resources.get::<Project>().unwrap();
+                let project_path = resources.get::<ProjectPath>().unwrap();
+                let settings = project.run_settings(project_path.dir());
+
+                browser.update_mesh_thumbnails(
+                    &game,
+                    &mut scenes,
+                    &instance,
+                    &mut textures,
+                    &settings,
+                );
+            }
+
+            if let Some(path) = browser.ui(ui, &server) {
+                if AssetKind::from_path(&path) == AssetKind::Scene {
+                    open_scene(resources, &server.resolve(&path));
+                } else {
+                    resources.get_mut::<SelectedEntity>().unwrap().0 = None;
+                    resources.get_mut::<SelectedAsset>().unwrap().0 = Some(path);
+                }
+            }
         });
 }
 
+/// Opens dropped scenes and spawns other dropped assets into the open scene with the
+/// spawners registered by the game.
+fn drop_asset(resources: &Resources, path: &Path) {
+    let resolved = resources.get::<AssetServer>().unwrap().resolve(path);
+
+    if AssetKind::from_path(path) == AssetKind::Scene {
+        open_scene(resources, &resolved);
+        return;
+    }
+
+    let mut scenes = resources.get_mut::<Scenes>().unwrap();
+
+    let open = match &scenes.open {
+        Some(open) => open.clone(),
+        None => return,
+    };
+
+    let app = &mut scenes.instances.get_mut(&open).unwrap().app;
+
+    match (app.spawn_asset)(&mut app.world, &mut app.resources, &app.spawners, path) {
+        Some(entity) => resources.get_mut::<SelectedEntity>().unwrap().0 = Some(entity),
+        None => log::warn!("the game can't spawn '{}'", path.display()),
+    }
+}
+
 pub fn scene_panel_ui(ctx: &CtxRef, world: &World, resources: &Resources) {
     let mut scenes = resources.get_mut::<Scenes>().unwrap();
 
@@ -326,7 +484,7 @@ pub fn scene_panel_ui(ctx: &CtxRef, world: &World, resources: &Resources) {
 }
 
 pub fn main_panel_ui(ctx: &CtxRef, world: &World, resources: &Resources) {
-    CentralPanel::default().show(ctx, |ui| {
+    let dropped = CentralPanel::default().show(ctx, |ui| {
         let mut render_textures = resources.get_mut::<Assets<RenderTexture>>().unwrap();
         let views = resources.get::<Assets<View>>().unwrap();
         let view = views.get(&PRIMARY_VIEW).unwrap();
@@ -346,9 +504,15 @@ pub fn main_panel_ui(ctx: &CtxRef, world: &World, resources: &Resources) {
             *textures.get_mut(&view.texture.clone().cast()).unwrap() = texture.texture.view();
         }
 
-        ui.image(
+        let response = ui.image(
             TextureId::User(view.texture.clone().unwrap_id()),
             image_size,
         );
+
+        dropped_asset(ui, &response)
     });
+
+    if let Some(asset) = dropped.inner {
+        drop_asset(resources, &asset.path);
+    }
 }
@@ -0,0 +1,210 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use clap::Clap;
+
+use crate::project::{GameSettings, Package, Project, WindowSettings};
+
+const MAIN_SCENE: &str = "scenes/main.scn";
+
+const CARGO_TOML: &str = include_str!("../templates/Cargo.toml.template");
+const GITIGNORE: &str = include_str!("../templates/gitignore.template");
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Template {
+    Empty,
+    D3,
+    D2,
+}
+
+impl Default for Template {
+    #[inline]
+    fn default() -> Self {
+        Self::D3
+    }
+}
+
+impl FromStr for Template {
+    type Err = String;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "empty" => Ok(Self::Empty),
+            "3d" => Ok(Self::D3),
+            "2d" => Ok(Self::D2),
+            _ => Err(format!(
+                "unknown template '{}', expected 'empty', '3d' or '2d'",
+                s
+            )),
+        }
+    }
+}
+
+impl Template {
+    #[inline]
+    fn lib(self) -> &'static str {
+        match self {
+            Self::Empty => include_str!("../templates/empty/lib.rs"),
+            Self::D3 => include_str!("../templates/3d/lib.rs"),
+            Self::D2 => include_str!("../templates/2d/lib.rs"),
+        }
+    }
+
+    #[inline]
+    fn scene(self) -> &'static str {
+        match self {
+            Self::Empty => include_str!("../templates/empty/main.scn"),
+            Self::D3 => include_str!("../templates/3d/main.scn"),
+            Self::D2 => include_str!("../templates/2d/main.scn"),
+        }
+    }
+
+    #[inline]
+    fn uses_3d(self) -> bool {
+        self != Self::Empty
+    }
+}
+
+#[derive(Clap)]
+pub struct NewOpts {
+    /// Directory to create the project in, its name is used as the package name.
+    pub path: PathBuf,
+    /// Template to start from, one of 'empty', '3d' or '2d'.
+    #[clap(long, default_value = "3d")]
+    pub template: Template,
+    /// Path to the nova repository the game depends on.
+    #[clap(long)]
+    pub nova_path: Option<PathBuf>,
+}
+
+/// Path to the nova repository the editor was built from.
+#[inline]
+pub fn default_nova_path() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("..").join("..")
+}
+
+/// Checks that `name` is usable as a package name.
+fn verify_name(name: &str) -> Result<(), String> {
+    let valid = name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+    if name.is_empty() || !valid || name.starts_with(|c: char| c.is_ascii_digit()) {
+        return Err(format!("'{}' isn't a valid package name", name));
+    }
+
+    Ok(())
+}
+
+/// Formats `path` as a toml string.
+#[inline]
+fn toml_path(path: &Path) -> String {
+    toml::Value::String(path.to_string_lossy().replace('\\', "/")).to_string()
+}
+
+fn cargo_toml(name: &str, nova_path: &Path, template: Template) -> String {
+    let mut dependencies = format!("nova-game = {{ path = {} }}", toml_path(nova_path));
+
+    if template.uses_3d() {
+        let nova_3d = nova_path.join("crates").join("nova-3d");
+        dependencies += &format!(
+            "\nnova-3d = {{ path = {}, features = [\"editor\"] }}",
+            toml_path(&nova_3d)
+        );
+    }
+
+    CARGO_TOML
+        .replace("{{name}}", name)
+        .replace("{{dependencies}}", &dependencies)
+}
+
+/// Writes `contents` to `dir/path` unless the file already exists.
+fn write_new(dir: &Path, path: &str, contents: &str) -> Result<(), std::io::Error> {
+    let path = dir.join(path);
+
+    if path.exists() {
+        log::info!("keeping existing file: {}", path.display());
+        return Ok(());
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(path, contents)
+}
+
+/// Writes every file of `template` that doesn't already exist in `dir`.
+pub fn scaffold(
+    dir: &Path,
+    name: &str,
+    template: Template,
+    nova_path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    verify_name(name)?;
+
+    let nova_path = match fs::canonicalize(nova_path) {
+        Ok(path) if path.join("Cargo.toml").exists() => path,
+        _ => {
+            return Err(format!(
+                "couldn't find nova at '{}', pass --nova-path",
+                nova_path.display()
+            )
+            .into())
+        }
+    };
+
+    fs::create_dir_all(dir)?;
+
+    let project_path = dir.join("Nova.toml");
+
+    if !project_path.exists() {
+        let project = Project {
+            package: Package {
+                name: String::from(name),
+            },
+            game: Some(GameSettings {
+                main_scene: Some(String::from(MAIN_SCENE)),
+            }),
+            window: Some(WindowSettings::default()),
+            ..Default::default()
+        };
+
+        project.write(&project_path)?;
+    }
+
+    write_new(dir, "Cargo.toml", &cargo_toml(name, &nova_path, template))?;
+    write_new(dir, ".gitignore", GITIGNORE)?;
+    write_new(dir, "src/lib.rs", template.lib())?;
+    write_new(dir, MAIN_SCENE, template.scene())?;
+
+    Ok(())
+}
+
+/// Creates a new project at `opts.path` from a template.
+pub fn new(opts: NewOpts) -> Result<(), Box<dyn std::error::Error>> {
+    if opts
+        .path
+        .read_dir()
+        .map_or(false, |mut dir| dir.next().is_some())
+    {
+        return Err(format!("'{}' already exists and isn't empty", opts.path.display()).into());
+    }
+
+    let name = match opts.path.file_name() {
+        Some(name) => name.to_string_lossy().into_owned(),
+        None => return Err(format!("'{}' has no directory name", opts.path.display()).into()),
+    };
+
+    let nova_path = opts.nova_path.unwrap_or_else(default_nova_path);
+
+    scaffold(&opts.path, &name, opts.template, &nova_path)?;
+
+    log::info!("created project '{}' at {}", name, opts.path.display());
+
+    Ok(())
+}
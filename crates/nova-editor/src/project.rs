@@ -1,13 +1,28 @@
+use glam::UVec2;
+use nova_engine::RunSettings;
 use serde::{Deserialize, Serialize};
 use std::{
     fs::read_to_string,
     path::{Path, PathBuf},
     time::SystemTime,
 };
+use toml::value::Table;
 
 pub struct ProjectPath(pub PathBuf);
 
 impl ProjectPath {
+    /// Resolves a path to either a project directory or a 'Nova.toml'.
+    #[inline]
+    pub fn resolve(path: &Path) -> Result<Self, std::io::Error> {
+        let mut path = std::fs::canonicalize(path)?;
+
+        if path.is_dir() {
+            path = path.join("Nova.toml");
+        }
+
+        Ok(Self(path))
+    }
+
     #[inline]
     pub fn dir(&self) -> &Path {
         self.0.parent().unwrap()
@@ -25,6 +40,23 @@ pub struct Package {
 pub struct BuildSettings {
     pub manifest_path: String,
     pub target_dir: String,
+    /// Cargo features enabled when building the game.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+    /// Cargo profile used for debug builds, release builds always use 'release'.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+}
+
+impl BuildSettings {
+    #[inline]
+    pub fn profile(&self, release: bool) -> &str {
+        if release {
+            "release"
+        } else {
+            self.profile.as_deref().unwrap_or("dev")
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -34,6 +66,134 @@ pub struct GameSettings {
     pub main_scene: Option<String>,
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ExportSettings {
+    pub out_dir: String,
+    /// Scenes exported in addition to the main scene.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scenes: Vec<String>,
+    /// Files or directories always exported, even if no scene references them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assets: Vec<String>,
+    /// Put the assets in the asset dirs into a single archive instead of loose files.
+    #[serde(default)]
+    pub pack_assets: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PresentMode {
+    Vsync,
+    Mailbox,
+    Immediate,
+}
+
+impl From<PresentMode> for nova_wgpu::PresentMode {
+    #[inline]
+    fn from(present_mode: PresentMode) -> Self {
+        match present_mode {
+            PresentMode::Vsync => Self::Fifo,
+            PresentMode::Mailbox => Self::Mailbox,
+            PresentMode::Immediate => Self::Immediate,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct WindowSettings {
+    /// Defaults to the package name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub width: u32,
+    pub height: u32,
+    pub present_mode: PresentMode,
+    pub fullscreen: bool,
+}
+
+impl Default for WindowSettings {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            title: None,
+            width: 1280,
+            height: 720,
+            present_mode: PresentMode::Vsync,
+            fullscreen: false,
+        }
+    }
+}
+
+impl WindowSettings {
+    /// Settings to run a game called `name` with, without render settings or bindings.
+    #[inline]
+    pub fn run_settings(&self, name: &str) -> RunSettings {
+        RunSettings {
+            title: self.title.clone().unwrap_or_else(|| String::from(name)),
+            size: UVec2::new(self.width, self.height),
+            present_mode: self.present_mode.into(),
+            fullscreen: self.fullscreen,
+            ..Default::default()
+        }
+    }
+}
+
+/// Overrides the settings the game passes to `RenderPlugin`.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct RenderSettings {
+    pub msaa: u32,
+    pub clear: [f32; 4],
+}
+
+impl Default for RenderSettings {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            msaa: 1,
+            clear: [0.0; 4],
+        }
+    }
+}
+
+impl From<&RenderSettings> for nova_render::render_settings::RenderSettings {
+    #[inline]
+    fn from(settings: &RenderSettings) -> Self {
+        Self {
+            msaa: settings.msaa,
+            clear: settings.clear.into(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct AssetSettings {
+    /// Directories assets are loaded from, relative to the project.
+    pub dirs: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct InputSettings {
+    /// Ron file with the input bindings, relative to the project.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bindings: Option<String>,
+}
+
+impl Default for ExportSettings {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            out_dir: String::from("export"),
+            scenes: Vec::new(),
+            assets: Vec::new(),
+            pack_assets: false,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct Project {
@@ -43,6 +203,36 @@ pub struct Project {
     pub build: BuildSettings,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub game: Option<GameSettings>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub export: Option<ExportSettings>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window: Option<WindowSettings>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub render: Option<RenderSettings>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assets: Option<AssetSettings>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input: Option<InputSettings>,
+    /// Overrides for a single os, keyed by `std::env::consts::OS`, e.g. `[platform.windows.window]`.
+    #[serde(default, skip_serializing_if = "Table::is_empty")]
+    pub platform: Table,
+}
+
+/// Merges the tables in `overrides` into `base`, replacing any other value.
+fn merge(base: &mut toml::Value, overrides: toml::Value) {
+    match (base, overrides) {
+        (toml::Value::Table(base), toml::Value::Table(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(&key) {
+                    Some(base) => merge(base, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overrides) => *base = overrides,
+    }
 }
 
 impl Default for Project {
@@ -56,13 +246,22 @@ impl Default for Project {
             build: BuildSettings {
                 manifest_path: String::from("Cargo.toml"),
                 target_dir: String::from("target"),
+                features: Vec::new(),
+                profile: None,
             },
             game: None,
+            export: None,
+            window: None,
+            render: None,
+            assets: None,
+            input: None,
+            platform: Table::new(),
         }
     }
 }
 
 impl Project {
+    /// Loads the project at `path` with the overrides for the current platform applied.
     #[inline]
     pub fn load(path: &Path) -> Result<Option<Self>, toml::de::Error> {
         let project_str = match read_to_string(path).ok() {
@@ -70,7 +269,18 @@ impl Project {
             None => return Ok(None),
         };
 
-        Ok(Some(toml::from_str(&project_str)?))
+        let mut project: toml::Value = toml::from_str(&project_str)?;
+
+        let overrides = project
+            .get("platform")
+            .and_then(|platform| platform.get(std::env::consts::OS))
+            .cloned();
+
+        if let Some(overrides) = overrides {
+            merge(&mut project, overrides);
+        }
+
+        Ok(Some(project.try_into()?))
     }
 
     #[inline]
@@ -83,6 +293,40 @@ impl Project {
         Path::new(&self.build.target_dir).iter().collect()
     }
 
+    #[inline]
+    pub fn asset_dirs(&self) -> Vec<PathBuf> {
+        match &self.assets {
+            Some(assets) => assets.dirs.iter().map(PathBuf::from).collect(),
+            None => vec![PathBuf::from("assets")],
+        }
+    }
+
+    #[inline]
+    pub fn bindings_path(&self) -> Option<PathBuf> {
+        Some(
+            Path::new(self.input.as_ref()?.bindings.as_ref()?)
+                .iter()
+                .collect(),
+        )
+    }
+
+    /// Settings to run the game with, paths are relative to `dir`.
+    #[inline]
+    pub fn run_settings(&self, dir: &Path) -> RunSettings {
+        let window = self.window.clone().unwrap_or_default();
+
+        RunSettings {
+            render: self.render.as_ref().map(Into::into),
+            bindings: self.bindings_path().map(|path| dir.join(path)),
+            asset_dirs: self
+                .asset_dirs()
+                .iter()
+                .map(|path| dir.join(path))
+                .collect(),
+            ..window.run_settings(&self.package.name)
+        }
+    }
+
     #[inline]
     pub fn main_scene_path(&self) -> Option<PathBuf> {
         Some(
@@ -0,0 +1,134 @@
+use std::path::{Path, PathBuf};
+
+use clap::Clap;
+use nova_engine::RunSettings;
+
+use crate::{
+    build::{build_command, lib_path, load_manifest},
+    export::{hash, ExportManifest, EXPORT_MANIFEST},
+    load::LoadedGame,
+    project::{Project, ProjectPath},
+    scenes::SceneInstance,
+};
+
+#[derive(Clap)]
+pub struct RunOpts {
+    /// Path to the root of the project or 'Nova.toml'.
+    pub path: PathBuf,
+    /// Build and run the game in release mode.
+    #[clap(long)]
+    pub release: bool,
+    /// Run in a window even if the project is set to fullscreen.
+    #[clap(long)]
+    pub windowed: bool,
+    /// Run without a window, rendering into a texture.
+    #[clap(long)]
+    pub headless: bool,
+    /// Exit after running this many frames.
+    #[clap(long)]
+    pub frames: Option<u64>,
+    /// Reload assets when their files change.
+    #[clap(long)]
+    pub watch: bool,
+}
+
+impl RunOpts {
+    /// Applies the command line overrides to `settings`.
+    #[inline]
+    pub fn apply(&self, settings: RunSettings) -> RunSettings {
+        RunSettings {
+            fullscreen: settings.fullscreen && !self.windowed && !self.headless,
+            headless: self.headless,
+            frames: self.frames,
+            watch_assets: self.watch,
+            ..settings
+        }
+    }
+}
+
+/// Builds the project at `opts.path` and runs its main scene without the editor, if
+/// `opts.path` is an exported game it's run directly.
+pub fn run(opts: RunOpts) -> Result<(), Box<dyn std::error::Error>> {
+    if opts.path.join(EXPORT_MANIFEST).exists() {
+        let dir = std::fs::canonicalize(&opts.path)?;
+        let manifest = ExportManifest::load(&dir)?;
+        let settings = opts.apply(manifest.run_settings(&dir));
+
+        return run_exported(&dir, manifest, settings);
+    }
+
+    let project_path = ProjectPath::resolve(&opts.path)?;
+
+    let project = match Project::load(&project_path.0)? {
+        Some(project) => project,
+        None => return Err(format!("no project at '{}'", project_path.0.display()).into()),
+    };
+
+    let scene_path = match project.main_scene_path() {
+        Some(main_scene) => project_path.dir().join(main_scene),
+        None => return Err("project has no 'game.main-scene' to run".into()),
+    };
+
+    let manifest_path = project_path.dir().join(project.manifest_path());
+    let target_dir = project_path.dir().join(project.target_dir());
+
+    let manifest = load_manifest(&manifest_path)?;
+
+    log::info!("building game lib");
+
+    let status =
+        build_command(&manifest_path, &target_dir, &project.build, opts.release).status()?;
+
+    if !status.success() {
+        return Err("failed to build game".into());
+    }
+
+    let lib_path = lib_path(&manifest, &target_dir, &project.build, opts.release);
+
+    log::info!("loading game lib: {}", lib_path.display());
+
+    let game = unsafe { LoadedGame::load(&lib_path)? };
+
+    let settings = opts.apply(project.run_settings(project_path.dir()));
+
+    log::info!("running scene: {}", scene_path.display());
+
+    unsafe {
+        game.run(settings, &mut |app| {
+            SceneInstance::deserialize(app, &scene_path).map(drop)
+        })?
+    };
+
+    Ok(())
+}
+
+/// Runs a game exported to `dir`, the lib is only loaded if it matches the manifest.
+pub fn run_exported(
+    dir: &Path,
+    manifest: ExportManifest,
+    settings: RunSettings,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = std::fs::canonicalize(dir)?;
+    let lib_path = dir.join(&manifest.lib.path);
+
+    if hash(&std::fs::read(&lib_path)?) != manifest.lib.sha256 {
+        return Err(format!("'{}' doesn't match {}", lib_path.display(), EXPORT_MANIFEST).into());
+    }
+
+    let game = unsafe { LoadedGame::load(&lib_path)? };
+
+    // relative paths used by the game are relative to the export
+    std::env::set_current_dir(&dir)?;
+
+    let scene_path = dir.join(&manifest.main_scene);
+
+    log::info!("running scene: {}", scene_path.display());
+
+    unsafe {
+        game.run(settings, &mut |app| {
+            SceneInstance::deserialize(app, &scene_path).map(drop)
+        })?
+    };
+
+    Ok(())
+}
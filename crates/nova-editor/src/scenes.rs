@@ -43,13 +43,27 @@ pub struct SceneInstance {
 
 impl SceneInstance {
     pub fn load(mut app: App, path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::deserialize(&mut app, path)?;
+
+        Ok(Self { app })
+    }
+
+    /// Deserializes the scene at `path` into the world of `app`, returns the asset files it
+    /// depends on.
+    pub fn deserialize(
+        app: &mut App,
+        path: &Path,
+    ) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
         let scene_data = read_to_string(path)?;
         let mut deserializer = ron::Deserializer::from_str(&scene_data)?;
         let mut deserializer = <dyn Deserializer>::erase(&mut deserializer);
 
-        (app.deserialize)(&mut app.world, &app.registry, &mut deserializer)?;
-
-        Ok(Self { app })
+        (app.deserialize)(
+            &mut app.world,
+            &app.resources,
+            &app.registry,
+            &mut deserializer,
+        )
     }
 
     /// For safety reasons, we must drop the app in the apps code.
@@ -64,6 +78,8 @@ pub struct Scenes {
     pub instances: HashMap<PathBuf, ManuallyDrop<SceneInstance>>,
     pub open: Option<PathBuf>,
     pub running: bool,
+    /// App of the game the asset browser renders mesh thumbnails with.
+    pub thumbnails: Option<ManuallyDrop<SceneInstance>>,
 }
 
 impl Scenes {
@@ -72,6 +88,10 @@ impl Scenes {
             ManuallyDrop::into_inner(instance).unload();
         }
 
+        if let Some(thumbnails) = self.thumbnails.take() {
+            ManuallyDrop::into_inner(thumbnails).unload();
+        }
+
         self.open = None;
         self.running = false;
     }
@@ -0,0 +1,50 @@
+use nova_3d::{D3Plugin, Vertex3d};
+use nova_game::prelude::*;
+
+pub const QUAD_MESH: Handle<MeshData> = Handle::from_u64(5710293847561);
+
+/// A unit quad facing the camera.
+fn quad() -> Mesh<Vertex3d> {
+    let corners = [
+        Vec2::new(-0.5, -0.5),
+        Vec2::new(0.5, -0.5),
+        Vec2::new(0.5, 0.5),
+        Vec2::new(-0.5, 0.5),
+    ];
+
+    let mut mesh = Mesh::default();
+
+    for corner in corners.iter() {
+        mesh.vertices.push(Vertex3d {
+            position: corner.extend(0.0),
+            normal: Vec3::Z,
+            uv: Vec2::new(corner.x + 0.5, 0.5 - corner.y),
+            color: Color::WHITE,
+        });
+    }
+
+    mesh.indices.extend_from_slice(&[0, 1, 2, 2, 3, 0]);
+
+    mesh
+}
+
+fn build(app: &mut AppBuilder) {
+    app.register_component::<String>();
+
+    app.with_plugin(TransformPlugin);
+    app.with_plugin(InputPlugin);
+    app.with_plugin(RenderPlugin::default());
+    app.with_plugin(D3Plugin);
+
+    app.insert_resource(AmbientLight {
+        color: Color::WHITE,
+        intensity: 0.2,
+    });
+
+    app.resources
+        .get_mut::<Assets<MeshData>>()
+        .unwrap()
+        .insert_untracked(QUAD_MESH, quad().into());
+}
+
+export_app!(build);
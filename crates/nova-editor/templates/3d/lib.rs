@@ -0,0 +1,22 @@
+use nova_3d::{shape::Cube, D3Plugin, Vertex3d};
+use nova_game::prelude::*;
+
+pub const CUBE_MESH: Handle<MeshData> = Handle::from_u64(8392740128374);
+
+fn build(app: &mut AppBuilder) {
+    app.register_component::<String>();
+
+    app.with_plugin(TransformPlugin);
+    app.with_plugin(InputPlugin);
+    app.with_plugin(RenderPlugin::default());
+    app.with_plugin(D3Plugin);
+
+    let cube: Mesh<Vertex3d> = Cube { size: Vec3::ONE }.into();
+
+    app.resources
+        .get_mut::<Assets<MeshData>>()
+        .unwrap()
+        .insert_untracked(CUBE_MESH, cube.into());
+}
+
+export_app!(build);
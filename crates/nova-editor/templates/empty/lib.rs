@@ -0,0 +1,11 @@
+use nova_game::prelude::*;
+
+fn build(app: &mut AppBuilder) {
+    app.register_component::<String>();
+
+    app.with_plugin(TransformPlugin);
+    app.with_plugin(InputPlugin);
+    app.with_plugin(RenderPlugin::default());
+}
+
+export_app!(build);
@@ -1,19 +1,138 @@
+use std::path::{Path, PathBuf};
+
+use glam::UVec2;
+use nova_assets::{
+    pack::PackedAssetIo,
+    server::{AssetServer, DEFAULT_WATCH_INTERVAL},
+};
 use nova_core::{App, AppBuilder};
-use nova_input::{key::Key, mouse_button::MouseButton, Input, Mouse, TextInput};
-use nova_render::{render_node::Target, render_target::RenderTarget, renderer::Renderer};
+use nova_input::{
+    bindings::Bindings, key::Key, mouse_button::MouseButton, Input, Mouse, TextInput,
+};
+use nova_render::{
+    render_node::Target, render_settings::RenderSettings, render_target::RenderTarget,
+    renderer::Renderer,
+};
 use nova_wgpu::{
     instance::Instance,
     wgpu_impl::{WgpuInstance, WgpuSwapChain},
-    SwapChain,
+    Extent3d, PresentMode, SwapChain, SwapChainError, TextureDescriptor, TextureDimension,
+    TextureFormat, TextureUsage,
 };
 use nova_window::Windows;
 use winit::{
+    dpi::LogicalSize,
     event::{ElementState, Event, KeyboardInput, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
-    window::{Window, WindowBuilder},
+    window::{Fullscreen, Window, WindowBuilder},
 };
 
-pub async fn init_wgpu(window: &Window) -> (Instance, SwapChain) {
+#[derive(Clone, Debug)]
+pub struct RunSettings {
+    pub title: String,
+    /// Size of the window, or of the render target when headless.
+    pub size: UVec2,
+    pub present_mode: PresentMode,
+    pub fullscreen: bool,
+    /// Render into a texture instead of a window.
+    pub headless: bool,
+    /// Exit after this many frames.
+    pub frames: Option<u64>,
+    /// Replaces the settings passed to `RenderPlugin`.
+    pub render: Option<RenderSettings>,
+    /// Ron file to load the [`Bindings`] from.
+    pub bindings: Option<PathBuf>,
+    /// Roots of the [`AssetServer`], its default roots are kept if empty.
+    pub asset_dirs: Vec<PathBuf>,
+    /// Archive to load assets from instead of `asset_dirs`.
+    pub asset_pack: Option<PathBuf>,
+    /// Reload assets when their files change.
+    pub watch_assets: bool,
+}
+
+impl Default for RunSettings {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            title: String::from("Nova"),
+            size: UVec2::new(1280, 720),
+            present_mode: PresentMode::Fifo,
+            fullscreen: false,
+            headless: false,
+            frames: None,
+            render: None,
+            bindings: None,
+            asset_dirs: Vec::new(),
+            asset_pack: None,
+            watch_assets: false,
+        }
+    }
+}
+
+#[inline]
+pub fn load_bindings(path: &Path) -> Result<Bindings, Box<dyn std::error::Error>> {
+    let bindings = std::fs::read_to_string(path)?;
+    Ok(ron::from_str(&bindings)?)
+}
+
+/// Inserts the resources described by `settings` into `app`, call before adding plugins.
+pub fn insert_settings(app: &mut AppBuilder, settings: &RunSettings) {
+    if let Some(render) = &settings.render {
+        app.insert_resource(render.clone());
+    }
+
+    if let Some(path) = &settings.bindings {
+        match load_bindings(path) {
+            Ok(bindings) => {
+                app.insert_resource(bindings);
+            }
+            Err(err) => log::error!("failed to load bindings '{}': {}", path.display(), err),
+        }
+    }
+
+    app.register_resource::<AssetServer>();
+    let mut asset_server = app.resources.get_mut::<AssetServer>().unwrap();
+
+    if !settings.asset_dirs.is_empty() {
+        asset_server.set_roots(settings.asset_dirs.clone());
+    }
+
+    if let Some(path) = &settings.asset_pack {
+        match PackedAssetIo::open(path) {
+            Ok(io) => asset_server.set_io(io),
+            Err(err) => log::error!("failed to open asset pack '{}': {}", path.display(), err),
+        }
+    }
+
+    if settings.watch_assets {
+        asset_server.watch_for_changes(DEFAULT_WATCH_INTERVAL);
+    }
+}
+
+struct HeadlessWindow {
+    size: UVec2,
+}
+
+impl nova_window::Window for HeadlessWindow {
+    #[inline]
+    fn request_redraw(&self) {}
+
+    #[inline]
+    fn size(&self) -> UVec2 {
+        self.size
+    }
+}
+
+fn init_logger() {
+    let _ = simple_logger::SimpleLogger::new()
+        .with_module_level("gfx", log::LevelFilter::Error)
+        .with_module_level("wgpu", log::LevelFilter::Error)
+        .with_module_level("winit", log::LevelFilter::Error)
+        .with_module_level("naga", log::LevelFilter::Error)
+        .init();
+}
+
+pub async fn init_wgpu(window: &Window, present_mode: PresentMode) -> (Instance, SwapChain) {
     let instance = wgpu::Instance::new(wgpu::BackendBit::PRIMARY);
     let surface = unsafe { instance.create_surface(window) };
     let adapter = instance
@@ -43,7 +162,7 @@ pub async fn init_wgpu(window: &Window) -> (Instance, SwapChain) {
         format: adapter.get_swap_chain_preferred_format(&surface).unwrap(),
         width: size.width,
         height: size.height,
-        present_mode: wgpu::PresentMode::Fifo,
+        present_mode,
     };
 
     let sc = WgpuSwapChain::new(&device, surface, desc);
@@ -51,32 +170,149 @@ pub async fn init_wgpu(window: &Window) -> (Instance, SwapChain) {
     (WgpuInstance::new(device, queue).into(), sc.into())
 }
 
+pub async fn init_wgpu_headless() -> Instance {
+    let instance = wgpu::Instance::new(wgpu::BackendBit::PRIMARY);
+    let adapter = instance
+        .request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            compatible_surface: None,
+        })
+        .await
+        .unwrap();
+
+    let (device, queue) = adapter
+        .request_device(
+            &wgpu::DeviceDescriptor {
+                features: wgpu::Features::empty(),
+                limits: wgpu::Limits::default(),
+                label: None,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+
+    WgpuInstance::new(device, queue).into()
+}
+
+/// Renders the [`RenderTarget`] with the [`Renderer`] if there is one.
+fn render_frame(app: &App) -> Result<(), SwapChainError> {
+    if let Some(mut renderer) = app.resources.get_mut::<Renderer>() {
+        let render_target = app.resources.get::<RenderTarget>().unwrap();
+
+        render_target.view(|view| {
+            let target = Target {
+                view,
+                depth: None,
+                size: render_target.size(),
+                format: render_target.format(),
+            };
+
+            renderer.render_view(&app.world, &app.resources, &target);
+        })?;
+    }
+
+    Ok(())
+}
+
 #[inline]
 pub fn run(title: &str, func: impl FnOnce(AppBuilder) -> App) -> ! {
-    let _ = simple_logger::SimpleLogger::new()
-        .with_module_level("gfx", log::LevelFilter::Error)
-        .with_module_level("wgpu", log::LevelFilter::Error)
-        .with_module_level("winit", log::LevelFilter::Error)
-        .with_module_level("naga", log::LevelFilter::Error)
-        .init();
+    run_with_settings(
+        RunSettings {
+            title: String::from(title),
+            ..Default::default()
+        },
+        func,
+    )
+}
+
+/// Runs without a window, rendering every frame into a texture.
+pub fn run_headless(settings: RunSettings, func: impl FnOnce(AppBuilder) -> App) -> ! {
+    init_logger();
+
+    let instance = pollster::block_on(init_wgpu_headless());
+
+    let size = settings.size;
+
+    let desc = TextureDescriptor {
+        label: Some("headless_target"),
+        size: Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: TextureFormat::Rgba8UnormSrgb,
+        usage: TextureUsage::RENDER_ATTACHMENT,
+    };
+
+    let view = instance.create_texture(&desc).view();
+
+    let mut app = AppBuilder::new();
+
+    app.insert_resource(instance);
+    app.insert_resource(RenderTarget::Texture { view, desc });
+    app.insert_resource(Windows::new(HeadlessWindow { size }));
+    insert_settings(&mut app, &settings);
+
+    let mut app = func(app);
+
+    app.startup_schedule
+        .execute(&mut app.world, &mut app.resources);
+
+    let mut frame = 0;
+
+    while settings.frames.map_or(true, |frames| frame < frames) {
+        app.schedule.execute(&mut app.world, &mut app.resources);
+
+        if let Err(err) = render_frame(&app) {
+            log::error!("failed to render frame {}: {:?}", frame, err);
+        }
+
+        frame += 1;
+    }
+
+    log::info!("ran {} frames", frame);
+
+    std::process::exit(0);
+}
+
+pub fn run_with_settings(settings: RunSettings, func: impl FnOnce(AppBuilder) -> App) -> ! {
+    if settings.headless {
+        run_headless(settings, func);
+    }
+
+    init_logger();
 
     let event_loop = EventLoop::new();
-    let window_builder = WindowBuilder::new().with_title(title);
+    let mut window_builder = WindowBuilder::new()
+        .with_title(&settings.title)
+        .with_inner_size(LogicalSize::new(settings.size.x, settings.size.y));
+
+    if settings.fullscreen {
+        window_builder = window_builder.with_fullscreen(Some(Fullscreen::Borderless(None)));
+    }
+
     let window = window_builder.build(&event_loop).unwrap();
 
-    let (instance, sc) = pollster::block_on(init_wgpu(&window));
+    let (instance, sc) = pollster::block_on(init_wgpu(&window, settings.present_mode));
 
     let mut app = AppBuilder::new();
 
     app.insert_resource(instance);
     app.insert_resource(RenderTarget::SwapChain(sc));
     app.insert_resource(Windows::new(window));
+    insert_settings(&mut app, &settings);
 
     let mut app = func(app);
 
     app.startup_schedule
         .execute(&mut app.world, &mut app.resources);
 
+    let mut frame_count = 0;
+
     event_loop.run(move |event, _, control_flow| match event {
         Event::MainEventsCleared => {
             app.resources
@@ -88,20 +324,17 @@ pub fn run(title: &str, func: impl FnOnce(AppBuilder) -> App) -> ! {
         Event::RedrawRequested(_) => {
             app.schedule.execute(&mut app.world, &mut app.resources);
 
-            if let Some(mut renderer) = app.resources.get_mut::<Renderer>() {
-                let swap_chain = app.resources.get::<SwapChain>().unwrap();
-                let frame = swap_chain.get_current_frame().unwrap();
-
-                let target = Target {
-                    view: &frame.output.view(),
-                    depth: None,
-                    size: swap_chain.size().into(),
-                    format: swap_chain.format(),
-                };
+            if let Err(err) = render_frame(&app) {
+                log::error!("failed to render frame {}: {:?}", frame_count, err);
+            }
 
-                drop(swap_chain);
+            frame_count += 1;
 
-                renderer.render_view(&app.world, &app.resources, &target);
+            if settings
+                .frames
+                .map_or(false, |frames| frame_count >= frames)
+            {
+                *control_flow = ControlFlow::Exit;
             }
         }
         Event::WindowEvent { event, .. } => match event {
@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{key::Key, mouse_button::MouseButton, Input};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Binding {
+    Key(Key),
+    Mouse(MouseButton),
+}
+
+/// Maps action names to the keys and mouse buttons that trigger them.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Bindings {
+    pub actions: HashMap<String, Vec<Binding>>,
+}
+
+impl Bindings {
+    #[inline]
+    pub fn bind(&mut self, action: impl Into<String>, binding: Binding) -> &mut Self {
+        self.actions.entry(action.into()).or_default().push(binding);
+
+        self
+    }
+
+    #[inline]
+    pub fn get(&self, action: &str) -> &[Binding] {
+        self.actions.get(action).map_or(&[], |bindings| bindings)
+    }
+
+    #[inline]
+    fn any(
+        &self,
+        action: &str,
+        keys: &Input<Key>,
+        mouse: &Input<MouseButton>,
+        f: impl Fn(&Input<Key>, &Input<MouseButton>, &Binding) -> bool,
+    ) -> bool {
+        self.get(action)
+            .iter()
+            .any(|binding| f(keys, mouse, binding))
+    }
+
+    #[inline]
+    pub fn pressed(&self, action: &str, keys: &Input<Key>, mouse: &Input<MouseButton>) -> bool {
+        self.any(action, keys, mouse, |keys, mouse, binding| match binding {
+            Binding::Key(key) => keys.pressed(key),
+            Binding::Mouse(button) => mouse.pressed(button),
+        })
+    }
+
+    #[inline]
+    pub fn down(&self, action: &str, keys: &Input<Key>, mouse: &Input<MouseButton>) -> bool {
+        self.any(action, keys, mouse, |keys, mouse, binding| match binding {
+            Binding::Key(key) => keys.down(key),
+            Binding::Mouse(button) => mouse.down(button),
+        })
+    }
+
+    #[inline]
+    pub fn released(&self, action: &str, keys: &Input<Key>, mouse: &Input<MouseButton>) -> bool {
+        self.any(action, keys, mouse, |keys, mouse, binding| match binding {
+            Binding::Key(key) => keys.released(key),
+            Binding::Mouse(button) => mouse.released(button),
+        })
+    }
+}
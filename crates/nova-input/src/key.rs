@@ -1,5 +1,7 @@
 // almost entirely directly copied from winit (https://github.com/rust-windowing/winit/blob/master/src/event.rs)
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
 pub enum Key {
     /// The '1' key over the letters.
     Key1,
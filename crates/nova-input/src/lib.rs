@@ -1,6 +1,8 @@
+pub mod bindings;
 pub mod key;
 pub mod mouse_button;
 
+use bindings::Bindings;
 use glam::Vec2;
 use key::Key;
 use mouse_button::MouseButton;
@@ -131,6 +133,7 @@ impl Plugin for InputPlugin {
         app.register_resource::<Input<MouseButton>>();
         app.register_resource::<TextInput>();
         app.register_resource::<Mouse>();
+        app.register_resource::<Bindings>();
 
         app.add_system_to_stage(stage::END, input_system());
         #[cfg(feature = "editor")]
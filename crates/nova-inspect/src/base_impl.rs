@@ -5,6 +5,7 @@ use crate::Inspectable;
 use egui::{DragValue, Response, Ui, Vec2};
 use glam::{IVec2, IVec3, IVec4, Quat, UVec2, UVec3, UVec4, Vec3, Vec4};
 use legion::Entity;
+use uuid::Uuid;
 
 macro_rules! impl_num {
     ($ty:ty) => {
@@ -65,6 +66,29 @@ impl Inspectable for PathBuf {
     }
 }
 
+impl Inspectable for Uuid {
+    #[inline]
+    fn name(&self) -> &'static str {
+        "Uuid"
+    }
+
+    /// Only valid uuids are applied, so they're best pasted.
+    #[inline]
+    fn inspect(&mut self, ui: &mut Ui) -> Option<Response> {
+        let mut string = self.to_string();
+
+        let response = string.inspect(ui)?;
+
+        if response.changed() {
+            if let Ok(uuid) = Uuid::parse_str(string.trim()) {
+                *self = uuid;
+            }
+        }
+
+        Some(response)
+    }
+}
+
 impl Inspectable for bool {
     #[inline]
     fn name(&self) -> &'static str {
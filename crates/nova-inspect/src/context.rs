@@ -0,0 +1,35 @@
+use std::{cell::Cell, ptr};
+
+use legion::Resources;
+
+thread_local! {
+    static RESOURCES: Cell<*const Resources> = Cell::new(ptr::null());
+}
+
+/// Makes `resources` available to [`Inspectable`](crate::Inspectable)s through
+/// [`with_resources`] while `f` runs, e.g. so handles can list the assets they can point to.
+#[inline]
+pub fn inspect_with<R>(resources: &Resources, f: impl FnOnce() -> R) -> R {
+    struct Restore(*const Resources);
+
+    impl Drop for Restore {
+        #[inline]
+        fn drop(&mut self) {
+            RESOURCES.with(|resources| resources.set(self.0));
+        }
+    }
+
+    let _restore = Restore(RESOURCES.with(|current| current.replace(resources)));
+
+    f()
+}
+
+/// Calls `f` with the resources of the app being inspected, `None` if not called within
+/// [`inspect_with`].
+#[inline]
+pub fn with_resources<R>(f: impl FnOnce(&Resources) -> R) -> Option<R> {
+    let resources = RESOURCES.with(Cell::get);
+
+    // SAFETY: the pointer is only set while `inspect_with` borrows the resources
+    unsafe { resources.as_ref() }.map(f)
+}
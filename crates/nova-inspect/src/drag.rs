@@ -0,0 +1,60 @@
+use std::path::PathBuf;
+
+use egui::{CtxRef, Id, Response, Ui};
+
+/// Asset dragged from the editor asset browser, dropped on inspector fields or the viewport.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DraggedAsset {
+    /// Path relative to the asset roots.
+    pub path: PathBuf,
+    /// Uuid from the meta file of the asset, if it has one.
+    pub uuid: Option<String>,
+}
+
+// stored as std types since the editor and game libs don't agree on type ids of nova types
+type Stored = (PathBuf, Option<String>);
+
+#[inline]
+fn id() -> Id {
+    Id::new("nova_dragged_asset")
+}
+
+#[inline]
+pub fn set_dragged_asset(ctx: &CtxRef, asset: Option<DraggedAsset>) {
+    let mut memory = ctx.memory();
+
+    match asset {
+        Some(asset) => memory
+            .id_data_temp
+            .insert::<Stored>(id(), (asset.path, asset.uuid)),
+        None => memory.id_data_temp.remove(&id()),
+    }
+}
+
+#[inline]
+pub fn dragged_asset(ctx: &CtxRef) -> Option<DraggedAsset> {
+    let (path, uuid) = ctx.memory().id_data_temp.get::<Stored>(&id())?.clone();
+
+    Some(DraggedAsset { path, uuid })
+}
+
+/// Returns the asset released over `response` this frame, and highlights `response` while
+/// an asset is dragged over it.
+#[inline]
+pub fn dropped_asset(ui: &Ui, response: &Response) -> Option<DraggedAsset> {
+    let asset = dragged_asset(ui.ctx())?;
+
+    if !ui.rect_contains_pointer(response.rect) {
+        return None;
+    }
+
+    if ui.input().pointer.any_released() {
+        Some(asset)
+    } else {
+        let stroke = ui.visuals().selection.stroke;
+        ui.painter()
+            .rect_stroke(response.rect.expand(1.0), 2.0, stroke);
+
+        None
+    }
+}
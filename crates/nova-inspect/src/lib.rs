@@ -1,4 +1,6 @@
 pub mod base_impl;
+pub mod context;
+pub mod drag;
 
 #[doc(hidden)]
 pub use egui;
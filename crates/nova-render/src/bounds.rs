@@ -0,0 +1,141 @@
+use glam::Vec3;
+
+/// Axis aligned bounding box.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    /// Smallest box containing `points`, `None` if there are none.
+    #[inline]
+    pub fn from_points(points: impl IntoIterator<Item = Vec3>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+
+        Some(points.fold(Self::new(first, first), |aabb, point| Self {
+            min: aabb.min.min(point),
+            max: aabb.max.max(point),
+        }))
+    }
+
+    #[inline]
+    pub const fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    #[inline]
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) / 2.0
+    }
+
+    #[inline]
+    pub fn half_extents(&self) -> Vec3 {
+        (self.max - self.min) / 2.0
+    }
+
+    #[inline]
+    pub fn contains(&self, point: Vec3) -> bool {
+        point.cmpge(self.min).all() && point.cmple(self.max).all()
+    }
+
+    /// The eight corners of the box.
+    #[inline]
+    pub fn corners(&self) -> [Vec3; 8] {
+        let [min, max] = [self.min, self.max];
+
+        [
+            Vec3::new(min.x, min.y, min.z),
+            Vec3::new(max.x, min.y, min.z),
+            Vec3::new(min.x, max.y, min.z),
+            Vec3::new(max.x, max.y, min.z),
+            Vec3::new(min.x, min.y, max.z),
+            Vec3::new(max.x, min.y, max.z),
+            Vec3::new(min.x, max.y, max.z),
+            Vec3::new(max.x, max.y, max.z),
+        ]
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundingSphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    /// Sphere containing `points` found with Ritter's algorithm, it's at most a few percent
+    /// larger than the smallest one. `None` if there are no points.
+    #[inline]
+    pub fn from_points(points: &[Vec3]) -> Option<Self> {
+        let first = *points.first()?;
+
+        let farthest = |from: Vec3| {
+            points
+                .iter()
+                .copied()
+                .max_by(|a, b| {
+                    a.distance_squared(from)
+                        .total_cmp(&b.distance_squared(from))
+                })
+                .unwrap()
+        };
+
+        // start with the sphere around two points far apart
+        let a = farthest(first);
+        let b = farthest(a);
+
+        let mut sphere = Self {
+            center: (a + b) / 2.0,
+            radius: a.distance(b) / 2.0,
+        };
+
+        for &point in points {
+            let distance = point.distance(sphere.center);
+
+            if distance > sphere.radius {
+                let radius = (sphere.radius + distance) / 2.0;
+                sphere.center += (point - sphere.center) * ((radius - sphere.radius) / distance);
+                sphere.radius = radius;
+            }
+        }
+
+        Some(sphere)
+    }
+
+    #[inline]
+    pub fn contains(&self, point: Vec3) -> bool {
+        point.distance_squared(self.center) <= self.radius * self.radius
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounding_sphere_contains_points() {
+        let points = (0..200)
+            .map(|i| {
+                let i = i as f32;
+                Vec3::new((i * 0.37).sin() * 4.0, (i * 1.3).cos() * i * 0.05, i % 7.0 - 3.0)
+            })
+            .collect::<Vec<_>>();
+
+        let sphere = BoundingSphere::from_points(&points).unwrap();
+
+        for &point in &points {
+            // the sphere is grown with float math, so allow for rounding
+            assert!(point.distance(sphere.center) <= sphere.radius * 1.0001);
+        }
+
+        let aabb = Aabb::from_points(points.iter().copied()).unwrap();
+        assert!(sphere.radius <= aabb.half_extents().length() * 1.1);
+    }
+
+    #[test]
+    fn bounding_sphere_of_nothing() {
+        assert_eq!(BoundingSphere::from_points(&[]), None);
+    }
+}
@@ -1,5 +1,5 @@
 use bytemuck::bytes_of;
-use glam::Mat4;
+use glam::{Mat4, Vec3, Vec4};
 use nova_core::{component, Entity, IntoQuery, Resources, Runnable, SystemBuilder, World};
 use nova_transform::component::{GlobalTransform, Transform};
 use nova_wgpu::{Buffer, BufferInitDescriptor, BufferUsage, Instance};
@@ -18,6 +18,17 @@ pub fn camera_system() -> impl Runnable {
         })
 }
 
+/// Layout of the camera uniform, the position is used for specular lighting.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct CameraRaw {
+    pub view_proj: Mat4,
+    pub position: Vec4,
+}
+
+unsafe impl bytemuck::Zeroable for CameraRaw {}
+unsafe impl bytemuck::Pod for CameraRaw {}
+
 pub struct CameraNode;
 
 impl CameraNode {
@@ -36,8 +47,11 @@ impl RenderNode for CameraNode {
     ) {
         let camera_system = resources.get::<Cameras>().unwrap();
 
+        let mut position = Vec3::ZERO;
+
         let matrix = if let Some(main) = camera_system.main {
             let view = if let Ok(transform) = <&GlobalTransform>::query().get(world, main) {
+                position = transform.translation;
                 transform.matrix()
             } else {
                 Transform::IDENTITY.matrix()
@@ -60,13 +74,18 @@ impl RenderNode for CameraNode {
 
         data.insert(Self::MATRIX, matrix);
 
+        let raw = CameraRaw {
+            view_proj: matrix,
+            position: position.extend(1.0),
+        };
+
         let instance = resources.get::<Instance>().unwrap();
         if let Some(buffer) = data.get::<Buffer>(Self::BUFFER) {
-            instance.write_buffer(buffer, 0, bytes_of(&matrix));
+            instance.write_buffer(buffer, 0, bytes_of(&raw));
         } else {
             let buffer = instance.create_buffer_init(&BufferInitDescriptor {
                 label: Some("camera_buffer"),
-                contents: bytes_of(&matrix),
+                contents: bytes_of(&raw),
                 usage: BufferUsage::COPY_DST | BufferUsage::UNIFORM,
             });
 
@@ -0,0 +1,234 @@
+use glam::UVec2;
+use nova_assets::loader::{AssetError, AssetLoader, LoadContext};
+use nova_wgpu::*;
+use once_cell::sync::OnceCell;
+
+/// Texture, sampler and view of an [`Image`] on the gpu.
+pub struct GpuImage {
+    pub texture: Texture,
+    pub view: TextureView<'static>,
+    pub sampler: Sampler,
+}
+
+/// RGBA8 image sampled by shaders.
+///
+/// The texture is created the first time [`Image::gpu`] is called, editing the pixels
+/// afterwards requires [`Image::unload`] for the texture to be created again.
+pub struct Image {
+    pub size: UVec2,
+    /// Pixels of every mip level, the full size image first, each level half the size of
+    /// the previous one.
+    pub mips: Vec<Vec<u8>>,
+    /// Whether the pixels hold colors in sRGB, false for data like normal maps.
+    pub srgb: bool,
+    pub address_mode: AddressMode,
+    pub filter: FilterMode,
+    gpu: OnceCell<GpuImage>,
+}
+
+impl Image {
+    /// Creates an image without mip levels, `pixels` are RGBA8 in rows.
+    #[inline]
+    pub fn new(size: impl Into<UVec2>, pixels: Vec<u8>, srgb: bool) -> Self {
+        let size = size.into();
+        assert_eq!(
+            pixels.len(),
+            size.x as usize * size.y as usize * 4,
+            "pixels don't match the size of the image"
+        );
+
+        Self {
+            size,
+            mips: vec![pixels],
+            srgb,
+            address_mode: AddressMode::Repeat,
+            filter: FilterMode::Linear,
+            gpu: OnceCell::new(),
+        }
+    }
+
+    /// Decodes a png or jpeg image.
+    #[inline]
+    pub fn decode(bytes: &[u8], srgb: bool) -> Result<Self, AssetError> {
+        let image = ::image::load_from_memory(bytes)?.into_rgba8();
+        let size = UVec2::new(image.width(), image.height());
+
+        Ok(Self::new(size, image.into_raw(), srgb))
+    }
+
+    #[inline]
+    pub fn pixels(&self) -> &[u8] {
+        &self.mips[0]
+    }
+
+    #[inline]
+    pub fn mip_level_count(&self) -> u32 {
+        self.mips.len() as u32
+    }
+
+    #[inline]
+    pub fn format(&self) -> TextureFormat {
+        if self.srgb {
+            TextureFormat::Rgba8UnormSrgb
+        } else {
+            TextureFormat::Rgba8Unorm
+        }
+    }
+
+    /// Replaces the mip levels with ones averaged from the full size image down to 1x1.
+    /// sRGB colors are averaged in linear space.
+    #[inline]
+    pub fn generate_mipmaps(&mut self) {
+        self.mips.truncate(1);
+        self.unload();
+
+        let mut size = self.size;
+
+        while size.x > 1 || size.y > 1 {
+            let next = (size / 2).max(UVec2::ONE);
+            let mip = downsample(self.mips.last().unwrap(), size, next, self.srgb);
+
+            self.mips.push(mip);
+            size = next;
+        }
+    }
+
+    /// Drops the texture, it's created again by the next [`Image::gpu`].
+    #[inline]
+    pub fn unload(&mut self) {
+        self.gpu = OnceCell::new();
+    }
+
+    #[inline]
+    pub fn is_uploaded(&self) -> bool {
+        self.gpu.get().is_some()
+    }
+
+    /// The texture of the image, uploading it on first use.
+    #[inline]
+    pub fn gpu(&self, instance: &Instance) -> &GpuImage {
+        self.gpu.get_or_init(|| {
+            let texture = instance.create_texture_with_data(
+                &TextureDescriptor {
+                    label: Some("image"),
+                    size: Extent3d {
+                        width: self.size.x,
+                        height: self.size.y,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: self.mip_level_count(),
+                    sample_count: 1,
+                    dimension: TextureDimension::D2,
+                    format: self.format(),
+                    usage: TextureUsage::SAMPLED | TextureUsage::COPY_DST,
+                },
+                &self.mips.concat(),
+            );
+
+            let sampler = instance.create_sampler(&SamplerDescriptor {
+                label: Some("image"),
+                address_mode_u: self.address_mode,
+                address_mode_v: self.address_mode,
+                address_mode_w: self.address_mode,
+                mag_filter: self.filter,
+                min_filter: self.filter,
+                mipmap_filter: self.filter,
+                ..Default::default()
+            });
+
+            GpuImage {
+                view: texture.view(),
+                texture,
+                sampler,
+            }
+        })
+    }
+}
+
+#[inline]
+fn srgb_to_linear(value: u8) -> f32 {
+    let value = value as f32 / 255.0;
+
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+#[inline]
+fn linear_to_srgb(value: f32) -> u8 {
+    let value = if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    };
+
+    (value * 255.0).round().clamp(0.0, 255.0) as u8
+}
+
+/// Averages 2x2 blocks of `pixels`, odd edges reuse the last row or column.
+#[inline]
+fn downsample(pixels: &[u8], size: UVec2, next: UVec2, srgb: bool) -> Vec<u8> {
+    let mut mip = Vec::with_capacity(next.x as usize * next.y as usize * 4);
+
+    for y in 0..next.y {
+        for x in 0..next.x {
+            let mut sum = [0.0; 4];
+
+            for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                let sx = (x * 2 + dx).min(size.x - 1);
+                let sy = (y * 2 + dy).min(size.y - 1);
+                let index = (sy * size.x + sx) as usize * 4;
+
+                for (channel, sum) in sum.iter_mut().enumerate() {
+                    let value = pixels[index + channel];
+
+                    // alpha is always linear
+                    *sum += if srgb && channel < 3 {
+                        srgb_to_linear(value)
+                    } else {
+                        value as f32 / 255.0
+                    };
+                }
+            }
+
+            for (channel, sum) in sum.iter().enumerate() {
+                let average = sum / 4.0;
+
+                mip.push(if srgb && channel < 3 {
+                    linear_to_srgb(average)
+                } else {
+                    (average * 255.0).round() as u8
+                });
+            }
+        }
+    }
+
+    mip
+}
+
+/// Loads png and jpeg files as [`Image`]s, with the sRGB and mipmap choices of the
+/// [`TextureSettings`](nova_assets::meta::TextureSettings) of the asset.
+pub struct ImageLoader;
+
+impl AssetLoader for ImageLoader {
+    type Asset = Image;
+
+    #[inline]
+    fn extensions(&self) -> &[&str] {
+        &["png", "jpg", "jpeg"]
+    }
+
+    #[inline]
+    fn load(&self, bytes: &[u8], ctx: &mut LoadContext<'_>) -> Result<Image, AssetError> {
+        let settings = &ctx.settings().texture;
+        let mut image = Image::decode(bytes, settings.srgb)?;
+
+        if settings.mipmaps {
+            image.generate_mipmaps();
+        }
+
+        Ok(image)
+    }
+}
@@ -1,8 +1,10 @@
+pub mod bounds;
 pub mod camera;
 pub mod camera_node;
 pub mod color;
 pub mod component;
 pub mod depth_node;
+pub mod image;
 pub mod light;
 pub mod light_node;
 pub mod mesh;
@@ -18,7 +20,8 @@ pub mod vertex;
 use camera::{Camera, Cameras, MainCamera};
 use camera_node::camera_system;
 use component::MeshInstance;
-use light::{AmbientLight, PointLight};
+use crate::image::{Image, ImageLoader};
+use light::{AmbientLight, DirectionalLight, PointLight, SpotLight};
 use mesh::MeshData;
 use nova_assets::AssetsAppExt;
 use nova_core::{plugin::Plugin, stage::PRE_UPDATE, AppBuilder};
@@ -39,20 +42,34 @@ impl Plugin for RenderPlugin {
 
         renderer.add_default_nodes();
 
-        app.insert_resource(self.0)
-            .add_system_to_stage(PRE_UPDATE, camera_system())
+        // settings inserted before the plugin, e.g. from the project, take precedence
+        if !app.resources.contains::<RenderSettings>() {
+            app.insert_resource(self.0);
+        }
+
+        app.add_system_to_stage(PRE_UPDATE, camera_system())
             .register_asset::<RenderPipeline>()
             .register_asset::<MeshData>()
             .register_asset::<RenderTexture>()
+            .register_asset::<Image>()
+            .add_asset_loader(ImageLoader)
             .register_resource::<AmbientLight>()
             .insert_resource(renderer)
             .register_resource::<Cameras>()
             .register_component::<Camera>()
             .register_component::<MainCamera>()
             .register_component::<MeshInstance>()
-            .register_component::<PointLight>();
+            .register_component::<PointLight>()
+            .register_component::<DirectionalLight>()
+            .register_component::<SpotLight>();
 
         #[cfg(feature = "editor")]
-        app.add_editor_system_to_stage(PRE_UPDATE, camera_system());
+        app.add_editor_system_to_stage(PRE_UPDATE, camera_system())
+            .register_inspectable::<Camera>()
+            .register_inspectable::<MainCamera>()
+            .register_inspectable::<MeshInstance>()
+            .register_inspectable::<PointLight>()
+            .register_inspectable::<DirectionalLight>()
+            .register_inspectable::<SpotLight>();
     }
 }
@@ -1,12 +1,88 @@
 use crate::color::Color;
 
+use glam::Vec3;
 use nova_inspect::Inspectable;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize, Inspectable)]
+/// Point lights that can cast shadows at the same time, further ones are lit without.
+pub const MAX_POINT_SHADOWS: usize = 4;
+
+/// Written to the `shadow` of raw lights without a shadow map.
+pub const NO_SHADOW: i32 = -1;
+
+#[derive(Clone, Debug, Serialize, Deserialize, Inspectable)]
+#[serde(default)]
 pub struct PointLight {
     pub color: Color,
     pub intensity: f32,
+    /// Distance at which the light has faded out completely, `f32::INFINITY` for only
+    /// inverse square falloff.
+    pub range: f32,
+    /// Render a cube shadow map from the light, see [`MAX_POINT_SHADOWS`].
+    pub casts_shadows: bool,
+}
+
+impl Default for PointLight {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            intensity: 1.0,
+            range: 20.0,
+            casts_shadows: false,
+        }
+    }
+}
+
+/// Light from infinitely far away, like the sun, shining along the negative z axis of the
+/// entity.
+#[derive(Clone, Debug, Serialize, Deserialize, Inspectable)]
+#[serde(default)]
+pub struct DirectionalLight {
+    pub color: Color,
+    pub intensity: f32,
+    /// Render cascaded shadow maps from the light, only the first directional light with
+    /// shadows gets them.
+    pub casts_shadows: bool,
+}
+
+impl Default for DirectionalLight {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            intensity: 1.0,
+            casts_shadows: false,
+        }
+    }
+}
+
+/// Point light limited to a cone along the negative z axis of the entity.
+#[derive(Clone, Debug, Serialize, Deserialize, Inspectable)]
+#[serde(default)]
+pub struct SpotLight {
+    pub color: Color,
+    pub intensity: f32,
+    /// Distance at which the light has faded out completely, see [`PointLight::range`].
+    pub range: f32,
+    /// Angle from the axis in radians within which the light is at full intensity.
+    pub inner_angle: f32,
+    /// Angle from the axis in radians past which there's no light, the light fades
+    /// between the inner and outer angle.
+    pub outer_angle: f32,
+}
+
+impl Default for SpotLight {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            intensity: 1.0,
+            range: 20.0,
+            inner_angle: 0.0,
+            outer_angle: std::f32::consts::FRAC_PI_4,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Inspectable)]
@@ -30,8 +106,58 @@ impl Default for AmbientLight {
 pub struct PointLightRaw {
     pub position: [f32; 3],
     pub intensity: f32,
-    pub color: [f32; 4],
+    pub color: [f32; 3],
+    pub range: f32,
+    /// Index of the shadow map of the light or [`NO_SHADOW`].
+    pub shadow: i32,
+    pub _padding: [f32; 3],
 }
 
 unsafe impl bytemuck::Zeroable for PointLightRaw {}
 unsafe impl bytemuck::Pod for PointLightRaw {}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DirectionalLightRaw {
+    /// Direction the light shines in.
+    pub direction: [f32; 3],
+    pub intensity: f32,
+    pub color: [f32; 3],
+    /// Index of the shadow map of the light or [`NO_SHADOW`].
+    pub shadow: i32,
+}
+
+unsafe impl bytemuck::Zeroable for DirectionalLightRaw {}
+unsafe impl bytemuck::Pod for DirectionalLightRaw {}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SpotLightRaw {
+    pub position: [f32; 3],
+    pub intensity: f32,
+    pub color: [f32; 3],
+    pub range: f32,
+    pub direction: [f32; 3],
+    /// Cosine of the inner angle.
+    pub inner_cos: f32,
+    pub outer_cos: f32,
+    pub _padding: [f32; 3],
+}
+
+unsafe impl bytemuck::Zeroable for SpotLightRaw {}
+unsafe impl bytemuck::Pod for SpotLightRaw {}
+
+/// Point light with a shadow map.
+#[derive(Clone, Copy, Debug)]
+pub struct PointShadowCaster {
+    pub position: Vec3,
+    pub range: f32,
+}
+
+/// Lights the shadow maps are rendered from, indexed by the `shadow` of their raw lights.
+#[derive(Clone, Debug, Default)]
+pub struct ShadowCasters {
+    /// Direction of the directional light with shadows.
+    pub directional: Option<Vec3>,
+    pub point: Vec<PointShadowCaster>,
+}
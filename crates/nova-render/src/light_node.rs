@@ -1,20 +1,83 @@
-use bytemuck::{bytes_of, cast_slice};
+use bytemuck::{bytes_of, cast_slice, Pod};
+use glam::Vec3;
 use nova_core::{App, IntoQuery, Resources, World};
 use nova_transform::component::GlobalTransform;
 use nova_wgpu::{Buffer, BufferDescriptor, BufferUsage, Instance};
 
 use crate::{
-    light::{AmbientLight, PointLight, PointLightRaw},
+    light::{
+        AmbientLight, DirectionalLight, DirectionalLightRaw, PointLight, PointLightRaw,
+        PointShadowCaster, ShadowCasters, SpotLight, SpotLightRaw, MAX_POINT_SHADOWS,
+        NO_SHADOW,
+    },
     render_node::{RenderData, RenderNode, Target},
 };
 
+/// Lights the storage buffers have room for when they're first created.
+const MIN_CAPACITY: usize = 16;
+
+/// Capacity of a light storage buffer.
+#[derive(Default)]
+struct Capacity(usize);
+
+impl Capacity {
+    /// Writes `lights` to the storage buffer `name`, creating it with room for the next
+    /// power of two of lights if it's missing or too small. Returns whether it was created.
+    #[inline]
+    fn write<T: Pod>(
+        &mut self,
+        instance: &Instance,
+        data: &mut RenderData,
+        name: &'static str,
+        lights: &[T],
+    ) -> bool {
+        let reallocate = data.get::<Buffer>(name).is_none() || lights.len() > self.0;
+
+        if reallocate {
+            self.0 = lights.len().next_power_of_two().max(MIN_CAPACITY);
+
+            let buffer = instance.create_buffer(&BufferDescriptor {
+                label: Some(name),
+                size: (std::mem::size_of::<T>() * self.0) as u64,
+                usage: BufferUsage::COPY_DST | BufferUsage::STORAGE,
+                mapped_at_creation: false,
+            });
+
+            data.insert(name, buffer);
+        }
+
+        if !lights.is_empty() {
+            let buffer = data.get::<Buffer>(name).unwrap();
+            instance.write_buffer(buffer, 0, cast_slice(lights));
+        }
+
+        reallocate
+    }
+}
+
 #[derive(Default)]
 pub struct LightNode {
     pub point_lights: Vec<PointLightRaw>,
+    pub directional_lights: Vec<DirectionalLightRaw>,
+    pub spot_lights: Vec<SpotLightRaw>,
+    pub shadow_casters: ShadowCasters,
+    point_capacity: Capacity,
+    directional_capacity: Capacity,
+    spot_capacity: Capacity,
+    generation: u64,
 }
 
 impl LightNode {
+    /// Uniform buffer with the ambient light and the number of lights of each kind.
     pub const BUFFER: &'static str = "light_stage_buffer";
+    pub const POINT_LIGHTS_BUFFER: &'static str = "point_lights_buffer";
+    pub const DIRECTIONAL_LIGHTS_BUFFER: &'static str = "directional_lights_buffer";
+    pub const SPOT_LIGHTS_BUFFER: &'static str = "spot_lights_buffer";
+    /// `u64` incremented whenever the light buffers are created again, bind groups using
+    /// them have to be recreated when it changes.
+    pub const GENERATION: &'static str = "light_buffers_generation";
+    /// [`ShadowCasters`] of the frame.
+    pub const SHADOW_CASTERS: &'static str = "shadow_casters";
 }
 
 impl RenderNode for LightNode {
@@ -26,38 +89,120 @@ impl RenderNode for LightNode {
         data: &mut RenderData,
     ) {
         self.point_lights.clear();
+        self.directional_lights.clear();
+        self.spot_lights.clear();
+        self.shadow_casters.directional = None;
+        self.shadow_casters.point.clear();
 
         for (point_light, transform) in <(&PointLight, &GlobalTransform)>::query().iter(world) {
+            let shadow = if point_light.casts_shadows
+                && self.shadow_casters.point.len() < MAX_POINT_SHADOWS
+            {
+                self.shadow_casters.point.push(PointShadowCaster {
+                    position: transform.translation,
+                    range: point_light.range,
+                });
+
+                self.shadow_casters.point.len() as i32 - 1
+            } else {
+                NO_SHADOW
+            };
+
             self.point_lights.push(PointLightRaw {
                 position: transform.translation.into(),
                 intensity: point_light.intensity,
-                color: point_light.color.into(),
+                color: [point_light.color.r, point_light.color.g, point_light.color.b],
+                range: point_light.range,
+                shadow,
+                _padding: [0.0; 3],
+            });
+        }
+
+        for (directional_light, transform) in
+            <(&DirectionalLight, &GlobalTransform)>::query().iter(world)
+        {
+            let direction = transform.rotation * -Vec3::Z;
+
+            let shadow = if directional_light.casts_shadows
+                && self.shadow_casters.directional.is_none()
+            {
+                self.shadow_casters.directional = Some(direction);
+                0
+            } else {
+                NO_SHADOW
+            };
+
+            self.directional_lights.push(DirectionalLightRaw {
+                direction: direction.into(),
+                intensity: directional_light.intensity,
+                color: [
+                    directional_light.color.r,
+                    directional_light.color.g,
+                    directional_light.color.b,
+                ],
+                shadow,
+            });
+        }
+
+        for (spot_light, transform) in <(&SpotLight, &GlobalTransform)>::query().iter(world) {
+            self.spot_lights.push(SpotLightRaw {
+                position: transform.translation.into(),
+                intensity: spot_light.intensity,
+                color: [spot_light.color.r, spot_light.color.g, spot_light.color.b],
+                range: spot_light.range,
+                direction: (transform.rotation * -Vec3::Z).into(),
+                inner_cos: spot_light.inner_angle.cos(),
+                outer_cos: spot_light.outer_angle.cos(),
+                _padding: [0.0; 3],
             });
         }
 
         let ambient = resources.get::<AmbientLight>().unwrap();
         let instance = resources.get::<Instance>().unwrap();
 
-        let mut point_light_data = bytes_of(&ambient.color).to_vec();
-        point_light_data.append(&mut bytes_of(&ambient.intensity).to_vec());
-        point_light_data.append(&mut bytes_of(&(self.point_lights.len() as u32)).to_vec());
-        point_light_data.append(&mut vec![0u8; 8]);
-
-        point_light_data.append(&mut cast_slice(&self.point_lights).to_vec());
+        let mut light_data = bytes_of(&ambient.color).to_vec();
+        light_data.extend_from_slice(bytes_of(&ambient.intensity));
+        light_data.extend_from_slice(bytes_of(&(self.point_lights.len() as u32)));
+        light_data.extend_from_slice(bytes_of(&(self.directional_lights.len() as u32)));
+        light_data.extend_from_slice(bytes_of(&(self.spot_lights.len() as u32)));
 
         if let Some(buffer) = data.get::<Buffer>(Self::BUFFER) {
-            instance.write_buffer(buffer, 0, &point_light_data);
+            instance.write_buffer(buffer, 0, &light_data);
         } else {
             let buffer = instance.create_buffer(&BufferDescriptor {
-                label: Some("point_lights"),
-                size: 16 + std::mem::size_of::<PointLightRaw>() as u64 * 64,
+                label: Some("lights"),
+                size: light_data.len() as u64,
                 usage: BufferUsage::COPY_DST | BufferUsage::UNIFORM,
                 mapped_at_creation: false,
             });
 
-            instance.write_buffer(&buffer, 0, &point_light_data);
+            instance.write_buffer(&buffer, 0, &light_data);
 
             data.insert(Self::BUFFER, buffer);
         };
+
+        let reallocated = self.point_capacity.write(
+            &instance,
+            data,
+            Self::POINT_LIGHTS_BUFFER,
+            &self.point_lights,
+        ) | self.directional_capacity.write(
+            &instance,
+            data,
+            Self::DIRECTIONAL_LIGHTS_BUFFER,
+            &self.directional_lights,
+        ) | self.spot_capacity.write(
+            &instance,
+            data,
+            Self::SPOT_LIGHTS_BUFFER,
+            &self.spot_lights,
+        );
+
+        if reallocated {
+            self.generation += 1;
+        }
+
+        data.insert(Self::GENERATION, self.generation);
+        data.insert(Self::SHADOW_CASTERS, self.shadow_casters.clone());
     }
 }
@@ -1,14 +1,18 @@
-use bytemuck::{cast_slice, Pod};
-use nova_wgpu::Buffer;
+use std::collections::HashMap;
+
+use bevy_mikktspace::Geometry;
+use bytemuck::{bytes_of, cast_slice, Pod};
+use glam::{Mat3, Mat4, Vec3, Vec4};
+
+use crate::{
+    bounds::{Aabb, BoundingSphere},
+    vertex::{VertexNormal, VertexPosition, VertexUv},
+};
 
 #[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct MeshData {
     pub vertices: Vec<u8>,
     pub indices: Vec<u32>,
-    #[cfg_attr(feature = "serialize", serde(skip))]
-    pub vertex_buffer: Option<Buffer>,
-    #[cfg_attr(feature = "serialize", serde(skip))]
-    pub index_buffer: Option<Buffer>,
 }
 
 #[derive(Clone, Debug)]
@@ -32,8 +36,6 @@ impl<V: Pod> From<Mesh<V>> for MeshData {
         MeshData {
             vertices: cast_slice(&mesh.vertices).to_vec(),
             indices: mesh.indices,
-            vertex_buffer: None,
-            index_buffer: None,
         }
     }
 }
@@ -43,8 +45,637 @@ impl<V: Pod> From<&Mesh<V>> for MeshData {
         MeshData {
             vertices: cast_slice(&mesh.vertices).to_vec(),
             indices: mesh.indices.clone(),
-            vertex_buffer: None,
-            index_buffer: None,
+        }
+    }
+}
+
+/// Size of the post transform cache [`Mesh::optimize_vertex_cache`] optimizes for.
+const CACHE_SIZE: usize = 32;
+
+/// Score of a vertex in Forsyth's algorithm, vertices recently used and vertices with few
+/// triangles left are preferred.
+#[inline]
+fn vertex_score(cache_position: Option<usize>, remaining: u32) -> f32 {
+    if remaining == 0 {
+        return -1.0;
+    }
+
+    let cache = match cache_position {
+        // the vertices of the last triangle are all in the cache whatever the order
+        Some(position) if position < 3 => 0.75,
+        Some(position) => (1.0 - (position - 3) as f32 / (CACHE_SIZE - 3) as f32).powf(1.5),
+        None => 0.0,
+    };
+
+    cache + 2.0 / (remaining as f32).sqrt()
+}
+
+impl<V> Mesh<V> {
+    #[inline]
+    pub fn triangles(&self) -> impl Iterator<Item = [u32; 3]> + '_ {
+        self.indices
+            .chunks_exact(3)
+            .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+    }
+
+    /// Converts the vertices with `f`, e.g. to another vertex type.
+    #[inline]
+    pub fn map<U>(self, f: impl FnMut(V) -> U) -> Mesh<U> {
+        Mesh {
+            vertices: self.vertices.into_iter().map(f).collect(),
+            indices: self.indices,
+        }
+    }
+
+    /// Replaces the vertices with the ones at `order`, `remap` is the new index of every
+    /// vertex.
+    #[inline]
+    fn reorder_vertices(&mut self, order: &[u32], remap: &[u32]) {
+        let mut vertices = std::mem::take(&mut self.vertices)
+            .into_iter()
+            .map(Some)
+            .collect::<Vec<_>>();
+
+        self.vertices = order
+            .iter()
+            .map(|&index| vertices[index as usize].take().unwrap())
+            .collect();
+
+        for index in &mut self.indices {
+            *index = remap[*index as usize];
+        }
+    }
+
+    /// Reorders the vertices in the order the triangles first use them, so they're read
+    /// sequentially. Vertices no triangle uses are removed.
+    #[inline]
+    pub fn optimize_vertex_fetch(&mut self) {
+        let mut remap = vec![u32::MAX; self.vertices.len()];
+        let mut order = Vec::new();
+
+        for &index in &self.indices {
+            if remap[index as usize] == u32::MAX {
+                remap[index as usize] = order.len() as u32;
+                order.push(index);
+            }
+        }
+
+        self.reorder_vertices(&order, &remap);
+    }
+
+    /// Removes vertices no triangle uses, keeping the order of the others.
+    #[inline]
+    pub fn remove_unused_vertices(&mut self) {
+        let mut used = vec![false; self.vertices.len()];
+
+        for &index in &self.indices {
+            used[index as usize] = true;
+        }
+
+        let mut remap = vec![u32::MAX; self.vertices.len()];
+        let mut order = Vec::new();
+
+        for (index, _) in used.iter().enumerate().filter(|(_, used)| **used) {
+            remap[index] = order.len() as u32;
+            order.push(index as u32);
+        }
+
+        self.reorder_vertices(&order, &remap);
+    }
+
+    /// Points the indices to `remap` of the vertex they point to, drops the triangles that
+    /// collapse and the vertices that aren't used anymore.
+    #[inline]
+    fn merge_vertices(&mut self, remap: &[u32]) {
+        let indices = std::mem::take(&mut self.indices);
+
+        for triangle in indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| remap[triangle[i] as usize]);
+
+            if a != b && b != c && c != a {
+                self.indices.extend_from_slice(&[a, b, c]);
+            }
+        }
+
+        self.remove_unused_vertices();
+    }
+
+    /// Reorders the triangles so vertices are reused while they're in the post transform
+    /// cache of the gpu, with Forsyth's algorithm, then the vertices with
+    /// [`Mesh::optimize_vertex_fetch`].
+    #[inline]
+    pub fn optimize_vertex_cache(&mut self) {
+        let triangle_count = self.indices.len() / 3;
+        let vertex_count = self.vertices.len();
+
+        let mut vertex_triangles = vec![Vec::new(); vertex_count];
+        for (triangle, indices) in self.indices.chunks_exact(3).enumerate() {
+            for &index in indices {
+                vertex_triangles[index as usize].push(triangle);
+            }
+        }
+
+        let mut remaining = vertex_triangles
+            .iter()
+            .map(|triangles| triangles.len() as u32)
+            .collect::<Vec<_>>();
+        let mut cache_positions = vec![None; vertex_count];
+        let mut vertex_scores = remaining
+            .iter()
+            .map(|&remaining| vertex_score(None, remaining))
+            .collect::<Vec<_>>();
+
+        let indices = &self.indices;
+        let triangle_score = |triangle: usize, scores: &[f32]| -> f32 {
+            indices[triangle * 3..triangle * 3 + 3]
+                .iter()
+                .map(|&index| scores[index as usize])
+                .sum()
+        };
+
+        let mut triangle_scores = (0..triangle_count)
+            .map(|triangle| triangle_score(triangle, &vertex_scores))
+            .collect::<Vec<_>>();
+        let mut emitted = vec![false; triangle_count];
+        let mut next_unemitted = 0;
+
+        let mut cache: Vec<u32> = Vec::new();
+        let mut optimized = Vec::with_capacity(indices.len());
+
+        for _ in 0..triangle_count {
+            // the best triangle using a cached vertex, or the next one left
+            let best = cache
+                .iter()
+                .flat_map(|&index| &vertex_triangles[index as usize])
+                .copied()
+                .filter(|&triangle| !emitted[triangle])
+                .max_by(|&a, &b| triangle_scores[a].total_cmp(&triangle_scores[b]));
+
+            let best = match best {
+                Some(best) => best,
+                None => {
+                    while emitted[next_unemitted] {
+                        next_unemitted += 1;
+                    }
+
+                    next_unemitted
+                }
+            };
+
+            emitted[best] = true;
+
+            let triangle = [0, 1, 2].map(|i| indices[best * 3 + i]);
+            optimized.extend_from_slice(&triangle);
+
+            for &index in &triangle {
+                remaining[index as usize] -= 1;
+            }
+
+            // the vertices of the triangle move to the front of the cache
+            let mut new_cache = triangle.to_vec();
+            new_cache.extend(cache.iter().filter(|index| !triangle.contains(index)));
+
+            let evicted = if new_cache.len() > CACHE_SIZE {
+                new_cache.split_off(CACHE_SIZE)
+            } else {
+                Vec::new()
+            };
+
+            for &index in &evicted {
+                cache_positions[index as usize] = None;
+            }
+
+            for (position, &index) in new_cache.iter().enumerate() {
+                cache_positions[index as usize] = Some(position);
+            }
+
+            cache = new_cache;
+
+            for &index in cache.iter().chain(&evicted) {
+                let index = index as usize;
+                vertex_scores[index] = vertex_score(cache_positions[index], remaining[index]);
+            }
+
+            for &index in cache.iter().chain(&evicted) {
+                for &triangle in &vertex_triangles[index as usize] {
+                    if !emitted[triangle] {
+                        triangle_scores[triangle] = triangle_score(triangle, &vertex_scores);
+                    }
+                }
+            }
+        }
+
+        self.indices = optimized;
+        self.optimize_vertex_fetch();
+    }
+}
+
+impl<V: Pod> Mesh<V> {
+    /// Merges vertices that are exactly the same.
+    #[inline]
+    pub fn deduplicate(&mut self) {
+        let mut first = HashMap::new();
+
+        let remap = self
+            .vertices
+            .iter()
+            .enumerate()
+            .map(|(index, vertex)| *first.entry(bytes_of(vertex)).or_insert(index as u32))
+            .collect::<Vec<_>>();
+
+        self.merge_vertices(&remap);
+    }
+}
+
+impl<V: VertexPosition> Mesh<V> {
+    /// Bounding box of the vertices, `None` if there are none.
+    #[inline]
+    pub fn bounding_box(&self) -> Option<Aabb> {
+        Aabb::from_points(self.vertices.iter().map(VertexPosition::position))
+    }
+
+    /// Bounding sphere of the vertices, see [`BoundingSphere::from_points`].
+    #[inline]
+    pub fn bounding_sphere(&self) -> Option<BoundingSphere> {
+        let positions = self
+            .vertices
+            .iter()
+            .map(VertexPosition::position)
+            .collect::<Vec<_>>();
+
+        BoundingSphere::from_points(&positions)
+    }
+
+    /// Merges vertices closer than `tolerance` into the first of them, whatever their other
+    /// attributes, and drops the triangles that collapse. `tolerance` must be positive, see
+    /// [`Mesh::deduplicate`] for exact matches.
+    #[inline]
+    pub fn weld(&mut self, tolerance: f32) {
+        assert!(tolerance > 0.0, "weld tolerance must be positive");
+
+        // vertices are bucketed in cells of the tolerance, so matches are in the 27 cells
+        // around a vertex
+        let cell = |position: Vec3| (position / tolerance).floor().as_i32().to_array();
+
+        let mut cells: HashMap<[i32; 3], Vec<u32>> = HashMap::new();
+        let mut remap = Vec::with_capacity(self.vertices.len());
+
+        for (index, vertex) in self.vertices.iter().enumerate() {
+            let position = vertex.position();
+            let [x, y, z] = cell(position);
+
+            let mut found = None;
+
+            'search: for dx in -1..=1 {
+                for dy in -1..=1 {
+                    for dz in -1..=1 {
+                        let neighbours = match cells.get(&[x + dx, y + dy, z + dz]) {
+                            Some(neighbours) => neighbours,
+                            None => continue,
+                        };
+
+                        found = neighbours.iter().copied().find(|&other| {
+                            self.vertices[other as usize].position().distance(position) <= tolerance
+                        });
+
+                        if found.is_some() {
+                            break 'search;
+                        }
+                    }
+                }
+            }
+
+            remap.push(found.unwrap_or_else(|| {
+                cells.entry([x, y, z]).or_default().push(index as u32);
+                index as u32
+            }));
+        }
+
+        self.merge_vertices(&remap);
+    }
+}
+
+#[inline]
+fn face_normal(positions: [Vec3; 3]) -> Vec3 {
+    let [a, b, c] = positions;
+
+    (b - a).cross(c - a)
+}
+
+impl<V: VertexPosition + VertexNormal + Clone> Mesh<V> {
+    /// Sets the normals to the average of the triangles sharing the position of each
+    /// vertex, weighted by area, so normals are smooth across uv seams.
+    #[inline]
+    pub fn compute_smooth_normals(&mut self) {
+        let key = |position: Vec3| position.to_array().map(f32::to_bits);
+        let mut normals: HashMap<[u32; 3], Vec3> = HashMap::new();
+
+        for triangle in self.triangles() {
+            let positions = triangle.map(|index| self.vertices[index as usize].position());
+            // not normalized so larger triangles weigh more
+            let normal = face_normal(positions);
+
+            for position in positions {
+                *normals.entry(key(position)).or_default() += normal;
+            }
+        }
+
+        for vertex in &mut self.vertices {
+            let normal = normals
+                .get(&key(vertex.position()))
+                .map_or(Vec3::ZERO, |normal| normal.normalize_or_zero());
+
+            vertex.set_normal(normal);
+        }
+    }
+
+    /// Gives every triangle its own vertices with the normal of the triangle, for hard
+    /// edges.
+    #[inline]
+    pub fn compute_flat_normals(&mut self) {
+        let mut vertices = Vec::with_capacity(self.indices.len());
+
+        for triangle in self.triangles() {
+            let triangle = triangle.map(|index| &self.vertices[index as usize]);
+            let normal = face_normal(triangle.map(VertexPosition::position)).normalize_or_zero();
+
+            for vertex in triangle {
+                let mut vertex = vertex.clone();
+                vertex.set_normal(normal);
+                vertices.push(vertex);
+            }
+        }
+
+        self.indices = (0..vertices.len() as u32).collect();
+        self.vertices = vertices;
+    }
+
+    /// Transforms the positions and normals by `transform`.
+    #[inline]
+    pub fn transform(&mut self, transform: Mat4) {
+        let normal_matrix = Mat3::from_mat4(transform).inverse().transpose();
+
+        for vertex in &mut self.vertices {
+            vertex.set_position(transform.transform_point3(vertex.position()));
+            vertex.set_normal((normal_matrix * vertex.normal()).normalize_or_zero());
+        }
+
+        // mirroring turns the triangles inside out
+        if transform.determinant() < 0.0 {
+            for triangle in self.indices.chunks_exact_mut(3) {
+                triangle.swap(1, 2);
+            }
+        }
+    }
+
+    /// Adds the triangles of `other` transformed by `transform`.
+    #[inline]
+    pub fn merge(&mut self, other: &Self, transform: Mat4) {
+        let mut other = other.clone();
+        other.transform(transform);
+
+        let offset = self.vertices.len() as u32;
+
+        self.vertices.extend(other.vertices);
+        self.indices
+            .extend(other.indices.iter().map(|index| index + offset));
+    }
+
+    /// Merges `meshes` into one, see [`Mesh::merge`].
+    #[inline]
+    pub fn merged<'a>(meshes: impl IntoIterator<Item = (&'a Self, Mat4)>) -> Self
+    where
+        V: 'a,
+    {
+        let mut merged = Self::default();
+
+        for (mesh, transform) in meshes {
+            merged.merge(mesh, transform);
+        }
+
+        merged
+    }
+}
+
+struct TangentGeometry<'a, V> {
+    mesh: &'a Mesh<V>,
+    tangents: Vec<Vec4>,
+}
+
+impl<'a, V> TangentGeometry<'a, V> {
+    #[inline]
+    fn vertex(&self, face: usize, vert: usize) -> &V {
+        &self.mesh.vertices[self.mesh.indices[face * 3 + vert] as usize]
+    }
+}
+
+impl<'a, V: VertexPosition + VertexNormal + VertexUv> Geometry for TangentGeometry<'a, V> {
+    #[inline]
+    fn num_faces(&self) -> usize {
+        self.mesh.indices.len() / 3
+    }
+
+    #[inline]
+    fn num_vertices_of_face(&self, _face: usize) -> usize {
+        3
+    }
+
+    #[inline]
+    fn position(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.vertex(face, vert).position().to_array()
+    }
+
+    #[inline]
+    fn normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.vertex(face, vert).normal().to_array()
+    }
+
+    #[inline]
+    fn tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        self.vertex(face, vert).uv().to_array()
+    }
+
+    #[inline]
+    fn set_tangent_encoded(&mut self, tangent: [f32; 4], face: usize, vert: usize) {
+        let index = self.mesh.indices[face * 3 + vert] as usize;
+        self.tangents[index] = Vec4::from(tangent);
+    }
+}
+
+impl<V: VertexPosition + VertexNormal + VertexUv> Mesh<V> {
+    /// MikkTSpace tangents of the vertices for normal mapping, with the sign of the
+    /// bitangent in w. `None` if the mesh has no triangles.
+    ///
+    /// A vertex shared by triangles whose tangents differ gets one of them, so vertices
+    /// should be split along uv seams like the normals.
+    #[inline]
+    pub fn generate_tangents(&self) -> Option<Vec<Vec4>> {
+        let mut geometry = TangentGeometry {
+            mesh: self,
+            tangents: vec![Vec4::ZERO; self.vertices.len()],
+        };
+
+        if self.indices.len() < 3 || !bevy_mikktspace::generate_tangents(&mut geometry) {
+            return None;
+        }
+
+        Some(geometry.tangents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Position(Vec3);
+
+    unsafe impl bytemuck::Zeroable for Position {}
+    unsafe impl Pod for Position {}
+
+    impl VertexPosition for Position {
+        fn position(&self) -> Vec3 {
+            self.0
+        }
+
+        fn set_position(&mut self, position: Vec3) {
+            self.0 = position;
+        }
+    }
+
+    fn mesh(positions: &[[f32; 3]], indices: &[u32]) -> Mesh<Position> {
+        Mesh {
+            vertices: positions
+                .iter()
+                .map(|&position| Position(position.into()))
+                .collect(),
+            indices: indices.to_vec(),
+        }
+    }
+
+    /// Triangles as positions, rotated so the smallest position comes first.
+    fn triangle_set(mesh: &Mesh<Position>) -> HashSet<[[u32; 3]; 3]> {
+        mesh.triangles()
+            .map(|triangle| {
+                let positions = triangle
+                    .map(|index| mesh.vertices[index as usize].0.to_array().map(f32::to_bits));
+                let first = (0..3).min_by_key(|&i| positions[i]).unwrap();
+
+                [0, 1, 2].map(|i| positions[(first + i) % 3])
+            })
+            .collect()
+    }
+
+    /// Grid of `size` by `size` quads.
+    fn grid(size: u32) -> Mesh<Position> {
+        let mut positions = Vec::new();
+        let mut indices = Vec::new();
+
+        for y in 0..=size {
+            for x in 0..=size {
+                positions.push([x as f32, y as f32, 0.0]);
+            }
+        }
+
+        for y in 0..size {
+            for x in 0..size {
+                let a = y * (size + 1) + x;
+                let b = a + size + 1;
+
+                indices.extend_from_slice(&[a, a + 1, b + 1, a, b + 1, b]);
+            }
+        }
+
+        mesh(&positions, &indices)
+    }
+
+    #[test]
+    fn weld_collapses_triangles() {
+        // the second triangle has two vertices within the tolerance, the last vertex is unused
+        let mut mesh = mesh(
+            &[
+                [0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0],
+                [1.0, 0.001, 0.0],
+                [0.0, 1.0, 1.0],
+                [5.0, 5.0, 5.0],
+            ],
+            &[0, 1, 2, 1, 3, 4, 2, 3, 4],
+        );
+
+        mesh.weld(0.01);
+
+        assert_eq!(mesh.vertices.len(), 4);
+        assert_eq!(mesh.indices, [0, 1, 2, 2, 1, 3]);
+        assert_eq!(mesh.vertices[3], Position(Vec3::new(0.0, 1.0, 1.0)));
+    }
+
+    #[test]
+    fn weld_merges_across_cells() {
+        // the vertices are in different cells of the tolerance but still close enough
+        let mut mesh = mesh(
+            &[
+                [0.099, 0.0, 0.0],
+                [0.101, 0.0, 0.0],
+                [0.0, 1.0, 0.0],
+                [1.0, 0.0, 0.0],
+            ],
+            &[0, 3, 2, 1, 2, 0],
+        );
+
+        mesh.weld(0.01);
+
+        assert_eq!(mesh.vertices.len(), 3);
+        assert_eq!(mesh.indices, [0, 2, 1]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn weld_zero_tolerance() {
+        grid(1).weld(0.0);
+    }
+
+    #[test]
+    fn deduplicate_removes_unused_vertices() {
+        let mut mesh = mesh(
+            &[
+                [0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [2.0, 2.0, 2.0],
+                [0.0, 1.0, 0.0],
+                [1.0, 0.0, 0.0],
+            ],
+            &[0, 1, 3, 4, 3, 0],
+        );
+
+        mesh.deduplicate();
+
+        assert_eq!(mesh.vertices.len(), 3);
+        assert_eq!(mesh.indices, [0, 1, 2, 1, 2, 0]);
+    }
+
+    #[test]
+    fn optimize_vertex_cache_keeps_triangles() {
+        let mut mesh = grid(8);
+        mesh.indices.reverse();
+
+        let triangles = triangle_set(&mesh);
+        let vertex_count = mesh.vertices.len();
+
+        mesh.optimize_vertex_cache();
+
+        assert_eq!(mesh.indices.len(), 8 * 8 * 6);
+        assert_eq!(mesh.vertices.len(), vertex_count);
+        assert_eq!(triangle_set(&mesh), triangles);
+
+        // vertices are in the order the triangles first use them
+        let mut next = 0;
+        for &index in &mesh.indices {
+            assert!(index <= next);
+            next = next.max(index + 1);
         }
     }
 }
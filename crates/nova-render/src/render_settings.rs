@@ -1,6 +1,6 @@
 use crate::color::Color;
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 #[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct RenderSettings {
     pub msaa: u32,
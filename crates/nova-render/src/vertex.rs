@@ -7,6 +7,23 @@ pub trait Vertex {
     fn layout() -> VertexBufferLayout<'static>;
 }
 
+/// Vertices with a position, for the operations on [`Mesh`](crate::mesh::Mesh).
+pub trait VertexPosition {
+    fn position(&self) -> Vec3;
+
+    fn set_position(&mut self, position: Vec3);
+}
+
+pub trait VertexNormal {
+    fn normal(&self) -> Vec3;
+
+    fn set_normal(&mut self, normal: Vec3);
+}
+
+pub trait VertexUv {
+    fn uv(&self) -> Vec2;
+}
+
 pub trait AsVertexFormat: Sized {
     const FORMAT: VertexFormat;
     const SIZE: u64 = std::mem::size_of::<Self>() as u64;
@@ -16,6 +16,8 @@ impl Plugin for TransformPlugin {
             .register_component::<Parent>();
 
         #[cfg(feature = "editor")]
-        app.add_editor_system_to_stage(stage::PRE_UPDATE, child_system());
+        app.add_editor_system_to_stage(stage::PRE_UPDATE, child_system())
+            .register_inspectable::<Transform>()
+            .register_inspectable::<GlobalTransform>();
     }
 }
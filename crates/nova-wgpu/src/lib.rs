@@ -28,5 +28,7 @@ pub use render_pass::{
 };
 pub use sampler::{Sampler, SamplerDescriptor};
 pub use swapchain::{SwapChain, SwapChainTrait};
-pub use texture::{SwapChainError, SwapChainFrame, SwapChainTexture, Texture, TextureView};
+pub use texture::{
+    SwapChainError, SwapChainFrame, SwapChainTexture, Texture, TextureView, TextureViewDescriptor,
+};
 pub use wgpu_types::*;
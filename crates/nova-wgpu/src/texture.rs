@@ -1,8 +1,45 @@
-use std::{any::Any, fmt::Debug, sync::Arc};
+use std::{any::Any, fmt::Debug, num::NonZeroU32, sync::Arc};
+
+use wgpu_types::{TextureAspect, TextureFormat, TextureViewDimension};
+
+/// Describes a view of part of a texture, e.g. a single layer of an array texture.
+#[derive(Clone, Debug)]
+pub struct TextureViewDescriptor<'a> {
+    pub label: Option<&'a str>,
+    /// Format of the texture if `None`.
+    pub format: Option<TextureFormat>,
+    /// Dimension of the texture if `None`.
+    pub dimension: Option<TextureViewDimension>,
+    pub aspect: TextureAspect,
+    pub base_mip_level: u32,
+    /// The remaining levels if `None`.
+    pub mip_level_count: Option<NonZeroU32>,
+    pub base_array_layer: u32,
+    /// The remaining layers if `None`.
+    pub array_layer_count: Option<NonZeroU32>,
+}
+
+impl<'a> Default for TextureViewDescriptor<'a> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            label: None,
+            format: None,
+            dimension: None,
+            aspect: TextureAspect::All,
+            base_mip_level: 0,
+            mip_level_count: None,
+            base_array_layer: 0,
+            array_layer_count: None,
+        }
+    }
+}
 
 pub trait TextureTrait: Debug + Send + Sync {
     fn view(&self) -> TextureView<'static>;
 
+    fn create_view(&self, desc: &TextureViewDescriptor) -> TextureView<'static>;
+
     fn any(&self) -> &dyn Any;
 }
 
@@ -14,6 +51,11 @@ impl Texture {
     pub fn view(&self) -> TextureView<'static> {
         self.0.view()
     }
+
+    #[inline]
+    pub fn create_view(&self, desc: &TextureViewDescriptor) -> TextureView<'static> {
+        self.0.create_view(desc)
+    }
 }
 
 pub trait SwapChainTextureTrait {
@@ -325,16 +325,24 @@ fn downcast_buffer_slice<'a, 'b>(slice: BufferSlice<'a>) -> wgpu::BufferSlice<'a
 impl TextureTrait for wgpu::Texture {
     #[inline]
     fn view(&self) -> TextureView<'static> {
-        TextureView::Owned(Arc::new(self.create_view(&wgpu::TextureViewDescriptor {
-            label: None,
-            format: None,
-            dimension: None,
-            aspect: wgpu::TextureAspect::All,
-            array_layer_count: None,
-            base_array_layer: 0,
-            mip_level_count: None,
-            base_mip_level: 0,
-        })))
+        TextureTrait::create_view(self, &TextureViewDescriptor::default())
+    }
+
+    #[inline]
+    fn create_view(&self, desc: &TextureViewDescriptor) -> TextureView<'static> {
+        TextureView::Owned(Arc::new(wgpu::Texture::create_view(
+            self,
+            &wgpu::TextureViewDescriptor {
+                label: desc.label,
+                format: desc.format,
+                dimension: desc.dimension,
+                aspect: desc.aspect,
+                array_layer_count: desc.array_layer_count,
+                base_array_layer: desc.base_array_layer,
+                mip_level_count: desc.mip_level_count,
+                base_mip_level: desc.base_mip_level,
+            },
+        )))
     }
 
     #[inline]
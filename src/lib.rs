@@ -1,20 +1,34 @@
 pub use nova_core as core;
 #[doc(hidden)]
+pub use nova_engine;
+#[doc(hidden)]
 pub use nova_inspect;
 
 pub mod prelude {
     pub use crate::export_app;
     pub use glam::{swizzles::*, *};
-    pub use nova_assets::{Assets, Handle};
+    pub use nova_assets::{
+        event::{AssetEvent, AssetEventReader},
+        loader::{AssetError, AssetLoader, LoadContext},
+        meta::{AssetMeta, ImportSettings},
+        server::{AssetServer, LoadProgress, LoadState},
+        Assets, AssetsAppExt, Handle, Uuid, WeakHandle,
+    };
     pub use nova_core::*;
     pub use nova_derive::{Inspectable, Vertex};
-    pub use nova_input::{key::Key, mouse_button::MouseButton, Input, InputPlugin};
+    pub use nova_input::{
+        bindings::{Binding, Bindings},
+        key::Key,
+        mouse_button::MouseButton,
+        Input, InputPlugin,
+    };
     pub use nova_inspect::*;
     pub use nova_render::{
         camera::{Camera, Cameras, MainCamera},
         color::Color,
         component::MeshInstance,
-        light::{AmbientLight, PointLight},
+        image::Image,
+        light::{AmbientLight, DirectionalLight, PointLight, SpotLight},
         mesh::{Mesh, MeshData},
         render_commands::RenderCommands,
         render_node::{RenderData, RenderNode, Target},
@@ -39,6 +53,16 @@ macro_rules! export_app {
         mod __nova_extern__ {
             use super::*;
 
+            #[no_mangle]
+            pub extern "C" fn nova_abi() -> $crate::core::abi::AbiDescriptor {
+                // must match the descriptor the editor expects in `load.rs`
+                $crate::core::abi::AbiDescriptor::current()
+                    .with_layout::<$crate::prelude::wgpu::Instance>()
+                    .with_layout::<$crate::prelude::RenderTarget>()
+                    .with_layout::<$crate::prelude::Target<'static>>()
+                    .with_layout::<$crate::nova_engine::RunSettings>()
+            }
+
             #[no_mangle]
             pub unsafe fn render_view(
                 world: &mut $crate::prelude::World,
@@ -54,12 +78,34 @@ macro_rules! export_app {
                 mut app: $crate::prelude::AppBuilder,
                 instance: $crate::prelude::wgpu::Instance,
                 render_target: $crate::prelude::RenderTarget,
+                settings: &$crate::nova_engine::RunSettings,
             ) -> $crate::prelude::App {
                 app.insert_resource(instance);
                 app.insert_resource(render_target);
+                $crate::nova_engine::insert_settings(&mut app, settings);
                 $expr(&mut app);
                 app.build()
             }
+
+            #[no_mangle]
+            pub unsafe fn run_app(
+                settings: $crate::nova_engine::RunSettings,
+                load_scene: &mut dyn FnMut(
+                    &mut $crate::prelude::App,
+                ) -> Result<(), Box<dyn std::error::Error>>,
+            ) -> ! {
+                $crate::nova_engine::run_with_settings(settings, |mut app| {
+                    $expr(&mut app);
+                    let mut app = app.build();
+
+                    if let Err(err) = load_scene(&mut app) {
+                        eprintln!("failed to load scene: {}", err);
+                        std::process::exit(1);
+                    }
+
+                    app
+                })
+            }
         }
     };
 }